@@ -0,0 +1,216 @@
+// Boost/Apache2 License
+
+//! Keyboard accelerator tables.
+
+use crate::client::Client;
+use crate::keyboard::{KeyModifiers, VirtualKey};
+use crate::Error;
+
+use alloc::vec::Vec;
+
+use core::cell::Cell;
+#[cfg(feature = "std")]
+use core::cell::RefCell;
+use core::marker::PhantomData;
+#[cfg(feature = "std")]
+use core::mem;
+
+use windows_sys::Win32::Foundation::ERROR_INVALID_PARAMETER;
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    CreateAcceleratorTableA, DestroyAcceleratorTable,
+};
+#[cfg(feature = "std")]
+use windows_sys::Win32::UI::WindowsAndMessaging::{GetAncestor, TranslateAcceleratorW, GA_ROOT};
+use windows_sys::Win32::UI::WindowsAndMessaging::{ACCEL, HACCEL, MSG};
+use windows_sys::Win32::UI::WindowsAndMessaging::{FALT, FCONTROL, FSHIFT, FVIRTKEY};
+
+/// A table of keyboard shortcuts that are translated into menu commands.
+pub struct AcceleratorTable {
+    /// The handle to the accelerator table.
+    handle: HACCEL,
+
+    /// This handle is `Send` but `!Sync`.
+    _thread_safety: PhantomData<Cell<()>>,
+}
+
+impl AcceleratorTable {
+    /// Create a new accelerator table.
+    ///
+    /// Each entry maps a key and its modifiers to the command identifier that is delivered
+    /// through [`Event::MenuCommand`](crate::event::Event::MenuCommand) when the key is pressed.
+    /// Win32 stores command identifiers in 16 bits, so larger identifiers are rejected.
+    pub fn new(entries: &[(KeyModifiers, VirtualKey, u32)]) -> Result<Self, Error> {
+        let mut accels = Vec::with_capacity(entries.len());
+
+        for &(modifiers, key, cmd) in entries {
+            if cmd > u16::MAX as u32 {
+                return Err(Error::custom(
+                    ERROR_INVALID_PARAMETER,
+                    "CreateAcceleratorTableA",
+                    "The command identifier must fit in 16 bits.",
+                ));
+            }
+
+            let mut flags = FVIRTKEY;
+
+            if modifiers.contains(KeyModifiers::SHIFT) {
+                flags |= FSHIFT;
+            }
+            if modifiers.contains(KeyModifiers::CONTROL) {
+                flags |= FCONTROL;
+            }
+            if modifiers.contains(KeyModifiers::ALT) {
+                flags |= FALT;
+            }
+
+            accels.push(ACCEL {
+                fVirt: flags,
+                key: key.raw(),
+                cmd: cmd as u16,
+            });
+        }
+
+        let handle = unsafe { CreateAcceleratorTableA(accels.as_ptr(), accels.len() as i32) };
+
+        if handle == 0 {
+            Err(Error::last_error("CreateAcceleratorTableA"))
+        } else {
+            Ok(Self {
+                handle,
+                _thread_safety: PhantomData,
+            })
+        }
+    }
+}
+
+impl Drop for AcceleratorTable {
+    fn drop(&mut self) {
+        unsafe {
+            DestroyAcceleratorTable(self.handle);
+        }
+    }
+}
+
+// The accelerator table used by the reactor on this thread.
+#[cfg(feature = "std")]
+std::thread_local! {
+    static ACCELERATORS: RefCell<Option<AcceleratorTable>> = const { RefCell::new(None) };
+}
+
+impl Client {
+    /// Set the accelerator table used to translate keyboard input on this thread.
+    ///
+    /// Matched accelerators are sent to the top-level window that received the key press, as
+    /// [`Event::MenuCommand`](crate::event::Event::MenuCommand). Passing `None` disables
+    /// translation. The previous table, if any, is returned.
+    #[cfg(feature = "std")]
+    pub fn set_accelerators(&self, table: Option<AcceleratorTable>) -> Option<AcceleratorTable> {
+        ACCELERATORS.with(|accelerators| mem::replace(&mut *accelerators.borrow_mut(), table))
+    }
+}
+
+/// Translate a message using the active accelerator table.
+///
+/// Returns `true` if the message was translated, in which case it should not be dispatched.
+#[cfg(feature = "std")]
+pub(crate) fn translate_accelerator(msg: &MSG) -> bool {
+    // Thread messages can't be translated.
+    if msg.hwnd == 0 {
+        return false;
+    }
+
+    ACCELERATORS.with(|accelerators| match &*accelerators.borrow() {
+        Some(table) => {
+            // Send the command to the top-level window, where the menu lives.
+            let hwnd = unsafe { GetAncestor(msg.hwnd, GA_ROOT) };
+            unsafe { TranslateAcceleratorW(hwnd, table.handle, msg) != 0 }
+        }
+        None => false,
+    })
+}
+
+/// Without libstd, there is no accelerator table to translate with.
+#[cfg(not(feature = "std"))]
+#[inline]
+pub(crate) fn translate_accelerator(_msg: &MSG) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cstr::CString;
+    use crate::event::Event;
+    use crate::reactor::Reactor;
+    use crate::window::{AsWindow, ExtendedStyle, WindowStyle};
+
+    use blood_geometry::{Point, Rect, Size};
+
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetKeyboardState, SetKeyboardState};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{PostMessageA, WM_KEYDOWN};
+
+    #[test]
+    fn test_accelerator_table() {
+        let client = Client::new();
+        let save = VirtualKey::from_ascii(b's').unwrap();
+        assert!(AcceleratorTable::new(&[(KeyModifiers::CONTROL, save, 0x1_0000)]).is_err());
+
+        let table = AcceleratorTable::new(&[(KeyModifiers::CONTROL, save, 10)])
+            .expect("Failed to create accelerator table");
+        assert!(client.set_accelerators(Some(table)).is_none());
+
+        let class_name = CString::new("test_accelerator_table").unwrap();
+        let window_title = CString::new("test_accelerator_table").unwrap();
+        let fired = Cell::new(None);
+        let class = client
+            .create_class(&class_name)
+            .build(|client, &(), _, ev| {
+                if let Event::MenuCommand(id) = ev {
+                    fired.set(Some(id));
+                    client.quit();
+                }
+            })
+            .expect("Failed to create window class");
+
+        let window = client
+            .create_window(
+                &class,
+                &window_title,
+                None,
+                None,
+                WindowStyle::empty(),
+                ExtendedStyle::empty(),
+                Rect::new(Point::new(0, 0), Size::new(1, 1)),
+                (),
+            )
+            .expect("Failed to create window");
+
+        // Posted key messages don't update the key state, so mark Ctrl as held by hand.
+        let mut keys = [0u8; 256];
+        assert_ne!(unsafe { GetKeyboardState(keys.as_mut_ptr()) }, 0);
+        let previous = keys;
+        keys[VirtualKey::CONTROL.raw() as usize] |= 0x80;
+        assert_ne!(unsafe { SetKeyboardState(keys.as_ptr()) }, 0);
+
+        // Press Ctrl+S, which should be translated into a command.
+        let result = unsafe {
+            PostMessageA(
+                window.as_window().handle(),
+                WM_KEYDOWN,
+                save.raw() as usize,
+                0,
+            )
+        };
+        assert_ne!(result, 0);
+
+        Reactor::new()
+            .expect("to create reactor")
+            .run()
+            .expect("to run without errors");
+        assert_eq!(fired.get(), Some(10));
+        unsafe { SetKeyboardState(previous.as_ptr()) };
+
+        drop(window);
+        assert!(client.set_accelerators(None).is_some());
+    }
+}
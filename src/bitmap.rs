@@ -2,18 +2,29 @@
 
 //! Functions for making and managing bitmaps.
 
+use crate::cstr::CStr;
+use crate::dc::{DeviceContext, ReleaseDC};
 use crate::gdi_object::{AsGdiObject, BorrowedGdiObject, OwnedGdiObject};
+use crate::module::current_module;
 use crate::Error;
 
 use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+use blood_geometry::Size;
 
 use core::cell::Cell;
 use core::marker::PhantomData;
+use core::mem::{self, MaybeUninit};
 use core::num::{NonZeroI32, NonZeroU16};
 use core::ptr::NonNull;
 
-use windows_sys::Win32::Graphics::Gdi::{CreateBitmapIndirect, DeleteObject};
-use windows_sys::Win32::Graphics::Gdi::{BITMAP, BITMAPINFOHEADER, HBITMAP};
+use windows_sys::Win32::Foundation::{ERROR_INVALID_PARAMETER, HINSTANCE};
+use windows_sys::Win32::Graphics::Gdi::{CreateBitmapIndirect, CreateDIBSection, DeleteObject};
+use windows_sys::Win32::Graphics::Gdi::{GetDIBits, GetObjectA, BI_RGB, DIB_RGB_COLORS};
+use windows_sys::Win32::Graphics::Gdi::{BITMAP, BITMAPINFO, BITMAPINFOHEADER, HBITMAP, RGBQUAD};
+use windows_sys::Win32::UI::WindowsAndMessaging::LoadImageA;
+use windows_sys::Win32::UI::WindowsAndMessaging::{IMAGE_BITMAP, IMAGE_FLAGS, LR_LOADFROMFILE};
 
 macro_rules! nz_unchecked {
     ($ty:ty, $expr:expr) => {{
@@ -138,6 +149,222 @@ impl Bitmap {
         }
     }
 
+    /// Create a device-independent bitmap whose pixels can be written to directly.
+    ///
+    /// `bpp` must be 16, 24 or 32. Rows are padded to a multiple of four bytes. If `height` is
+    /// positive, the bitmap is bottom-up and the first row of the data is the bottom row of the
+    /// image. If it is negative, the bitmap is top-down and is `-height` pixels tall.
+    pub fn create_dib_section(
+        dc: &DeviceContext<impl ReleaseDC + ?Sized>,
+        width: i32,
+        height: i32,
+        bpp: u16,
+    ) -> Result<DIBitmap, Error> {
+        const NAME: &str = "CreateDIBSection";
+
+        if width <= 0 || height == 0 {
+            return Err(Error::custom(
+                ERROR_INVALID_PARAMETER,
+                NAME,
+                "The bitmap must not be empty.",
+            ));
+        }
+
+        // Lower bit depths need a color table, which isn't supported.
+        if !matches!(bpp, 16 | 24 | 32) {
+            return Err(Error::custom(
+                ERROR_INVALID_PARAMETER,
+                NAME,
+                "The bitmap must have 16, 24 or 32 bits per pixel.",
+            ));
+        }
+
+        let info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width,
+                biHeight: height,
+                biPlanes: 1,
+                biBitCount: bpp,
+                biCompression: BI_RGB,
+                biSizeImage: 0,
+                biXPelsPerMeter: 0,
+                biYPelsPerMeter: 0,
+                biClrUsed: 0,
+                biClrImportant: 0,
+            },
+            bmiColors: [unsafe { mem::zeroed() }],
+        };
+
+        let mut bits = core::ptr::null_mut();
+        let handle =
+            unsafe { CreateDIBSection(dc.handle(), &info, DIB_RGB_COLORS, &mut bits, 0, 0) };
+
+        if handle == 0 {
+            return Err(Error::last_error(NAME));
+        }
+
+        let handle = Self {
+            handle: unsafe { OwnedGdiObject::new(handle) },
+            thread_safety: PhantomData,
+        };
+
+        // Each row is padded to a whole number of 32-bit words.
+        let stride = ((width as usize * bpp as usize + 31) & !31) / 8;
+        let len = stride * height.unsigned_abs() as usize;
+
+        let data = match NonNull::new(core::ptr::slice_from_raw_parts_mut(bits.cast::<u8>(), len)) {
+            Some(data) => data,
+            None => abort!("CreateDIBSection returned a null pointer"),
+        };
+
+        Ok(DIBitmap { handle, data })
+    }
+
+    /// Read the pixels of the bitmap.
+    ///
+    /// The pixels are converted to the bits per pixel in `info`. If the height in `info` is
+    /// negative, the rows are returned top-down; otherwise, they are bottom-up. On success, the
+    /// width, height and scanline width in `info` are updated to match the returned data.
+    ///
+    /// The bitmap must not be selected into a device context.
+    pub fn get_bits(
+        &self,
+        dc: &DeviceContext<impl ReleaseDC + ?Sized>,
+        info: &mut BitmapInfo<'_>,
+    ) -> Result<Vec<u8>, Error> {
+        /// A `BITMAPINFO` with room for the largest color table.
+        #[repr(C)]
+        struct BitmapInfoWithColors {
+            header: BITMAPINFOHEADER,
+            colors: [RGBQUAD; 256],
+        }
+
+        let handle = self.as_gdi_object().as_raw();
+        let mut raw: BitmapInfoWithColors = unsafe { mem::zeroed() };
+        raw.header.biSize = mem::size_of::<BITMAPINFOHEADER>() as u32;
+
+        // With no buffer and no bit count, GetDIBits fills in the dimensions of the bitmap.
+        let result = unsafe {
+            GetDIBits(
+                dc.handle(),
+                handle,
+                0,
+                0,
+                core::ptr::null_mut(),
+                &mut raw as *mut BitmapInfoWithColors as *mut BITMAPINFO,
+                DIB_RGB_COLORS,
+            )
+        };
+
+        if result == 0 {
+            return Err(Error::last_error("GetDIBits"));
+        }
+
+        // Request the pixels in the caller's format.
+        let width = raw.header.biWidth;
+        let height = raw.header.biHeight.abs();
+        let bpp = info.bits_per_pixel().get();
+        let top_down = info.height().get() < 0;
+
+        raw.header.biHeight = if top_down { -height } else { height };
+        raw.header.biPlanes = 1;
+        raw.header.biBitCount = bpp;
+        raw.header.biCompression = BI_RGB;
+        raw.header.biSizeImage = 0;
+
+        // Each row is padded to a whole number of 32-bit words.
+        let stride = ((width as usize * bpp as usize + 31) & !31) / 8;
+        let mut bits = alloc::vec![0u8; stride * height as usize];
+
+        let result = unsafe {
+            GetDIBits(
+                dc.handle(),
+                handle,
+                0,
+                height as u32,
+                bits.as_mut_ptr().cast(),
+                &mut raw as *mut BitmapInfoWithColors as *mut BITMAPINFO,
+                DIB_RGB_COLORS,
+            )
+        };
+
+        if result == 0 {
+            return Err(Error::last_error("GetDIBits"));
+        }
+
+        info.inner.bmWidth = width;
+        info.inner.bmHeight = raw.header.biHeight;
+        info.inner.bmWidthBytes = stride as i32;
+
+        Ok(bits)
+    }
+
+    /// Load a bitmap from a `.bmp` file.
+    pub fn load_from_file(path: &CStr) -> Result<Self, Error> {
+        Self::load(0, path.as_ptr().cast(), LR_LOADFROMFILE)
+    }
+
+    /// Load a bitmap from a resource embedded in the executable.
+    pub fn load_resource(id: u16) -> Result<Self, Error> {
+        // MAKEINTRESOURCE: the identifier is passed in place of the name.
+        Self::load(current_module(), id as usize as *const u8, 0)
+    }
+
+    /// Load a bitmap with `LoadImageA`.
+    fn load(module: HINSTANCE, name: *const u8, flags: IMAGE_FLAGS) -> Result<Self, Error> {
+        let handle = unsafe { LoadImageA(module, name, IMAGE_BITMAP, 0, 0, flags) };
+
+        if handle == 0 {
+            Err(Error::last_error("LoadImageA"))
+        } else {
+            Ok(Self {
+                handle: unsafe { OwnedGdiObject::new(handle) },
+                thread_safety: PhantomData,
+            })
+        }
+    }
+
+    /// Get the width and height of the bitmap, in pixels.
+    pub fn dimensions(&self) -> Result<Size<i32>, Error> {
+        self.object()
+            .map(|bitmap| Size::new(bitmap.bmWidth, bitmap.bmHeight))
+    }
+
+    /// Get the format of the bitmap.
+    ///
+    /// The returned information has no bits; use [`get_bits`](Self::get_bits) to read the
+    /// pixels.
+    pub fn info(&self) -> Result<BitmapInfo<'static>, Error> {
+        let mut inner = self.object()?;
+
+        // The pointer is only set for DIB sections, and the data isn't copied.
+        inner.bmBits = core::ptr::null_mut();
+
+        Ok(BitmapInfo {
+            inner,
+            bits: Cow::Borrowed(&[]),
+        })
+    }
+
+    /// Get the `BITMAP` structure describing the bitmap.
+    fn object(&self) -> Result<BITMAP, Error> {
+        let mut bitmap = MaybeUninit::<BITMAP>::uninit();
+        let result = unsafe {
+            GetObjectA(
+                self.as_gdi_object().as_raw(),
+                mem::size_of::<BITMAP>() as i32,
+                bitmap.as_mut_ptr().cast(),
+            )
+        };
+
+        if result == 0 {
+            Err(Error::last_error("GetObjectA"))
+        } else {
+            Ok(unsafe { bitmap.assume_init() })
+        }
+    }
+
     pub(crate) fn into_handle(self) -> HBITMAP {
         self.handle.into_handle()
     }
@@ -158,6 +385,44 @@ impl From<Bitmap> for OwnedGdiObject {
     }
 }
 
+impl DIBitmap {
+    /// Get the pixel data.
+    pub fn data(&self) -> &[u8] {
+        unsafe { self.data.as_ref() }
+    }
+
+    /// Get the pixel data mutably.
+    ///
+    /// GDI may still be drawing to the bitmap, so call `GdiFlush` before writing to it if it has
+    /// been drawn to.
+    pub fn data_mut(&mut self) -> &mut [u8] {
+        unsafe { self.data.as_mut() }
+    }
+
+    /// Get the underlying bitmap.
+    pub fn bitmap(&self) -> &Bitmap {
+        &self.handle
+    }
+}
+
+impl From<DIBitmap> for Bitmap {
+    fn from(bitmap: DIBitmap) -> Self {
+        bitmap.handle
+    }
+}
+
+impl From<DIBitmap> for OwnedGdiObject {
+    fn from(bitmap: DIBitmap) -> Self {
+        bitmap.handle.handle
+    }
+}
+
+impl AsGdiObject for DIBitmap {
+    fn as_gdi_object(&self) -> BorrowedGdiObject<'_> {
+        self.handle.as_gdi_object()
+    }
+}
+
 impl AsGdiObject for Bitmap {
     fn as_gdi_object(&self) -> BorrowedGdiObject<'_> {
         self.handle.as_gdi_object()
@@ -180,6 +445,133 @@ mod tests {
             Cow::Borrowed(([0u8, 0]).as_ref()),
         );
         let bitmap = Bitmap::new(&info).unwrap();
+        assert_eq!(bitmap.dimensions().unwrap(), Size::new(1, 1));
         drop(bitmap);
     }
+
+    #[test]
+    fn info() {
+        let info = BitmapInfo::new(
+            nz_unchecked!(NonZeroI32, 3),
+            nz_unchecked!(NonZeroI32, 5),
+            nz_unchecked!(NonZeroI32, 2),
+            nz_unchecked!(NonZeroU16, 1),
+            nz_unchecked!(NonZeroU16, 1),
+            Cow::Borrowed([0u8; 10].as_ref()),
+        );
+        let bitmap = Bitmap::new(&info).unwrap();
+
+        let info = bitmap.info().expect("Failed to get bitmap info");
+        assert_eq!(info.width().get(), 3);
+        assert_eq!(info.height().get(), 5);
+        assert_eq!(info.scanline_width().get(), 2);
+        assert_eq!(info.planes().get(), 1);
+        assert_eq!(info.bits_per_pixel().get(), 1);
+        assert!(info.bits().is_empty());
+    }
+
+    #[test]
+    fn dib_section() {
+        use crate::dc::BitBltOp;
+        use blood_geometry::{Point, Rect};
+        use windows_sys::Win32::Graphics::Gdi::{CreateCompatibleDC, DeleteDC, GetPixel};
+
+        let screen = unsafe { CreateCompatibleDC(0) };
+        let dc = unsafe { DeviceContext::borrowed(screen) };
+
+        // A top-down 4x4 bitmap, so the first row of the data is the top row.
+        let mut dib = Bitmap::create_dib_section(&dc, 4, -4, 32).expect("Failed to create DIB");
+        assert_eq!(dib.bitmap().dimensions().unwrap(), Size::new(4, 4));
+        assert_eq!(dib.data().len(), 4 * 4 * 4);
+
+        // Pixels are stored as BGRX. Make the pixel at (1, 0) red.
+        dib.data_mut()[4..8].copy_from_slice(&[0x00, 0x00, 0xFF, 0x00]);
+
+        // Blit the DIB into another memory DC and read the pixel back.
+        let src = dc.create_compatible_dc().unwrap();
+        let _old_src = src.select_object(dib).unwrap();
+        let dest = src.create_compatible_dc().unwrap();
+        let _old_dest = dest
+            .select_object(src.create_compatible_bitmap(Size::new(4, 4)).unwrap())
+            .unwrap();
+        dest.bit_blt(
+            &src,
+            Rect::new(Point::new(0, 0), Size::new(4, 4)),
+            Point::new(0, 0),
+            BitBltOp::SrcCopy,
+        )
+        .expect("Failed to blit");
+
+        assert_eq!(unsafe { GetPixel(dest.handle(), 1, 0) }, 0x0000FF);
+        assert_eq!(unsafe { GetPixel(dest.handle(), 0, 0) }, 0);
+
+        assert!(Bitmap::create_dib_section(&dc, 4, 4, 8).is_err());
+
+        drop(dc);
+        unsafe { DeleteDC(screen) };
+    }
+
+    #[test]
+    fn get_bits() {
+        use windows_sys::Win32::Graphics::Gdi::{CreateCompatibleDC, DeleteDC};
+
+        let screen = unsafe { CreateCompatibleDC(0) };
+        let dc = unsafe { DeviceContext::borrowed(screen) };
+
+        let mut dib = Bitmap::create_dib_section(&dc, 3, -2, 32).expect("Failed to create DIB");
+        // The fourth byte of each pixel is unused, so leave it at zero.
+        let pixels = (0..24)
+            .map(|i| if i % 4 == 3 { 0 } else { i })
+            .collect::<Vec<u8>>();
+        dib.data_mut().copy_from_slice(&pixels);
+        let bitmap = Bitmap::from(dib);
+
+        // Read the pixels back top-down, in the same format.
+        let mut info = BitmapInfo::new(
+            nz_unchecked!(NonZeroI32, 1),
+            nz_unchecked!(NonZeroI32, -1),
+            nz_unchecked!(NonZeroI32, 1),
+            nz_unchecked!(NonZeroU16, 1),
+            nz_unchecked!(NonZeroU16, 32),
+            Cow::Borrowed(&[][..]),
+        );
+        let bits = bitmap.get_bits(&dc, &mut info).expect("Failed to get bits");
+
+        assert_eq!(bits, pixels);
+        assert_eq!(info.width().get(), 3);
+        assert_eq!(info.height().get(), -2);
+        assert_eq!(info.scanline_width().get(), 12);
+
+        drop(dc);
+        unsafe { DeleteDC(screen) };
+    }
+
+    #[test]
+    fn load_from_file() {
+        use crate::cstr::CString;
+
+        // A 2x3 24-bit bitmap. Each row of 6 bytes is padded to 8.
+        let mut file = alloc::vec::Vec::new();
+        file.extend_from_slice(b"BM");
+        file.extend_from_slice(&(14u32 + 40 + 24).to_le_bytes());
+        file.extend_from_slice(&[0; 4]);
+        file.extend_from_slice(&(14u32 + 40).to_le_bytes());
+        file.extend_from_slice(&40u32.to_le_bytes());
+        file.extend_from_slice(&2i32.to_le_bytes());
+        file.extend_from_slice(&3i32.to_le_bytes());
+        file.extend_from_slice(&1u16.to_le_bytes());
+        file.extend_from_slice(&24u16.to_le_bytes());
+        file.extend_from_slice(&[0; 24]);
+        file.extend_from_slice(&[0xFF; 24]);
+
+        let path = std::env::temp_dir().join("porcupine_load_from_file.bmp");
+        std::fs::write(&path, &file).unwrap();
+        let c_path = CString::new(path.to_str().unwrap()).unwrap();
+
+        let bitmap = Bitmap::load_from_file(&c_path).expect("Failed to load bitmap");
+        assert_ne!(bitmap.as_gdi_object().as_raw(), 0);
+        assert_eq!(bitmap.dimensions().unwrap(), Size::new(2, 3));
+
+        std::fs::remove_file(&path).unwrap();
+    }
 }
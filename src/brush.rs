@@ -0,0 +1,129 @@
+// Boost/Apache2 License
+
+//! Functions for making and managing brushes.
+
+use crate::color::Color;
+use crate::gdi_object::{AsGdiObject, BorrowedGdiObject, OwnedGdiObject};
+use crate::Error;
+
+use core::cell::Cell;
+use core::marker::PhantomData;
+
+use windows_sys::Win32::Graphics::Gdi::CreateSolidBrush;
+use windows_sys::Win32::Graphics::Gdi::HBRUSH;
+use windows_sys::Win32::Graphics::Gdi::{
+    COLOR_APPWORKSPACE, COLOR_BACKGROUND, COLOR_BTNFACE, COLOR_BTNTEXT, COLOR_GRAYTEXT,
+    COLOR_HIGHLIGHT, COLOR_HIGHLIGHTTEXT, COLOR_INFOBK, COLOR_INFOTEXT, COLOR_MENU, COLOR_MENUTEXT,
+    COLOR_WINDOW, COLOR_WINDOWTEXT, SYS_COLOR_INDEX,
+};
+
+/// A brush.
+pub struct Brush {
+    /// The handle to the brush.
+    handle: OwnedGdiObject,
+
+    /// This handle is `Send` but `!Sync`.
+    thread_safety: PhantomData<Cell<()>>,
+}
+
+impl Brush {
+    /// Create a brush that paints with a solid color.
+    pub fn solid(color: impl Into<Color>) -> Result<Self, Error> {
+        let brush = unsafe { CreateSolidBrush(color.into().colorref()) };
+
+        if brush == 0 {
+            Err(Error::last_error("CreateSolidBrush"))
+        } else {
+            Ok(Self::from(unsafe { OwnedGdiObject::new(brush) }))
+        }
+    }
+
+    pub(crate) fn into_handle(self) -> HBRUSH {
+        self.handle.into_handle()
+    }
+}
+
+impl From<OwnedGdiObject> for Brush {
+    fn from(handle: OwnedGdiObject) -> Self {
+        Self {
+            handle,
+            thread_safety: PhantomData,
+        }
+    }
+}
+
+impl From<Brush> for OwnedGdiObject {
+    fn from(brush: Brush) -> Self {
+        brush.handle
+    }
+}
+
+impl AsGdiObject for Brush {
+    fn as_gdi_object(&self) -> BorrowedGdiObject<'_> {
+        self.handle.as_gdi_object()
+    }
+}
+
+/// Colors of the system's display elements.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SystemColor {
+    /// The desktop background.
+    Desktop,
+
+    /// The background of menus.
+    Menu,
+
+    /// The text in menus.
+    MenuText,
+
+    /// The background of windows.
+    Window,
+
+    /// The text in windows.
+    WindowText,
+
+    /// The background of multiple-document interface applications.
+    AppWorkspace,
+
+    /// Items selected in a control.
+    Highlight,
+
+    /// The text of items selected in a control.
+    HighlightText,
+
+    /// The face of buttons and dialog boxes.
+    ButtonFace,
+
+    /// The text on buttons.
+    ButtonText,
+
+    /// Grayed (disabled) text.
+    GrayText,
+
+    /// The background of tooltips.
+    InfoBackground,
+
+    /// The text of tooltips.
+    InfoText,
+}
+
+impl SystemColor {
+    /// Get the `COLOR_*` index for this color.
+    pub(crate) fn index(self) -> SYS_COLOR_INDEX {
+        match self {
+            Self::Desktop => COLOR_BACKGROUND,
+            Self::Menu => COLOR_MENU,
+            Self::MenuText => COLOR_MENUTEXT,
+            Self::Window => COLOR_WINDOW,
+            Self::WindowText => COLOR_WINDOWTEXT,
+            Self::AppWorkspace => COLOR_APPWORKSPACE,
+            Self::Highlight => COLOR_HIGHLIGHT,
+            Self::HighlightText => COLOR_HIGHLIGHTTEXT,
+            Self::ButtonFace => COLOR_BTNFACE,
+            Self::ButtonText => COLOR_BTNTEXT,
+            Self::GrayText => COLOR_GRAYTEXT,
+            Self::InfoBackground => COLOR_INFOBK,
+            Self::InfoText => COLOR_INFOTEXT,
+        }
+    }
+}
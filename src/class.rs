@@ -1,8 +1,12 @@
 // Boost/Apache2 License
 
+use crate::brush::{Brush, SystemColor};
 use crate::client::Client;
 use crate::cstr::CStr;
+use crate::cursor::Cursor;
 use crate::event::Event;
+use crate::gdi_object::AsGdiObject;
+use crate::icon::Icon;
 use crate::module::current_module;
 use crate::strict;
 use crate::window::BorrowedWindow;
@@ -10,14 +14,16 @@ use crate::Error;
 
 use alloc::boxed::Box;
 use alloc::rc::Rc;
+use core::cell::{Cell, RefCell};
 use core::marker::PhantomData;
 use core::mem;
 use core::ptr::{self, NonNull};
 
+use windows_sys::Win32::Foundation::{GetLastError, SetLastError};
 use windows_sys::Win32::UI::WindowsAndMessaging::WNDCLASSEXA;
 use windows_sys::Win32::UI::WindowsAndMessaging::{
     CreateWindowExA, DefWindowProcA, DestroyWindow, RegisterClassExA, SetClassLongPtrA,
-    UnregisterClassA,
+    SetClassLongPtrW, UnregisterClassA,
 };
 use windows_sys::Win32::UI::WindowsAndMessaging::{
     CS_BYTEALIGNCLIENT, CS_BYTEALIGNWINDOW, CS_CLASSDC, CS_DBLCLKS, CS_DROPSHADOW, CS_GLOBALCLASS,
@@ -32,6 +38,12 @@ pub struct ClassBuilder<'a> {
     /// The client.
     client: Client,
 
+    /// Whether unhandled messages are delivered as [`Event::Raw`].
+    raw_events: bool,
+
+    /// The background brush, which is only handed over to a class once it is registered.
+    background: Cell<Option<BackgroundBrush>>,
+
     /// Capture lifetime for string fields.
     _marker: PhantomData<&'a CStr>,
 }
@@ -54,6 +66,8 @@ impl<'a> ClassBuilder<'a> {
                 ..unsafe { mem::zeroed() }
             },
             client: client.clone(),
+            raw_events: false,
+            background: Cell::new(None),
             _marker: PhantomData,
         }
     }
@@ -76,6 +90,82 @@ impl<'a> ClassBuilder<'a> {
         self
     }
 
+    /// Set the cursor shown over windows of this class.
+    pub fn cursor(&mut self, cursor: Cursor) -> &mut Self {
+        self.inner.hCursor = cursor.handle();
+        self
+    }
+
+    /// Set the icon of windows of this class.
+    ///
+    /// This is shown in the taskbar and in the Alt+Tab window. If there is no small icon, a
+    /// smaller version of this one is also shown in the caption.
+    pub fn icon(&mut self, icon: Icon) -> &mut Self {
+        self.inner.hIcon = icon.handle();
+        self
+    }
+
+    /// Set the small icon of windows of this class, which is shown in the caption.
+    pub fn icon_small(&mut self, icon: Icon) -> &mut Self {
+        self.inner.hIconSm = icon.handle();
+        self
+    }
+
+    /// Set the brush used to erase the background of windows of this class.
+    ///
+    /// An owned brush is handed over to the first class that is registered with it, and the
+    /// system deletes it once that class is unregistered. Classes built afterwards have no
+    /// background brush unless another one is set. If the builder is dropped first, the brush is
+    /// deleted along with it.
+    pub fn background(&mut self, brush: BackgroundBrush) -> &mut Self {
+        self.background.set(Some(brush));
+        self
+    }
+
+    /// Deliver the messages that this crate doesn't handle as [`Event::Raw`].
+    ///
+    /// This is an escape hatch for messages that aren't modeled by [`Event`] yet.
+    pub fn raw_events(&mut self, raw_events: bool) -> &mut Self {
+        self.raw_events = raw_events;
+        self
+    }
+
+    /// Construct the class with an event handler that can mutate the window-specific data.
+    ///
+    /// The data is kept in a `RefCell`, so windows of this class are created with
+    /// `RefCell::new(data)`. The handler only runs for one event at a time. Events delivered to
+    /// a window while the handler is running for it, such as ones sent synchronously by a call
+    /// made from the handler, are queued until it returns.
+    ///
+    /// Events that can't be queued, like [`Event::HitTest`], and events delivered to other
+    /// windows of this class while the handler is running are not passed to the handler. Events
+    /// with a [`Reply`](crate::event::Reply) get the system's default handling. Use
+    /// [`post_message`](crate::window::AsWindow::post_message) to reach other windows of the
+    /// class from inside the handler.
+    pub fn build_mut<
+        'evl,
+        T: 'evl,
+        F: FnMut(&Client, &mut T, BorrowedWindow<'_>, Event<'_>) + 'evl,
+    >(
+        &self,
+        handler: F,
+    ) -> Result<WindowClass<'evl, RefCell<T>>, Error> {
+        let handler = RefCell::new(handler);
+
+        self.build(move |client, data: &RefCell<T>, window, event| {
+            // Both are borrowed while the handler is running for any window of the class.
+            let (mut handler, mut data) = match (handler.try_borrow_mut(), data.try_borrow_mut()) {
+                (Ok(handler), Ok(data)) => (handler, data),
+                _ => {
+                    tracing::debug!("Skipping an event delivered while the handler was running");
+                    return;
+                }
+            };
+
+            (*handler)(client, &mut data, window, event)
+        })
+    }
+
     /// Construct the class with the given event handler and window-specific data.
     pub fn build<'evl, T: 'evl, F: Fn(&Client, &T, BorrowedWindow<'_>, Event<'_>) + 'evl>(
         &self,
@@ -87,14 +177,36 @@ impl<'a> ClassBuilder<'a> {
         cls.cbClsExtra = mem::size_of::<Rc<ClassData<F>>>() as i32;
         cls.lpfnWndProc = Some(DefWindowProcA);
 
+        let background = self.background.take();
+        cls.hbrBackground = match &background {
+            None => 0,
+            // Win32 expects system colors to be one more than their COLOR_* index, so that
+            // index zero isn't mistaken for a null brush.
+            Some(BackgroundBrush::SystemColor(color)) => (color.index() + 1) as _,
+            Some(BackgroundBrush::Brush(brush)) => brush.as_gdi_object().as_raw(),
+        };
+
         // Register the class.
         let atom = unsafe { RegisterClassExA(&cls) };
+        if atom == 0 {
+            self.background.set(background);
+            return Err(Error::last_error("RegisterClassExA"));
+        }
+
+        // The class owns the brush now, even if it is unregistered below.
+        match background {
+            Some(BackgroundBrush::Brush(brush)) => {
+                brush.into_handle();
+            }
+            other => self.background.set(other),
+        }
+        let class_ptr = strict::invalid(atom as _).cast::<u8>();
 
         // Create a dummy window to manipulate the class data.
         let dummy_hwnd = unsafe {
             CreateWindowExA(
                 0,
-                strict::invalid(atom as _).cast(),
+                class_ptr,
                 ptr::null(),
                 0,
                 0,
@@ -107,6 +219,11 @@ impl<'a> ClassBuilder<'a> {
                 ptr::null(),
             )
         };
+        if dummy_hwnd == 0 {
+            let err = Error::last_error("CreateWindowExA");
+            unsafe { UnregisterClassA(class_ptr, current_module()) };
+            return Err(err);
+        }
 
         // Store the client and the event handler.
         let data = Rc::into_raw(Rc::new(ClassData {
@@ -116,20 +233,37 @@ impl<'a> ClassBuilder<'a> {
                 drop(data);
             },
             client: self.client.clone(),
+            raw_events: self.raw_events,
             handler,
         }));
 
         // Set the class data and the window procedure.
-        unsafe {
-            SetClassLongPtrA(dummy_hwnd, 0, strict::expose(data as *const _ as *const _));
-
-            #[allow(clippy::fn_to_numeric_cast)]
-            SetClassLongPtrA(
-                dummy_hwnd,
-                GCLP_WNDPROC,
-                crate::wndproc::porcupine_window_procedure::<T, F> as isize,
-            );
-        }
+        let result = unsafe {
+            // The class data starts out as zero, so a zero return value is only an error if the
+            // last error is set.
+            SetLastError(0);
+            if SetClassLongPtrA(dummy_hwnd, 0, strict::expose(data as *const _ as *const _)) == 0
+                && GetLastError() != 0
+            {
+                Err(Error::last_error("SetClassLongPtrA"))
+            } else {
+                // Setting the procedure through the wide-character API makes the windows of this
+                // class Unicode, so text outside of the ANSI code page isn't lost.
+                #[allow(clippy::fn_to_numeric_cast)]
+                let previous = SetClassLongPtrW(
+                    dummy_hwnd,
+                    GCLP_WNDPROC,
+                    crate::wndproc::porcupine_window_procedure::<T, F> as isize,
+                );
+
+                // The previous window procedure is never null.
+                if previous == 0 {
+                    Err(Error::last_error("SetClassLongPtrW"))
+                } else {
+                    Ok(())
+                }
+            }
+        };
 
         // Destroy the dummy window.
         unsafe {
@@ -137,11 +271,17 @@ impl<'a> ClassBuilder<'a> {
         }
 
         // Check for errors.
-        if atom == 0 {
-            Err(Error::last_error("RegisterClassEx"))
+        if let Err(err) = result {
+            // Windows of this class would never dispatch events, so get rid of it.
+            unsafe {
+                UnregisterClassA(class_ptr, current_module());
+                drop(Rc::from_raw(data));
+            }
+
+            Err(err)
         } else {
             Ok(WindowClass {
-                ptr: crate::strict::invalid(atom as isize).cast(),
+                ptr: class_ptr,
                 // We need to deallocate the event handler when the time comes.
                 //
                 // Since ClassData always has the drop function as its first field,
@@ -153,6 +293,29 @@ impl<'a> ClassBuilder<'a> {
     }
 }
 
+/// The brush used to erase the background of a window class.
+pub enum BackgroundBrush {
+    /// Use one of the system colors.
+    ///
+    /// The `+ 1` encoding that Win32 uses for system color brushes is applied automatically.
+    SystemColor(SystemColor),
+
+    /// Use a brush owned by the class.
+    Brush(Brush),
+}
+
+impl From<SystemColor> for BackgroundBrush {
+    fn from(color: SystemColor) -> Self {
+        Self::SystemColor(color)
+    }
+}
+
+impl From<Brush> for BackgroundBrush {
+    fn from(brush: Brush) -> Self {
+        Self::Brush(brush)
+    }
+}
+
 bitflags::bitflags! {
     /// Bitflags for the `ClassBuilder::style` method.
     pub struct Style : u32 {
@@ -264,6 +427,9 @@ pub(crate) struct ClassData<F> {
     /// The client.
     pub(crate) client: Client,
 
+    /// Whether unhandled messages are delivered as [`Event::Raw`].
+    pub(crate) raw_events: bool,
+
     /// The event handler.
     pub(crate) handler: F,
 }
@@ -291,6 +457,13 @@ impl<T, F: Fn(&Client, &T, BorrowedWindow<'_>, Event<'_>)> ErasedClassData<T> fo
 mod tests {
     use super::*;
     use crate::cstr::CString;
+    use crate::cursor::StandardCursor;
+    use crate::window::{AsWindow, ExtendedStyle, Window, WindowStyle};
+
+    use blood_geometry::{Point, Rect, Size};
+
+    use windows_sys::Win32::Foundation::HWND;
+    use windows_sys::Win32::UI::WindowsAndMessaging::WM_HOTKEY;
 
     #[test]
     fn test_class_builder() {
@@ -301,4 +474,300 @@ mod tests {
             .build(move |_, &(), _, _| {})
             .expect("Failed to build class");
     }
+
+    #[test]
+    fn test_class_builder_duplicate() {
+        let client = Client::new();
+        let name = CString::new("test_class_builder_duplicate").unwrap();
+        let _class = ClassBuilder::new(&client, &name)
+            .build(move |_, &(), _, _| {})
+            .expect("Failed to build class");
+
+        // Registering the same name again fails, and the handler is dropped.
+        let captured = Rc::new(());
+        let handler_data = captured.clone();
+        let err = ClassBuilder::new(&client, &name)
+            .build(move |_, &(), _, _| {
+                let _ = &handler_data;
+            })
+            .err()
+            .expect("Duplicate class should fail");
+        assert_eq!(err.kind(), crate::ErrorKind::ClassAlreadyExists);
+        assert_eq!(err.function(), "RegisterClassExA");
+        assert_eq!(Rc::strong_count(&captured), 1);
+    }
+
+    #[test]
+    fn test_class_build_mut() {
+        let client = Client::new();
+        let name = CString::new("test_class_build_mut").unwrap();
+        let class = ClassBuilder::new(&client, &name)
+            .build_mut(|_, count: &mut u32, _, ev| {
+                if let Event::HotKey(_) = ev {
+                    *count += 1;
+                }
+            })
+            .expect("Failed to build class");
+
+        let window = client
+            .create_window(
+                &class,
+                &name,
+                None,
+                None,
+                WindowStyle::empty(),
+                ExtendedStyle::empty(),
+                Rect::new(Point::new(0, 0), Size::new(10, 10)),
+                RefCell::new(0),
+            )
+            .expect("Failed to create window");
+
+        for id in 0..3 {
+            unsafe { window.send_message(WM_HOTKEY, id, 0) };
+        }
+
+        let count = unsafe {
+            window
+                .as_window()
+                .with_user_data(|count: &RefCell<u32>| *count.borrow())
+        };
+        assert_eq!(count, Some(3));
+    }
+
+    #[test]
+    fn test_class_build_mut_sibling() {
+        let client = Client::new();
+        let name = CString::new("test_class_build_mut_sibling").unwrap();
+
+        // Each window counts its hotkeys and forwards them to its sibling, if it has one.
+        let class = ClassBuilder::new(&client, &name)
+            .build_mut(|_, (sibling, count): &mut (HWND, u32), _, ev| {
+                if let Event::HotKey(id) = ev {
+                    *count += 1;
+
+                    if *sibling != 0 {
+                        let sibling = unsafe { BorrowedWindow::from_raw_handle(*sibling) };
+                        unsafe { sibling.post_message(WM_HOTKEY, id as usize, 0) }
+                            .expect("Failed to post message");
+                    }
+                }
+            })
+            .expect("Failed to build class");
+
+        let create = |data| {
+            client
+                .create_window(
+                    &class,
+                    &name,
+                    None,
+                    None,
+                    WindowStyle::empty(),
+                    ExtendedStyle::empty(),
+                    Rect::new(Point::new(0, 0), Size::new(10, 10)),
+                    RefCell::new(data),
+                )
+                .expect("Failed to create window")
+        };
+        let second = create((0, 0));
+        let first = create((second.as_window().handle(), 0));
+
+        unsafe { first.send_message(WM_HOTKEY, 1, 0) };
+        client.poll_events().expect("Failed to poll events");
+
+        let count = |window: &Window<'_, _>| unsafe {
+            window
+                .as_window()
+                .with_user_data(|data: &RefCell<(HWND, u32)>| data.borrow().1)
+        };
+        assert_eq!(count(&first), Some(1));
+        assert_eq!(count(&second), Some(1));
+    }
+
+    #[test]
+    fn test_class_build_mut_reentrant() {
+        let client = Client::new();
+        let name = CString::new("test_class_build_mut_reentrant").unwrap();
+
+        // The first hotkey sends another one to the same window and repaints it.
+        let class = ClassBuilder::new(&client, &name)
+            .build_mut(|_, ids: &mut Vec<i32>, window, ev| {
+                if let Event::HotKey(id) = ev {
+                    ids.push(id);
+
+                    if id == 1 {
+                        unsafe { window.send_message(WM_HOTKEY, 2, 0) };
+                        window.repaint().expect("Failed to repaint");
+                        ids.push(3);
+                    }
+                }
+            })
+            .expect("Failed to build class");
+
+        let window = client
+            .create_window(
+                &class,
+                &name,
+                None,
+                None,
+                WindowStyle::empty(),
+                ExtendedStyle::empty(),
+                Rect::new(Point::new(0, 0), Size::new(10, 10)),
+                RefCell::new(Vec::new()),
+            )
+            .expect("Failed to create window");
+
+        unsafe { window.send_message(WM_HOTKEY, 1, 0) };
+
+        // The nested hotkey is handled once the first one returns.
+        let ids = unsafe {
+            window
+                .as_window()
+                .with_user_data(|ids: &RefCell<Vec<i32>>| ids.borrow().clone())
+        };
+        assert_eq!(ids, Some(vec![1, 3, 2]));
+    }
+
+    #[test]
+    fn test_class_icon() {
+        use crate::icon::StandardIcon;
+        use windows_sys::Win32::UI::WindowsAndMessaging::{
+            GetClassLongPtrW, GCLP_HICON, GCLP_HICONSM, HICON,
+        };
+
+        let client = Client::new();
+        let name = CString::new("test_class_icon").unwrap();
+        let icon = Icon::load(StandardIcon::Application).unwrap();
+        let icon_small = Icon::load(StandardIcon::Information).unwrap();
+        let (icon_handle, icon_small_handle) = (icon.handle(), icon_small.handle());
+        let class = ClassBuilder::new(&client, &name)
+            .icon(icon)
+            .icon_small(icon_small)
+            .build(move |_, &(), _, _| {})
+            .expect("Failed to build class");
+
+        let window = client
+            .create_window(
+                &class,
+                &name,
+                None,
+                None,
+                WindowStyle::OVERLAPPED_WINDOW,
+                ExtendedStyle::empty(),
+                Rect::new(Point::new(0, 0), Size::new(10, 10)),
+                (),
+            )
+            .expect("Failed to create window");
+
+        let hwnd = window.as_window().handle();
+        assert_eq!(
+            unsafe { GetClassLongPtrW(hwnd, GCLP_HICON) } as HICON,
+            icon_handle
+        );
+        assert_eq!(
+            unsafe { GetClassLongPtrW(hwnd, GCLP_HICONSM) } as HICON,
+            icon_small_handle
+        );
+    }
+
+    #[test]
+    fn test_class_background() {
+        let client = Client::new();
+        let name = CString::new("test_class_background").unwrap();
+        let class = ClassBuilder::new(&client, &name)
+            .background(BackgroundBrush::SystemColor(SystemColor::Window))
+            .build(move |_, &(), _, _| {})
+            .expect("Failed to build class");
+
+        let window = client
+            .create_window(
+                &class,
+                &name,
+                None,
+                None,
+                WindowStyle::VISIBLE,
+                ExtendedStyle::empty(),
+                Rect::new(Point::new(0, 0), Size::new(10, 10)),
+                (),
+            )
+            .expect("Failed to create window");
+        window
+            .invalidate(None, true)
+            .expect("Failed to invalidate window");
+    }
+
+    #[test]
+    fn test_class_background_brush() {
+        use windows_sys::Win32::UI::WindowsAndMessaging::{GetClassLongPtrA, GCLP_HBRBACKGROUND};
+
+        let client = Client::new();
+        let name = CString::new("test_class_background_brush").unwrap();
+        let mut builder = ClassBuilder::new(&client, &name);
+
+        // Replacing a brush that was never handed over deletes it.
+        builder.background(Brush::solid(0x0000FF).unwrap().into());
+        builder.background(Brush::solid(0x00FF00).unwrap().into());
+
+        let background = |class: &WindowClass<'_, ()>| {
+            let window = client
+                .create_window(
+                    class,
+                    &name,
+                    None,
+                    None,
+                    WindowStyle::empty(),
+                    ExtendedStyle::empty(),
+                    Rect::new(Point::new(0, 0), Size::new(10, 10)),
+                    (),
+                )
+                .expect("Failed to create window");
+            unsafe { GetClassLongPtrA(window.as_window().handle(), GCLP_HBRBACKGROUND) }
+        };
+
+        let class = builder
+            .build(|_, &(), _, _| {})
+            .expect("Failed to build class");
+        assert_ne!(background(&class), 0);
+        drop(class);
+
+        // The first class took the brush, so the next one doesn't share it.
+        let class = builder
+            .build(|_, &(), _, _| {})
+            .expect("Failed to build class");
+        assert_eq!(background(&class), 0);
+    }
+
+    #[test]
+    fn test_class_cursor() {
+        use windows_sys::Win32::UI::WindowsAndMessaging::{
+            GetClassLongPtrW, GCLP_HCURSOR, HCURSOR,
+        };
+
+        let client = Client::new();
+        let name = CString::new("test_class_cursor").unwrap();
+        let cursor = Cursor::load(StandardCursor::Arrow).expect("Failed to load cursor");
+        let cursor_handle = cursor.handle();
+        let class = ClassBuilder::new(&client, &name)
+            .cursor(cursor)
+            .build(move |_, &(), _, _| {})
+            .expect("Failed to build class");
+
+        let window = client
+            .create_window(
+                &class,
+                &name,
+                None,
+                None,
+                WindowStyle::empty(),
+                ExtendedStyle::empty(),
+                Rect::new(Point::new(0, 0), Size::new(1, 1)),
+                (),
+            )
+            .expect("Failed to create window");
+
+        let hwnd = window.as_window().handle();
+        assert_eq!(
+            unsafe { GetClassLongPtrW(hwnd, GCLP_HCURSOR) } as HCURSOR,
+            cursor_handle
+        );
+    }
 }
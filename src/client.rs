@@ -1,22 +1,52 @@
 // Boost/Apache2 License
 
+use crate::cstr::CStr;
+use crate::geometry::point_from_win32;
 use crate::module::current_module;
-use crate::Error;
+use crate::{strict, Error};
 
 use alloc::rc::Rc;
 
 use core::cell::Cell;
 use core::marker::{PhantomData, PhantomPinned};
+use core::mem::MaybeUninit;
 use core::num::NonZeroU32;
+use core::ptr;
+use core::time::Duration;
 
 use blood_geometry::Point;
 
-use windows_sys::Win32::UI::WindowsAndMessaging::{PostQuitMessage, SetCursorPos};
+#[cfg(feature = "std")]
+use std::ffi::OsString;
+#[cfg(feature = "std")]
+use std::os::windows::ffi::OsStringExt;
+
+use windows_sys::Win32::Foundation::{BOOL, HWND, WAIT_FAILED, WAIT_OBJECT_0, WAIT_TIMEOUT};
+#[cfg(feature = "std")]
+use windows_sys::Win32::System::{Environment::GetCommandLineW, Memory::LocalFree};
+#[cfg(feature = "std")]
+use windows_sys::Win32::UI::Shell::CommandLineToArgvW;
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    GetCursorPos, MsgWaitForMultipleObjectsEx, PostQuitMessage, RegisterWindowMessageA,
+    SendMessageTimeoutA, SetCursorPos, ShowCursor,
+};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    MWMO_INPUTAVAILABLE, QS_ALLINPUT, SMTO_ABORTIFHUNG, WM_SETTINGCHANGE,
+};
+
+/// The window handle used to send a message to all top-level windows.
+const HWND_BROADCAST: HWND = 0xFFFF;
+
+/// How long to wait for each window to handle a broadcast message, in milliseconds.
+const BROADCAST_TIMEOUT: u32 = 5000;
 
 /// NonZeroU32 as a one.
 const ONE: NonZeroU32 = unsafe { NonZeroU32::new_unchecked(1) };
 
 /// The client used to send instructions to the system.
+///
+/// Cloning a `Client` is cheap. All clones share the same state, including the window count used
+/// to quit the application once the last window is destroyed.
 #[derive(Clone)]
 pub struct Client(Rc<Inner>);
 
@@ -59,6 +89,93 @@ impl Client {
         }
     }
 
+    /// Get the cursor position, in screen coordinates.
+    pub fn cursor_pos(&self) -> Result<Point<i32>, Error> {
+        let mut point = MaybeUninit::uninit();
+        let result = unsafe { GetCursorPos(point.as_mut_ptr()) };
+
+        if result == 0 {
+            Err(Error::last_error("GetCursorPos"))
+        } else {
+            Ok(point_from_win32(unsafe { point.assume_init() }))
+        }
+    }
+
+    /// Show or hide the cursor.
+    ///
+    /// This doesn't set the visibility directly. Instead, the system keeps a display counter that
+    /// is incremented when `show` is `true` and decremented when it is `false`, and the cursor is
+    /// only shown while the counter is zero or more. Every call to hide the cursor needs to be
+    /// balanced by a call to show it. Returns the new value of the counter.
+    pub fn show_cursor(&self, show: bool) -> i32 {
+        unsafe { ShowCursor(show as BOOL) }
+    }
+
+    /// Notify all top-level windows that a system-wide setting has changed.
+    ///
+    /// `area` names the part of the system that changed, such as `Environment` after changing
+    /// environment variables in the registry. Windows that don't respond in time are skipped so
+    /// that a hung application doesn't block the caller.
+    pub fn broadcast_setting_change(&self, area: &CStr) -> Result<(), Error> {
+        send_setting_change(HWND_BROADCAST, area)
+    }
+
+    /// Get a message identifier that is unique to `name` across the whole system.
+    ///
+    /// Every process that registers the same name gets the same identifier, which makes it useful
+    /// for communicating between applications. Windows receive these messages as
+    /// [`Event::User`](crate::event::Event::User).
+    pub fn register_message(&self, name: &CStr) -> Result<u32, Error> {
+        let msg = unsafe { RegisterWindowMessageA(name.as_ptr().cast()) };
+
+        if msg == 0 {
+            Err(Error::last_error("RegisterWindowMessageA"))
+        } else {
+            Ok(msg)
+        }
+    }
+
+    /// Get the command-line arguments of the process, including the program name.
+    ///
+    /// This parses the command line the same way the C runtime does, and works regardless of the
+    /// entry point, such as when the application is launched through a file association.
+    #[cfg(feature = "std")]
+    pub fn command_line(&self) -> Result<Vec<OsString>, Error> {
+        let mut count = 0;
+        let argv = unsafe { CommandLineToArgvW(GetCommandLineW(), &mut count) };
+
+        if argv.is_null() {
+            return Err(Error::last_error("CommandLineToArgvW"));
+        }
+
+        let args = (0..count as usize)
+            .map(|i| unsafe {
+                let arg = *argv.add(i);
+                let len = (0..).take_while(|&j| *arg.add(j) != 0).count();
+                OsString::from_wide(core::slice::from_raw_parts(arg, len))
+            })
+            .collect();
+
+        // The arguments are allocated in one block, which is freed with LocalFree.
+        unsafe {
+            LocalFree(strict::expose(argv.cast()));
+        }
+
+        Ok(args)
+    }
+
+    /// Set the language used for the messages of errors created after this call.
+    ///
+    /// `language` is a Win32 language identifier, as created by `MAKELANGID`. By default, messages
+    /// are formatted in US English, so that they are consistent regardless of the system locale.
+    /// If messages aren't available in the requested language, the language-neutral lookup order
+    /// is used instead. Passing `0` always uses the language-neutral lookup order.
+    ///
+    /// This setting is global to the process.
+    pub fn set_error_language(&self, language: u32) {
+        crate::set_error_language(language);
+    }
+
     /// Increment the window count.
     pub(crate) fn increment_window_count(&self) {
         let count = self.0.window_count.get().map_or(ONE, |count| unsafe {
@@ -77,7 +194,11 @@ impl Client {
     pub(crate) fn decrement_window_count(&self) {
         let count = match self.0.window_count.get() {
             Some(count) => count,
-            None => unreachable!("Count should never be zero in this case"),
+            None => {
+                // Don't post a second quit message if we're already at zero.
+                tracing::warn!("Window count decremented while no windows exist");
+                return;
+            }
         };
 
         let new_count = count.get().saturating_sub(1);
@@ -98,8 +219,15 @@ impl Client {
 
     /// Send a quit message to the application.
     pub fn quit(&self) {
+        self.quit_with_code(0);
+    }
+
+    /// Send a quit message to the application, with an exit code.
+    ///
+    /// The code is returned from [`Reactor::run`](crate::reactor::Reactor::run).
+    pub fn quit_with_code(&self, code: i32) {
         unsafe {
-            PostQuitMessage(0);
+            PostQuitMessage(code);
         }
     }
 
@@ -107,6 +235,64 @@ impl Client {
     pub async fn wait_for_event(&self) {
         crate::reactor::wait_for_message().await;
     }
+
+    /// Block the current thread until a message is available or the timeout elapses.
+    ///
+    /// Returns `true` if a message is available. This does not process the message; it still
+    /// needs to be dispatched, for instance by a [`Reactor`](crate::reactor::Reactor).
+    pub fn wait_event_timeout(&self, timeout: Duration) -> bool {
+        // Clamp the timeout so that it isn't mistaken for INFINITE.
+        let millis = timeout.as_millis().min((u32::MAX - 1) as u128) as u32;
+
+        let result = unsafe {
+            MsgWaitForMultipleObjectsEx(0, ptr::null(), millis, QS_ALLINPUT, MWMO_INPUTAVAILABLE)
+        };
+
+        match result {
+            WAIT_OBJECT_0 => true,
+            WAIT_TIMEOUT => false,
+            WAIT_FAILED => {
+                tracing::error!(
+                    "Failed to wait for a message: {}",
+                    Error::last_error("MsgWaitForMultipleObjectsEx")
+                );
+                false
+            }
+            other => {
+                tracing::warn!("Unexpected MsgWaitForMultipleObjectsEx result: {:x}", other);
+                false
+            }
+        }
+    }
+
+    /// Process all pending messages for this thread without blocking.
+    ///
+    /// This is useful for integrating into an event loop that is owned by someone else, such as
+    /// a game loop that calls this once per frame. Returns `true` if a quit message was seen.
+    pub fn poll_events(&self) -> Result<bool, Error> {
+        crate::reactor::drain_queue().map(|status| status.quit)
+    }
+}
+
+/// Send `WM_SETTINGCHANGE` for `area` to `hwnd`, skipping windows that don't respond in time.
+fn send_setting_change(hwnd: HWND, area: &CStr) -> Result<(), Error> {
+    let result = unsafe {
+        SendMessageTimeoutA(
+            hwnd,
+            WM_SETTINGCHANGE,
+            0,
+            strict::expose(area.as_ptr().cast()),
+            SMTO_ABORTIFHUNG,
+            BROADCAST_TIMEOUT,
+            ptr::null_mut(),
+        )
+    };
+
+    if result == 0 {
+        Err(Error::last_error("SendMessageTimeoutA"))
+    } else {
+        Ok(())
+    }
 }
 
 #[cfg(feature = "raw-window-handle")]
@@ -119,3 +305,222 @@ unsafe impl raw_window_handle::HasRawDisplayHandle for Client {
         raw_window_handle::RawDisplayHandle::Windows(handle)
     }
 }
+
+#[cfg(feature = "raw-window-handle-06")]
+impl raw_window_handle_06::HasDisplayHandle for Client {
+    fn display_handle(
+        &self,
+    ) -> Result<raw_window_handle_06::DisplayHandle<'_>, raw_window_handle_06::HandleError> {
+        let handle = raw_window_handle_06::WindowsDisplayHandle::new();
+
+        // SAFETY: The Windows display handle doesn't refer to anything.
+        Ok(unsafe {
+            raw_window_handle_06::DisplayHandle::borrow_raw(
+                raw_window_handle_06::RawDisplayHandle::Windows(handle),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cstr::CString;
+    use crate::window::{ExtendedStyle, WindowStyle};
+
+    use blood_geometry::{Rect, Size};
+
+    use windows_sys::Win32::UI::WindowsAndMessaging::{PeekMessageA, PostMessageA};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{PM_REMOVE, WM_APP, WM_QUIT};
+
+    /// Drain the quit messages in the queue and count them.
+    ///
+    /// `PostQuitMessage` only sets a flag, so several calls in a row are drained as one message.
+    /// Drain after every step that could quit to tell one quit from two.
+    fn count_quit_messages() -> usize {
+        let mut msg = MaybeUninit::uninit();
+        let mut count = 0;
+
+        while unsafe { PeekMessageA(msg.as_mut_ptr(), 0, WM_QUIT, WM_QUIT, PM_REMOVE) } > 0 {
+            count += 1;
+        }
+
+        count
+    }
+
+    #[test]
+    fn test_shared_window_count() {
+        let client = Client::new();
+        let clone = client.clone();
+
+        let first_name = CString::new("test_shared_window_count_1").unwrap();
+        let second_name = CString::new("test_shared_window_count_2").unwrap();
+        let first_class = client
+            .create_class(&first_name)
+            .build(|_, &(), _, _| {})
+            .unwrap();
+        let second_class = clone
+            .create_class(&second_name)
+            .build(|_, &(), _, _| {})
+            .unwrap();
+
+        let rect = Rect::new(Point::new(0, 0), Size::new(1, 1));
+        let first = client
+            .create_window(
+                &first_class,
+                &first_name,
+                None,
+                None,
+                WindowStyle::empty(),
+                ExtendedStyle::empty(),
+                rect,
+                (),
+            )
+            .unwrap();
+        let second = clone
+            .create_window(
+                &second_class,
+                &second_name,
+                None,
+                None,
+                WindowStyle::empty(),
+                ExtendedStyle::empty(),
+                rect,
+                (),
+            )
+            .unwrap();
+
+        // Both clones should see both windows.
+        assert_eq!(client.window_count(), 2);
+        assert_eq!(clone.window_count(), 2);
+        let mut quits = count_quit_messages();
+
+        // Dropping one window should not quit.
+        drop(first);
+        assert_eq!(clone.window_count(), 1);
+        quits += count_quit_messages();
+        assert_eq!(quits, 0);
+
+        // Dropping the last one should quit.
+        drop(second);
+        assert_eq!(client.window_count(), 0);
+        quits += count_quit_messages();
+
+        // Decrementing at zero should not quit again, through either clone.
+        client.decrement_window_count();
+        quits += count_quit_messages();
+        clone.decrement_window_count();
+        quits += count_quit_messages();
+
+        assert_eq!(quits, 1);
+    }
+
+    #[test]
+    fn test_wait_event_timeout() {
+        let client = Client::new();
+
+        // Post a message to this thread.
+        assert_ne!(unsafe { PostMessageA(0, WM_APP, 0, 0) }, 0);
+        assert!(client.wait_event_timeout(Duration::from_millis(100)));
+
+        // Remove it, and the queue should be empty.
+        let mut msg = MaybeUninit::uninit();
+        assert!(unsafe { PeekMessageA(msg.as_mut_ptr(), 0, WM_APP, WM_APP, PM_REMOVE) } > 0);
+        assert!(!client.wait_event_timeout(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn test_poll_events() {
+        let client = Client::new();
+
+        // With nothing in the queue, polling returns immediately.
+        assert!(!client.poll_events().expect("to poll events"));
+
+        // A quit message should be reported.
+        unsafe {
+            PostQuitMessage(0);
+        }
+        assert!(client.poll_events().expect("to poll events"));
+        assert!(!client.poll_events().expect("to poll events"));
+    }
+
+    #[test]
+    fn test_cursor_pos() {
+        let client = Client::new();
+        let original = client.cursor_pos().expect("Failed to get cursor position");
+
+        let target = Point::new(10, 20);
+        client
+            .set_cursor_pos(target)
+            .expect("Failed to set cursor position");
+        let pos = client.cursor_pos().expect("Failed to get cursor position");
+
+        // The position may be clipped or scaled, so allow a little leeway.
+        assert!((pos.x() - target.x()).abs() <= 1);
+        assert!((pos.y() - target.y()).abs() <= 1);
+
+        client
+            .set_cursor_pos(original)
+            .expect("Failed to restore cursor position");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_command_line() {
+        let client = Client::new();
+        let args = client.command_line().expect("Failed to get command line");
+
+        // The first argument is the program, which matches the standard library's view.
+        let std_args = std::env::args_os().collect::<Vec<_>>();
+        assert_eq!(args, std_args);
+    }
+
+    #[test]
+    fn test_send_setting_change() {
+        use crate::event::Event;
+        use crate::window::AsWindow;
+        use std::cell::RefCell;
+        use windows_sys::Win32::UI::WindowsAndMessaging::WM_SETTINGCHANGE;
+
+        let client = Client::new();
+        let class_name = CString::new("test_send_setting_change").unwrap();
+        let received = RefCell::new(None);
+        let class = client
+            .create_class(&class_name)
+            .raw_events(true)
+            .build(|_, &(), _, ev| {
+                if let Event::Raw {
+                    msg: WM_SETTINGCHANGE,
+                    lparam,
+                    ..
+                } = ev
+                {
+                    // The window is Unicode, so the system converts the area to a wide string.
+                    let area = strict::reconstitute(lparam).cast::<u16>();
+                    let len = (0..).take_while(|&i| unsafe { *area.add(i) } != 0).count();
+                    let area = unsafe { core::slice::from_raw_parts(area, len) };
+                    *received.borrow_mut() = Some(String::from_utf16_lossy(area));
+                }
+            })
+            .expect("Failed to create window class");
+
+        // Send it to a window of our own instead of every window on the desktop.
+        let window = client
+            .create_window(
+                &class,
+                &class_name,
+                None,
+                None,
+                WindowStyle::empty(),
+                ExtendedStyle::empty(),
+                Rect::new(Point::new(0, 0), Size::new(1, 1)),
+                (),
+            )
+            .expect("Failed to create window");
+
+        let area = CString::new("porcupine_test_area").unwrap();
+        send_setting_change(window.as_window().handle(), &area)
+            .expect("Failed to send setting change");
+        assert_eq!(received.borrow().as_deref(), Some("porcupine_test_area"));
+    }
+}
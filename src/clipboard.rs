@@ -0,0 +1,186 @@
+// Boost/Apache2 License
+
+//! Reading and writing the system clipboard.
+
+use crate::client::Client;
+use crate::Error;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use core::cell::Cell;
+use core::marker::PhantomData;
+use core::mem;
+use core::ptr;
+use core::slice;
+
+use windows_sys::Win32::Foundation::HANDLE;
+use windows_sys::Win32::System::DataExchange::{
+    CloseClipboard, EmptyClipboard, GetClipboardData, IsClipboardFormatAvailable, OpenClipboard,
+    SetClipboardData,
+};
+use windows_sys::Win32::System::Memory::{
+    GlobalAlloc, GlobalFree, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE,
+};
+use windows_sys::Win32::System::SystemServices::CF_UNICODETEXT;
+
+/// The clipboard, opened by this thread.
+///
+/// The clipboard is closed when this is dropped.
+struct OpenedClipboard {
+    /// The clipboard belongs to this thread until it is closed.
+    _thread_safety: PhantomData<Cell<()>>,
+}
+
+impl OpenedClipboard {
+    /// Open the clipboard.
+    fn open() -> Result<Self, Error> {
+        if unsafe { OpenClipboard(0) } == 0 {
+            Err(Error::last_error("OpenClipboard"))
+        } else {
+            Ok(Self {
+                _thread_safety: PhantomData,
+            })
+        }
+    }
+}
+
+impl Drop for OpenedClipboard {
+    fn drop(&mut self) {
+        if unsafe { CloseClipboard() } == 0 {
+            tracing::warn!(
+                "Failed to close the clipboard: {}",
+                Error::last_error("CloseClipboard")
+            );
+        }
+    }
+}
+
+/// A locked global memory object.
+///
+/// The memory is unlocked when this is dropped.
+struct GlobalLockGuard {
+    /// The memory object.
+    handle: HANDLE,
+
+    /// The pointer to the locked memory.
+    data: *mut u16,
+}
+
+impl GlobalLockGuard {
+    /// Lock a global memory object.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a valid global memory object containing UTF-16 data.
+    unsafe fn lock(handle: HANDLE) -> Result<Self, Error> {
+        let data = GlobalLock(handle) as *mut u16;
+
+        if data.is_null() {
+            Err(Error::last_error("GlobalLock"))
+        } else {
+            Ok(Self { handle, data })
+        }
+    }
+}
+
+impl Drop for GlobalLockGuard {
+    fn drop(&mut self) {
+        unsafe {
+            GlobalUnlock(self.handle);
+        }
+    }
+}
+
+impl Client {
+    /// Get the text on the clipboard.
+    ///
+    /// Returns `None` if the clipboard doesn't contain any text.
+    pub fn clipboard_text(&self) -> Result<Option<String>, Error> {
+        let _clipboard = OpenedClipboard::open()?;
+
+        if unsafe { IsClipboardFormatAvailable(CF_UNICODETEXT) } == 0 {
+            return Ok(None);
+        }
+
+        let handle = unsafe { GetClipboardData(CF_UNICODETEXT) };
+        if handle == 0 {
+            return Err(Error::last_error("GetClipboardData"));
+        }
+
+        let text = unsafe {
+            let lock = GlobalLockGuard::lock(handle)?;
+
+            // The text is null-terminated, but don't read past the end of the allocation.
+            let max_len = GlobalSize(handle) / mem::size_of::<u16>();
+            let data = slice::from_raw_parts(lock.data, max_len);
+            let len = data.iter().position(|&c| c == 0).unwrap_or(max_len);
+
+            String::from_utf16_lossy(&data[..len])
+        };
+
+        Ok(Some(text))
+    }
+
+    /// Replace the contents of the clipboard with text.
+    pub fn set_clipboard_text(&self, text: &str) -> Result<(), Error> {
+        let mut wide = text.encode_utf16().collect::<Vec<_>>();
+        wide.push(0);
+
+        // Copy the text into memory that the clipboard can take ownership of.
+        let handle = unsafe { GlobalAlloc(GMEM_MOVEABLE, wide.len() * mem::size_of::<u16>()) };
+        if handle == 0 {
+            return Err(Error::last_error("GlobalAlloc"));
+        }
+
+        let result = (|| unsafe {
+            {
+                let lock = GlobalLockGuard::lock(handle)?;
+                ptr::copy_nonoverlapping(wide.as_ptr(), lock.data, wide.len());
+            }
+
+            let _clipboard = OpenedClipboard::open()?;
+
+            if EmptyClipboard() == 0 {
+                return Err(Error::last_error("EmptyClipboard"));
+            }
+
+            if SetClipboardData(CF_UNICODETEXT, handle) == 0 {
+                return Err(Error::last_error("SetClipboardData"));
+            }
+
+            Ok(())
+        })();
+
+        // On success, the system owns the memory. Otherwise, we need to free it.
+        if result.is_err() {
+            unsafe {
+                GlobalFree(handle);
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clipboard_text() {
+        let client = Client::new();
+        let text = "porcupine clipboard test ✓";
+
+        client
+            .set_clipboard_text(text)
+            .expect("Failed to set clipboard text");
+        assert_eq!(
+            client
+                .clipboard_text()
+                .expect("Failed to get clipboard text")
+                .as_deref(),
+            Some(text)
+        );
+    }
+}
@@ -0,0 +1,82 @@
+// Boost/Apache2 License
+
+//! Colors for drawing.
+
+/// An RGB color.
+///
+/// Win32 stores colors as `COLORREF`s, which are laid out as `0x00BBGGRR`. That is the reverse
+/// of the usual hex notation, so building them by hand tends to swap red and blue. Anything that
+/// takes a `Color` also takes a raw `COLORREF` through `From<u32>`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Color {
+    /// The `COLORREF` for this color.
+    colorref: u32,
+}
+
+impl Color {
+    /// Pure black.
+    pub const BLACK: Self = Self::rgb(0, 0, 0);
+
+    /// Pure white.
+    pub const WHITE: Self = Self::rgb(0xFF, 0xFF, 0xFF);
+
+    /// Create a color from its red, green and blue components.
+    pub const fn rgb(red: u8, green: u8, blue: u8) -> Self {
+        Self {
+            colorref: (red as u32) | ((green as u32) << 8) | ((blue as u32) << 16),
+        }
+    }
+
+    /// Create a color from a raw `COLORREF`.
+    pub const fn from_colorref(colorref: u32) -> Self {
+        Self { colorref }
+    }
+
+    /// Get the raw `COLORREF` for this color.
+    pub const fn colorref(self) -> u32 {
+        self.colorref
+    }
+
+    /// Get the red component.
+    pub const fn red(self) -> u8 {
+        self.colorref as u8
+    }
+
+    /// Get the green component.
+    pub const fn green(self) -> u8 {
+        (self.colorref >> 8) as u8
+    }
+
+    /// Get the blue component.
+    pub const fn blue(self) -> u8 {
+        (self.colorref >> 16) as u8
+    }
+}
+
+impl From<u32> for Color {
+    fn from(colorref: u32) -> Self {
+        Self::from_colorref(colorref)
+    }
+}
+
+impl From<Color> for u32 {
+    fn from(color: Color) -> Self {
+        color.colorref
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rgb() {
+        let color = Color::rgb(0x12, 0x34, 0x56);
+        assert_eq!(color.colorref(), 0x0056_3412);
+        assert_eq!(
+            (color.red(), color.green(), color.blue()),
+            (0x12, 0x34, 0x56)
+        );
+        assert_eq!(Color::from(0x0056_3412), color);
+    }
+}
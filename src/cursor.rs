@@ -0,0 +1,128 @@
+// Boost/Apache2 License
+
+//! Mouse cursors.
+
+use crate::client::Client;
+use crate::Error;
+
+use core::cell::Cell;
+use core::marker::PhantomData;
+
+use windows_sys::core::PCWSTR;
+use windows_sys::Win32::UI::WindowsAndMessaging::HCURSOR;
+use windows_sys::Win32::UI::WindowsAndMessaging::{LoadCursorW, SetCursor};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    IDC_APPSTARTING, IDC_ARROW, IDC_CROSS, IDC_HAND, IDC_HELP, IDC_IBEAM, IDC_NO, IDC_SIZEALL,
+    IDC_SIZENESW, IDC_SIZENS, IDC_SIZENWSE, IDC_SIZEWE, IDC_UPARROW, IDC_WAIT,
+};
+
+/// A mouse cursor.
+pub struct Cursor {
+    /// The handle to the cursor.
+    handle: HCURSOR,
+
+    /// This handle is `Send` but `!Sync`.
+    _thread_safety: PhantomData<Cell<()>>,
+}
+
+/// The cursors provided by the system.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StandardCursor {
+    /// The standard arrow.
+    Arrow,
+
+    /// The text selection I-beam.
+    IBeam,
+
+    /// The hourglass.
+    Wait,
+
+    /// The crosshair.
+    Cross,
+
+    /// The vertical arrow.
+    UpArrow,
+
+    /// The double-pointed arrow pointing northwest and southeast.
+    SizeNwse,
+
+    /// The double-pointed arrow pointing northeast and southwest.
+    SizeNesw,
+
+    /// The double-pointed arrow pointing west and east.
+    SizeWe,
+
+    /// The double-pointed arrow pointing north and south.
+    SizeNs,
+
+    /// The four-pointed arrow pointing north, south, east, and west.
+    SizeAll,
+
+    /// The slashed circle.
+    No,
+
+    /// The hand.
+    Hand,
+
+    /// The standard arrow with a small hourglass.
+    AppStarting,
+
+    /// The arrow with a question mark.
+    Help,
+}
+
+impl StandardCursor {
+    /// Get the resource identifier for this cursor.
+    fn resource(self) -> PCWSTR {
+        match self {
+            Self::Arrow => IDC_ARROW,
+            Self::IBeam => IDC_IBEAM,
+            Self::Wait => IDC_WAIT,
+            Self::Cross => IDC_CROSS,
+            Self::UpArrow => IDC_UPARROW,
+            Self::SizeNwse => IDC_SIZENWSE,
+            Self::SizeNesw => IDC_SIZENESW,
+            Self::SizeWe => IDC_SIZEWE,
+            Self::SizeNs => IDC_SIZENS,
+            Self::SizeAll => IDC_SIZEALL,
+            Self::No => IDC_NO,
+            Self::Hand => IDC_HAND,
+            Self::AppStarting => IDC_APPSTARTING,
+            Self::Help => IDC_HELP,
+        }
+    }
+}
+
+impl Cursor {
+    /// Load one of the cursors provided by the system.
+    ///
+    /// System cursors are shared, so they are never destroyed.
+    pub fn load(cursor: StandardCursor) -> Result<Self, Error> {
+        let handle = unsafe { LoadCursorW(0, cursor.resource()) };
+
+        if handle == 0 {
+            Err(Error::last_error("LoadCursorW"))
+        } else {
+            Ok(Self {
+                handle,
+                _thread_safety: PhantomData,
+            })
+        }
+    }
+
+    pub(crate) fn handle(&self) -> HCURSOR {
+        self.handle
+    }
+}
+
+impl Client {
+    /// Set the cursor shape.
+    ///
+    /// The system resets the cursor whenever the mouse moves unless the window class has no
+    /// cursor, so this is typically called while handling `WM_SETCURSOR`.
+    pub fn set_cursor(&self, cursor: &Cursor) {
+        unsafe {
+            SetCursor(cursor.handle);
+        }
+    }
+}
@@ -3,29 +3,54 @@
 //! Functions for managing device contexts.
 
 use crate::bitmap::Bitmap;
-use crate::gdi_object::OwnedGdiObject;
+use crate::brush::Brush;
+use crate::client::Client;
+use crate::color::Color;
+use crate::gdi_object::{AsGdiObject, OwnedGdiObject};
+use crate::geometry::{point_from_win32, size_from_win32, size_to_win32};
 use crate::region::Region;
 use crate::window::{BorrowedWindow, GetDcFlags, RegionType};
 use crate::Error;
 use __sealed::Sealed;
 use blood_geometry::{Point, Rect, Size};
 
+use alloc::vec::Vec;
+
 use core::cell::Cell;
 use core::marker::PhantomData;
 use core::mem::MaybeUninit;
 
+use windows_sys::Win32::Graphics::Gdi::{
+    Arc, Chord, Pie, SetArcDirection, AD_CLOCKWISE, AD_COUNTERCLOCKWISE,
+};
 use windows_sys::Win32::Graphics::Gdi::{
     BeginPaint, BitBlt, CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, EndPaint, GetDCEx,
     ReleaseDC, SetPixel, StretchBlt, SelectObject, MoveToEx
 };
+use windows_sys::Win32::Graphics::Gdi::{ExtFloodFill, FLOODFILLBORDER, FLOODFILLSURFACE};
+use windows_sys::Win32::Graphics::Gdi::{FillRect, GetClipBox, RGN_ERROR};
+use windows_sys::Win32::Graphics::Gdi::{
+    SetBrushOrgEx, SetMapMode, SetROP2, SetViewportExtEx, SetViewportOrgEx, SetWindowExtEx,
+    SetWindowOrgEx,
+};
 use windows_sys::Win32::Graphics::Gdi::{
     BLACKNESS, CAPTUREBLT, DCX_EXCLUDERGN, DCX_INTERSECTRGN, DSTINVERT, MERGECOPY, MERGEPAINT,
     NOTSRCCOPY, NOTSRCERASE, PATCOPY, PATINVERT, PATPAINT, SRCAND, SRCCOPY, SRCERASE, SRCINVERT,
     SRCPAINT, WHITENESS,
 };
 use windows_sys::Win32::Graphics::Gdi::{HDC, PAINTSTRUCT};
+use windows_sys::Win32::Graphics::Gdi::{
+    MM_ANISOTROPIC, MM_HIENGLISH, MM_HIMETRIC, MM_ISOTROPIC, MM_LOENGLISH, MM_LOMETRIC, MM_TEXT,
+    MM_TWIPS,
+};
+use windows_sys::Win32::Graphics::Gdi::{
+    R2_BLACK, R2_COPYPEN, R2_MASKNOTPEN, R2_MASKPEN, R2_MASKPENNOT, R2_MERGENOTPEN, R2_MERGEPEN,
+    R2_MERGEPENNOT, R2_NOP, R2_NOT, R2_NOTCOPYPEN, R2_NOTMASKPEN, R2_NOTMERGEPEN, R2_NOTXORPEN,
+    R2_WHITE, R2_XORPEN,
+};
 
 use windows_sys::Win32::Foundation::HWND;
+use windows_sys::Win32::Foundation::{BOOL, POINT, RECT, SIZE};
 
 /// A device context.
 pub struct DeviceContext<Releaser: ReleaseDC + ?Sized> {
@@ -86,6 +111,36 @@ unsafe impl Sealed for DeleteReleaser {
 
 unsafe impl ReleaseDC for DeleteReleaser {}
 
+/// A DC Releaser for device contexts that are owned and released by someone else.
+pub struct BorrowedReleaser<'a> {
+    _marker: PhantomData<&'a ()>,
+}
+
+unsafe impl Sealed for BorrowedReleaser<'_> {
+    unsafe fn release_dc(&mut self, _dc: HDC) {
+        // Do nothing, the owner releases it.
+    }
+}
+
+unsafe impl ReleaseDC for BorrowedReleaser<'_> {}
+
+impl<'a> DeviceContext<BorrowedReleaser<'a>> {
+    /// Borrow a device context owned by someone else.
+    ///
+    /// # Safety
+    ///
+    /// `dc` must be a valid device context for the lifetime `'a`.
+    pub(crate) unsafe fn borrowed(dc: HDC) -> Self {
+        Self {
+            handle: dc,
+            _thread_safety: PhantomData,
+            releaser: BorrowedReleaser {
+                _marker: PhantomData,
+            },
+        }
+    }
+}
+
 impl<'a> DeviceContext<PaintReleaser<'a>> {
     /// Begin painting a window.
     pub(crate) fn begin_paint<R>(
@@ -169,7 +224,20 @@ impl<'a> DeviceContext<GetReleaser<'a>> {
     }
 }
 
+impl Client {
+    /// Get a device context for the whole screen.
+    ///
+    /// Blitting from this device context takes a screenshot.
+    pub fn screen_dc(&self) -> Result<DeviceContext<GetReleaser<'static>>, Error> {
+        DeviceContext::get_dc(None, RegionType::None, GetDcFlags::empty())
+    }
+}
+
 impl<Releaser: ReleaseDC + ?Sized> DeviceContext<Releaser> {
+    pub(crate) fn handle(&self) -> HDC {
+        self.handle
+    }
+
     /// Create a compatible device context with this one.
     pub fn create_compatible_dc(&self) -> Result<DeviceContext<DeleteReleaser>, Error> {
         let dc = unsafe { CreateCompatibleDC(self.handle) };
@@ -201,6 +269,36 @@ impl<Releaser: ReleaseDC + ?Sized> DeviceContext<Releaser> {
         }
     }
 
+    /// Draw into an off-screen bitmap compatible with this device context.
+    ///
+    /// `f` draws into a memory device context with a new bitmap of the given size selected.
+    /// Afterwards, the original bitmap is restored and the drawn bitmap is returned, so it can be
+    /// blitted to this device context in one step to avoid flickering.
+    pub fn with_back_buffer<R>(
+        &self,
+        size: Size<i32>,
+        f: impl FnOnce(&DeviceContext<DeleteReleaser>) -> Result<R, Error>,
+    ) -> Result<(R, Bitmap), Error> {
+        let dc = self.create_compatible_dc()?;
+
+        // If drawing fails or panics, the original bitmap is selected back in and the drawn
+        // bitmap is deleted.
+        let mut restore = Restore {
+            dc: &dc,
+            originals: Vec::new(),
+        };
+        restore
+            .originals
+            .push(dc.select_object(self.create_compatible_bitmap(size)?)?);
+
+        let result = f(&dc)?;
+
+        // Select the original bitmap back in before the DC is deleted, and keep the drawn one.
+        let old_bitmap = restore.originals.pop().expect("the bitmap was selected");
+        let bitmap = Bitmap::from(dc.select_object(old_bitmap)?);
+        Ok((result, bitmap))
+    }
+
     /// Select a GDI object into this device context.
     pub fn select_object(
         &self,
@@ -216,6 +314,27 @@ impl<Releaser: ReleaseDC + ?Sized> DeviceContext<Releaser> {
         }
     }
 
+    /// Select several GDI objects into this device context while running `f`.
+    ///
+    /// The objects are selected in order, and the original objects are selected back in reverse
+    /// order afterwards, even if `f` fails or panics. The selected objects are then deleted.
+    pub fn with_objects<R>(
+        &self,
+        objects: impl IntoIterator<Item = OwnedGdiObject>,
+        f: impl FnOnce(&Self) -> Result<R, Error>,
+    ) -> Result<R, Error> {
+        let mut restore = Restore {
+            dc: self,
+            originals: Vec::new(),
+        };
+
+        for object in objects {
+            restore.originals.push(self.select_object(object)?);
+        }
+
+        f(self)
+    }
+
     /// Preform a bit-block color transfer from one DC to another.
     pub fn bit_blt(
         &self,
@@ -301,9 +420,9 @@ impl<Releaser: ReleaseDC + ?Sized> DeviceContext<Releaser> {
     }
 
     /// Set a pixel in the device context.
-    pub fn set_pixel(&self, point: Point<i32>, color: u32) -> Result<(), Error> {
+    pub fn set_pixel(&self, point: Point<i32>, color: impl Into<Color>) -> Result<(), Error> {
         let [x, y]: [i32; 2] = point.into();
-        let result = unsafe { SetPixel(self.handle, x, y, color) };
+        let result = unsafe { SetPixel(self.handle, x, y, color.into().colorref()) };
 
         // If SetPixel failed, return an error.
         if result == 0 {
@@ -312,6 +431,406 @@ impl<Releaser: ReleaseDC + ?Sized> DeviceContext<Releaser> {
             Ok(())
         }
     }
+
+    /// Fill an area with the current brush, starting at `point`.
+    ///
+    /// With [`FloodFillMode::Border`], the fill spreads until it reaches pixels of `color`. With
+    /// [`FloodFillMode::Surface`], it spreads over the pixels of `color` around `point`.
+    pub fn flood_fill(
+        &self,
+        point: Point<i32>,
+        color: impl Into<Color>,
+        mode: FloodFillMode,
+    ) -> Result<(), Error> {
+        let [x, y]: [i32; 2] = point.into();
+        let color = color.into().colorref();
+        let result = unsafe { ExtFloodFill(self.handle, x, y, color, mode as _) };
+
+        // If ExtFloodFill failed, return an error.
+        if result == 0 {
+            Err(Error::last_error("ExtFloodFill"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Fill the whole drawable area of the device context with a solid color.
+    pub fn clear(&self, color: impl Into<Color>) -> Result<(), Error> {
+        let mut rect = MaybeUninit::<RECT>::uninit();
+        let result = unsafe { GetClipBox(self.handle, rect.as_mut_ptr()) };
+
+        // If GetClipBox failed, return an error.
+        if result == RGN_ERROR {
+            return Err(Error::last_error("GetClipBox"));
+        }
+
+        // The brush is deleted once it goes out of scope.
+        let brush = Brush::solid(color)?;
+        let result =
+            unsafe { FillRect(self.handle, rect.as_ptr(), brush.as_gdi_object().as_raw()) };
+
+        // If FillRect failed, return an error.
+        if result == 0 {
+            Err(Error::last_error("FillRect"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Draw an elliptical arc with the current pen.
+    ///
+    /// The arc is part of the ellipse bounded by `rect`. It starts where the ellipse meets the
+    /// line from its center to `start`, and ends where it meets the line to `end`. It is drawn
+    /// in the current [arc direction](Self::set_arc_direction).
+    pub fn arc(&self, rect: Rect<i32>, start: Point<i32>, end: Point<i32>) -> Result<(), Error> {
+        self.draw_elliptic(Arc, "Arc", rect, start, end)
+    }
+
+    /// Draw a pie slice with the current pen and fill it with the current brush.
+    ///
+    /// The slice is bounded by the same arc as [`arc`](Self::arc), and by the two radials that
+    /// join its ends to the center of the ellipse.
+    pub fn pie(&self, rect: Rect<i32>, start: Point<i32>, end: Point<i32>) -> Result<(), Error> {
+        self.draw_elliptic(Pie, "Pie", rect, start, end)
+    }
+
+    /// Draw a chord with the current pen and fill it with the current brush.
+    ///
+    /// The chord is bounded by the same arc as [`arc`](Self::arc), and by the line that joins
+    /// its ends.
+    pub fn chord(&self, rect: Rect<i32>, start: Point<i32>, end: Point<i32>) -> Result<(), Error> {
+        self.draw_elliptic(Chord, "Chord", rect, start, end)
+    }
+
+    /// Set the direction that arcs, pies and chords are drawn in.
+    ///
+    /// Returns the previous direction.
+    pub fn set_arc_direction(&self, direction: ArcDirection) -> Result<ArcDirection, Error> {
+        let result = unsafe { SetArcDirection(self.handle, direction as _) };
+
+        // If SetArcDirection failed, return an error.
+        if result as u32 == AD_CLOCKWISE {
+            Ok(ArcDirection::Clockwise)
+        } else if result as u32 == AD_COUNTERCLOCKWISE {
+            Ok(ArcDirection::CounterClockwise)
+        } else {
+            Err(Error::last_error("SetArcDirection"))
+        }
+    }
+
+    /// Set how logical coordinates are mapped to device pixels.
+    ///
+    /// In every mode except [`MapMode::Text`], [`MapMode::Isotropic`] and
+    /// [`MapMode::Anisotropic`], the Y axis points up, so points below the origin have negative
+    /// Y coordinates. Returns the previous mode.
+    pub fn set_map_mode(&self, mode: MapMode) -> Result<MapMode, Error> {
+        let result = unsafe { SetMapMode(self.handle, mode as _) };
+
+        // If SetMapMode failed, return an error.
+        match MapMode::from_raw(result as u32) {
+            Some(previous) => Ok(previous),
+            None => Err(Error::last_error("SetMapMode")),
+        }
+    }
+
+    /// Set the extents of the window, in logical units.
+    ///
+    /// Together with the [viewport extents](Self::set_viewport_ext), this sets the scale of
+    /// logical coordinates. It is only used by [`MapMode::Isotropic`] and
+    /// [`MapMode::Anisotropic`]. Returns the previous extents.
+    pub fn set_window_ext(&self, extent: Size<i32>) -> Result<Size<i32>, Error> {
+        let SIZE { cx: x, cy: y } = size_to_win32(extent);
+        let mut previous = MaybeUninit::<SIZE>::uninit();
+        let result = unsafe { SetWindowExtEx(self.handle, x, y, previous.as_mut_ptr()) };
+
+        // If SetWindowExtEx failed, return an error.
+        if result == 0 {
+            Err(Error::last_error("SetWindowExtEx"))
+        } else {
+            Ok(size_from_win32(unsafe { previous.assume_init() }))
+        }
+    }
+
+    /// Set the extents of the viewport, in device units.
+    ///
+    /// It is only used by [`MapMode::Isotropic`] and [`MapMode::Anisotropic`]. Returns the
+    /// previous extents.
+    pub fn set_viewport_ext(&self, extent: Size<i32>) -> Result<Size<i32>, Error> {
+        let SIZE { cx: x, cy: y } = size_to_win32(extent);
+        let mut previous = MaybeUninit::<SIZE>::uninit();
+        let result = unsafe { SetViewportExtEx(self.handle, x, y, previous.as_mut_ptr()) };
+
+        // If SetViewportExtEx failed, return an error.
+        if result == 0 {
+            Err(Error::last_error("SetViewportExtEx"))
+        } else {
+            Ok(size_from_win32(unsafe { previous.assume_init() }))
+        }
+    }
+
+    /// Set the logical point that maps to the viewport origin.
+    ///
+    /// Returns the previous origin.
+    pub fn set_window_org(&self, origin: Point<i32>) -> Result<Point<i32>, Error> {
+        let [x, y]: [i32; 2] = origin.into();
+        let mut previous = MaybeUninit::<POINT>::uninit();
+        let result = unsafe { SetWindowOrgEx(self.handle, x, y, previous.as_mut_ptr()) };
+
+        // If SetWindowOrgEx failed, return an error.
+        if result == 0 {
+            Err(Error::last_error("SetWindowOrgEx"))
+        } else {
+            Ok(point_from_win32(unsafe { previous.assume_init() }))
+        }
+    }
+
+    /// Set the device point that the window origin maps to.
+    ///
+    /// Returns the previous origin.
+    pub fn set_viewport_org(&self, origin: Point<i32>) -> Result<Point<i32>, Error> {
+        let [x, y]: [i32; 2] = origin.into();
+        let mut previous = MaybeUninit::<POINT>::uninit();
+        let result = unsafe { SetViewportOrgEx(self.handle, x, y, previous.as_mut_ptr()) };
+
+        // If SetViewportOrgEx failed, return an error.
+        if result == 0 {
+            Err(Error::last_error("SetViewportOrgEx"))
+        } else {
+            Ok(point_from_win32(unsafe { previous.assume_init() }))
+        }
+    }
+
+    /// Set how the pen and brush are combined with the existing pixels when drawing.
+    ///
+    /// Drawing a shape twice with [`Rop2::XorPen`] or [`Rop2::NotXorPen`] restores the original
+    /// pixels, which is useful for rubber-band selections. Returns the previous mode.
+    pub fn set_rop2(&self, mode: Rop2) -> Result<Rop2, Error> {
+        let result = unsafe { SetROP2(self.handle, mode as _) };
+
+        // If SetROP2 failed, return an error.
+        match Rop2::from_raw(result) {
+            Some(previous) => Ok(previous),
+            None => Err(Error::last_error("SetROP2")),
+        }
+    }
+
+    /// Set the point in device coordinates that the next selected brush's pattern starts at.
+    ///
+    /// This aligns tiled pattern brushes, and must also be called after setting the stretch
+    /// mode to halftone. Returns the previous origin.
+    pub fn set_brush_origin(&self, point: Point<i32>) -> Result<Point<i32>, Error> {
+        let [x, y]: [i32; 2] = point.into();
+        let mut previous = MaybeUninit::<POINT>::uninit();
+        let result = unsafe { SetBrushOrgEx(self.handle, x, y, previous.as_mut_ptr()) };
+
+        // If SetBrushOrgEx failed, return an error.
+        if result == 0 {
+            Err(Error::last_error("SetBrushOrgEx"))
+        } else {
+            Ok(point_from_win32(unsafe { previous.assume_init() }))
+        }
+    }
+
+    /// Call one of the GDI functions that draw part of an ellipse.
+    fn draw_elliptic(
+        &self,
+        function: unsafe extern "system" fn(HDC, i32, i32, i32, i32, i32, i32, i32, i32) -> BOOL,
+        name: &'static str,
+        rect: Rect<i32>,
+        start: Point<i32>,
+        end: Point<i32>,
+    ) -> Result<(), Error> {
+        let [left, top]: [i32; 2] = rect.origin().into();
+        let [width, height]: [i32; 2] = rect.size().into();
+        let [x_start, y_start]: [i32; 2] = start.into();
+        let [x_end, y_end]: [i32; 2] = end.into();
+
+        let result = unsafe {
+            function(
+                self.handle,
+                left,
+                top,
+                left + width,
+                top + height,
+                x_start,
+                y_start,
+                x_end,
+                y_end,
+            )
+        };
+
+        if result == 0 {
+            Err(Error::last_error(name))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Selects the original objects back into a device context when dropped.
+struct Restore<'a, Releaser: ReleaseDC + ?Sized> {
+    dc: &'a DeviceContext<Releaser>,
+    originals: Vec<OwnedGdiObject>,
+}
+
+impl<Releaser: ReleaseDC + ?Sized> Drop for Restore<'_, Releaser> {
+    fn drop(&mut self) {
+        // The objects that are selected out are deleted.
+        while let Some(original) = self.originals.pop() {
+            if let Err(err) = self.dc.select_object(original) {
+                tracing::error!("Failed to restore a GDI object: {}", err);
+            }
+        }
+    }
+}
+
+/// How logical coordinates are mapped to device pixels.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u32)]
+pub enum MapMode {
+    /// Each logical unit is one pixel, and the Y axis points down. This is the default.
+    Text = MM_TEXT,
+
+    /// Each logical unit is 0.1 millimeters.
+    LoMetric = MM_LOMETRIC,
+
+    /// Each logical unit is 0.01 millimeters.
+    HiMetric = MM_HIMETRIC,
+
+    /// Each logical unit is 0.01 inches.
+    LoEnglish = MM_LOENGLISH,
+
+    /// Each logical unit is 0.001 inches.
+    HiEnglish = MM_HIENGLISH,
+
+    /// Each logical unit is a twentieth of a printer's point, or 1/1440 inches.
+    Twips = MM_TWIPS,
+
+    /// The scale is set by the window and viewport extents, and is the same on both axes.
+    Isotropic = MM_ISOTROPIC,
+
+    /// The scale is set by the window and viewport extents.
+    Anisotropic = MM_ANISOTROPIC,
+}
+
+impl MapMode {
+    /// Convert an `MM_*` constant into a `MapMode`.
+    fn from_raw(mode: u32) -> Option<Self> {
+        Some(match mode {
+            MM_TEXT => Self::Text,
+            MM_LOMETRIC => Self::LoMetric,
+            MM_HIMETRIC => Self::HiMetric,
+            MM_LOENGLISH => Self::LoEnglish,
+            MM_HIENGLISH => Self::HiEnglish,
+            MM_TWIPS => Self::Twips,
+            MM_ISOTROPIC => Self::Isotropic,
+            MM_ANISOTROPIC => Self::Anisotropic,
+            _ => return None,
+        })
+    }
+}
+
+/// How the pen and brush are combined with the existing pixels.
+///
+/// In the descriptions, "pen" is the color of the pen or brush, and "screen" is the existing
+/// color.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(i32)]
+pub enum Rop2 {
+    /// The pixel is always black.
+    Black = R2_BLACK,
+
+    /// The inverse of `MergePen`.
+    NotMergePen = R2_NOTMERGEPEN,
+
+    /// The screen and the inverse of the pen.
+    MaskNotPen = R2_MASKNOTPEN,
+
+    /// The inverse of the pen.
+    NotCopyPen = R2_NOTCOPYPEN,
+
+    /// The pen and the inverse of the screen.
+    MaskPenNot = R2_MASKPENNOT,
+
+    /// The inverse of the screen.
+    Not = R2_NOT,
+
+    /// The pen or the screen, but not both.
+    XorPen = R2_XORPEN,
+
+    /// The inverse of `MaskPen`.
+    NotMaskPen = R2_NOTMASKPEN,
+
+    /// The pen and the screen.
+    MaskPen = R2_MASKPEN,
+
+    /// The inverse of `XorPen`.
+    NotXorPen = R2_NOTXORPEN,
+
+    /// The pixel is left unchanged.
+    Nop = R2_NOP,
+
+    /// The screen or the inverse of the pen.
+    MergeNotPen = R2_MERGENOTPEN,
+
+    /// The pen. This is the default.
+    CopyPen = R2_COPYPEN,
+
+    /// The pen or the inverse of the screen.
+    MergePenNot = R2_MERGEPENNOT,
+
+    /// The pen or the screen.
+    MergePen = R2_MERGEPEN,
+
+    /// The pixel is always white.
+    White = R2_WHITE,
+}
+
+impl Rop2 {
+    /// Convert an `R2_*` constant into a `Rop2`.
+    fn from_raw(mode: i32) -> Option<Self> {
+        Some(match mode {
+            R2_BLACK => Self::Black,
+            R2_NOTMERGEPEN => Self::NotMergePen,
+            R2_MASKNOTPEN => Self::MaskNotPen,
+            R2_NOTCOPYPEN => Self::NotCopyPen,
+            R2_MASKPENNOT => Self::MaskPenNot,
+            R2_NOT => Self::Not,
+            R2_XORPEN => Self::XorPen,
+            R2_NOTMASKPEN => Self::NotMaskPen,
+            R2_MASKPEN => Self::MaskPen,
+            R2_NOTXORPEN => Self::NotXorPen,
+            R2_NOP => Self::Nop,
+            R2_MERGENOTPEN => Self::MergeNotPen,
+            R2_COPYPEN => Self::CopyPen,
+            R2_MERGEPENNOT => Self::MergePenNot,
+            R2_MERGEPEN => Self::MergePen,
+            R2_WHITE => Self::White,
+            _ => return None,
+        })
+    }
+}
+
+/// How [`DeviceContext::flood_fill`] decides which area to fill.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u32)]
+pub enum FloodFillMode {
+    /// Fill the area bounded by the given color.
+    Border = FLOODFILLBORDER,
+
+    /// Fill the area that has the given color.
+    Surface = FLOODFILLSURFACE,
+}
+
+/// The direction that arcs are drawn in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ArcDirection {
+    /// Arcs are drawn counterclockwise. This is the default.
+    CounterClockwise = AD_COUNTERCLOCKWISE,
+
+    /// Arcs are drawn clockwise.
+    Clockwise = AD_CLOCKWISE,
 }
 
 /// Operations for bit-block device transfer.
@@ -392,3 +911,259 @@ mod __sealed {
         unsafe fn release_dc(&mut self, dc: HDC);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a memory device context that is compatible with the screen.
+    fn memory_dc() -> DeviceContext<DeleteReleaser> {
+        let dc = unsafe { CreateCompatibleDC(0) };
+        assert_ne!(dc, 0, "Failed to create a memory DC");
+
+        DeviceContext {
+            handle: dc,
+            _thread_safety: PhantomData,
+            releaser: DeleteReleaser {
+                _marker: PhantomData,
+            },
+        }
+    }
+
+    #[test]
+    fn test_pie() {
+        let dc = memory_dc();
+
+        let rect = Rect::new(Point::new(0, 0), Size::new(20, 20));
+        dc.pie(rect, Point::new(20, 10), Point::new(10, 0))
+            .expect("Failed to draw pie");
+        dc.arc(rect, Point::new(0, 10), Point::new(10, 20))
+            .expect("Failed to draw arc");
+        dc.chord(rect, Point::new(0, 0), Point::new(20, 20))
+            .expect("Failed to draw chord");
+
+        let previous = dc
+            .set_arc_direction(ArcDirection::Clockwise)
+            .expect("Failed to set arc direction");
+        assert_eq!(previous, ArcDirection::CounterClockwise);
+        assert_eq!(
+            dc.set_arc_direction(ArcDirection::CounterClockwise)
+                .unwrap(),
+            ArcDirection::Clockwise
+        );
+    }
+
+    #[test]
+    fn test_rop2() {
+        use alloc::vec::Vec;
+        use windows_sys::Win32::Graphics::Gdi::GetPixel;
+
+        let dc = memory_dc();
+        let bitmap = dc.create_compatible_bitmap(Size::new(16, 16)).unwrap();
+        let _old = dc.select_object(bitmap).unwrap();
+
+        let pixels = || {
+            (0..16)
+                .flat_map(|y| (0..16).map(move |x| (x, y)))
+                .map(|(x, y)| unsafe { GetPixel(dc.handle, x, y) })
+                .collect::<Vec<_>>()
+        };
+        let original = pixels();
+
+        let previous = dc.set_rop2(Rop2::NotXorPen).expect("Failed to set ROP2");
+        assert_eq!(previous, Rop2::CopyPen);
+
+        // The default pen is black, so this inverts the pixels under the arc.
+        let rect = Rect::new(Point::new(0, 0), Size::new(16, 16));
+        dc.arc(rect, Point::new(0, 0), Point::new(0, 0)).unwrap();
+        assert_ne!(pixels(), original);
+
+        // Drawing it again restores them.
+        dc.arc(rect, Point::new(0, 0), Point::new(0, 0)).unwrap();
+        assert_eq!(pixels(), original);
+
+        assert_eq!(dc.set_rop2(Rop2::XorPen).unwrap(), Rop2::NotXorPen);
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        use crate::brush::Brush;
+        use windows_sys::Win32::Graphics::Gdi::GetPixel;
+
+        let dc = memory_dc();
+        let dib = Bitmap::create_dib_section(&dc, 16, -16, 32).unwrap();
+        let _old_bitmap = dc.select_object(dib).unwrap();
+
+        // Draw a black circle on a white background.
+        let rect = Rect::new(Point::new(0, 0), Size::new(16, 16));
+        dc.bit_blt(&dc, rect, Point::new(0, 0), BitBltOp::Whiteness)
+            .unwrap();
+        dc.arc(rect, Point::new(0, 0), Point::new(0, 0)).unwrap();
+
+        // Fill the inside of the circle with red.
+        let _old_brush = dc.select_object(Brush::solid(0x0000FF).unwrap()).unwrap();
+        dc.flood_fill(Point::new(8, 8), 0x000000, FloodFillMode::Border)
+            .expect("Failed to flood fill");
+
+        assert_eq!(unsafe { GetPixel(dc.handle, 8, 8) }, 0x0000FF);
+        assert_eq!(unsafe { GetPixel(dc.handle, 0, 0) }, 0xFFFFFF);
+
+        // Fill the red surface with green.
+        let _red_brush = dc.select_object(Brush::solid(0x00FF00).unwrap()).unwrap();
+        dc.flood_fill(Point::new(8, 8), 0x0000FF, FloodFillMode::Surface)
+            .expect("Failed to flood fill");
+
+        assert_eq!(unsafe { GetPixel(dc.handle, 8, 8) }, 0x00FF00);
+        assert_eq!(unsafe { GetPixel(dc.handle, 0, 0) }, 0xFFFFFF);
+    }
+
+    #[test]
+    fn test_with_objects() {
+        use crate::brush::Brush;
+        use windows_sys::Win32::Graphics::Gdi::GetPixel;
+
+        let dc = memory_dc();
+        let dib = Bitmap::create_dib_section(&dc, 16, -16, 32).unwrap();
+        let _old_bitmap = dc.select_object(dib).unwrap();
+
+        let rect = Rect::new(Point::new(0, 0), Size::new(16, 16));
+        dc.bit_blt(&dc, rect, Point::new(0, 0), BitBltOp::Whiteness)
+            .unwrap();
+
+        // Fill the white surface with red.
+        let red = Brush::solid(0x0000FF).unwrap();
+        dc.with_objects(Some(red.into()), |dc| {
+            dc.flood_fill(Point::new(8, 8), 0xFFFFFF, FloodFillMode::Surface)
+        })
+        .expect("Failed to draw with objects");
+        assert_eq!(unsafe { GetPixel(dc.handle, 8, 8) }, 0x0000FF);
+
+        // The original objects are restored even if drawing fails.
+        let green = Brush::solid(0x00FF00).unwrap();
+        let result = dc.with_objects(Some(green.into()), |_| -> Result<(), Error> {
+            Err(Error::last_error("Test"))
+        });
+        assert!(result.is_err());
+
+        // The stock white brush is selected again.
+        dc.flood_fill(Point::new(8, 8), 0x0000FF, FloodFillMode::Surface)
+            .expect("Failed to flood fill");
+        assert_eq!(unsafe { GetPixel(dc.handle, 8, 8) }, 0xFFFFFF);
+    }
+
+    #[test]
+    fn test_brush_origin() {
+        let dc = memory_dc();
+
+        let previous = dc
+            .set_brush_origin(Point::new(3, 5))
+            .expect("Failed to set brush origin");
+        assert_eq!(previous, Point::new(0, 0));
+        assert_eq!(
+            dc.set_brush_origin(Point::new(0, 0)).unwrap(),
+            Point::new(3, 5)
+        );
+    }
+
+    #[test]
+    fn test_back_buffer() {
+        use windows_sys::Win32::Graphics::Gdi::GetPixel;
+
+        let dc = memory_dc();
+        let dib = Bitmap::create_dib_section(&dc, 8, -8, 32).unwrap();
+        let _old_dib = dc.select_object(dib).unwrap();
+
+        // Fill the back buffer with white.
+        let rect = Rect::new(Point::new(0, 0), Size::new(8, 8));
+        let (answer, buffer) = dc
+            .with_back_buffer(Size::new(8, 8), |buffer| {
+                buffer.bit_blt(buffer, rect, Point::new(0, 0), BitBltOp::Whiteness)?;
+                Ok(42)
+            })
+            .expect("Failed to draw into back buffer");
+        assert_eq!(answer, 42);
+        assert_eq!(buffer.dimensions().unwrap(), Size::new(8, 8));
+
+        // Nothing is drawn until the buffer is blitted.
+        assert_eq!(unsafe { GetPixel(dc.handle, 4, 4) }, 0x000000);
+
+        let src = dc.create_compatible_dc().unwrap();
+        let _old_buffer = src.select_object(buffer).unwrap();
+        dc.bit_blt(&src, rect, Point::new(0, 0), BitBltOp::SrcCopy)
+            .unwrap();
+        assert_eq!(unsafe { GetPixel(dc.handle, 4, 4) }, 0xFFFFFF);
+
+        // Errors from drawing are passed through.
+        let result = dc.with_back_buffer(Size::new(8, 8), |_| -> Result<(), Error> {
+            Err(Error::last_error("Test"))
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_clear() {
+        use windows_sys::Win32::Graphics::Gdi::GetPixel;
+
+        let dc = memory_dc();
+        let dib = Bitmap::create_dib_section(&dc, 8, -8, 32).unwrap();
+        let _old_bitmap = dc.select_object(dib).unwrap();
+
+        dc.clear(0xFF0000).expect("Failed to clear");
+        assert_eq!(unsafe { GetPixel(dc.handle, 0, 0) }, 0xFF0000);
+        assert_eq!(unsafe { GetPixel(dc.handle, 7, 7) }, 0xFF0000);
+    }
+
+    #[test]
+    fn test_screen_dc() {
+        let client = Client::new();
+        let screen = client.screen_dc().expect("Failed to get the screen DC");
+
+        // Copy the top-left corner of the screen into a bitmap.
+        let memory = screen.create_compatible_dc().unwrap();
+        let bitmap = screen.create_compatible_bitmap(Size::new(16, 16)).unwrap();
+        let _old_bitmap = memory.select_object(bitmap).unwrap();
+        memory
+            .bit_blt(
+                &screen,
+                Rect::new(Point::new(0, 0), Size::new(16, 16)),
+                Point::new(0, 0),
+                BitBltOp::SrcCopy,
+            )
+            .expect("Failed to copy the screen");
+    }
+
+    #[test]
+    fn test_map_mode() {
+        let dc = memory_dc();
+
+        let previous = dc
+            .set_map_mode(MapMode::LoMetric)
+            .expect("Failed to set map mode");
+        assert_eq!(previous, MapMode::Text);
+
+        // The Y axis points up, so the shape is drawn below the origin.
+        dc.move_to(Point::new(0, 0)).expect("Failed to move");
+        dc.chord(
+            Rect::new(Point::new(0, -100), Size::new(100, 100)),
+            Point::new(0, -50),
+            Point::new(100, -50),
+        )
+        .expect("Failed to draw chord");
+
+        dc.set_map_mode(MapMode::Anisotropic).unwrap();
+        dc.set_window_ext(Size::new(100, 100))
+            .expect("Failed to set window extents");
+        dc.set_viewport_ext(Size::new(50, 50))
+            .expect("Failed to set viewport extents");
+        dc.set_window_org(Point::new(10, 10))
+            .expect("Failed to set window origin");
+        let previous = dc
+            .set_viewport_org(Point::new(5, 5))
+            .expect("Failed to set viewport origin");
+        assert_eq!(previous, Point::new(0, 0));
+        assert_eq!(
+            dc.set_viewport_org(Point::new(0, 0)).unwrap(),
+            Point::new(5, 5)
+        );
+    }
+}
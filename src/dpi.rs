@@ -0,0 +1,137 @@
+// Boost/Apache2 License
+
+//! High-DPI support.
+
+use crate::client::Client;
+use crate::Error;
+
+use core::mem;
+
+use windows_sys::Win32::Foundation::{BOOL, ERROR_PROC_NOT_FOUND, FARPROC, HWND};
+use windows_sys::Win32::System::LibraryLoader::{GetModuleHandleA, GetProcAddress};
+use windows_sys::Win32::UI::HiDpi::{
+    DPI_AWARENESS_CONTEXT, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE,
+    DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2, DPI_AWARENESS_CONTEXT_SYSTEM_AWARE,
+    DPI_AWARENESS_CONTEXT_UNAWARE, DPI_AWARENESS_CONTEXT_UNAWARE_GDISCALED,
+};
+
+/// The DPI that the system treats as 100% scaling.
+pub const DEFAULT_DPI: u32 = 96;
+
+/// How the process handles displays with different DPIs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DpiAwareness {
+    /// The process assumes every display is 96 DPI, and the system scales it up.
+    Unaware,
+
+    /// Like `Unaware`, but text and GDI primitives are rendered at a higher quality.
+    UnawareGdiScaled,
+
+    /// The process uses the DPI of the primary display when it started, and the system scales
+    /// it on other displays.
+    SystemAware,
+
+    /// The process handles the DPI of each display itself, through
+    /// [`Event::DpiChanged`](crate::event::Event::DpiChanged).
+    PerMonitor,
+
+    /// Like `PerMonitor`, but the system also scales the non-client area, menus and dialogs.
+    PerMonitorV2,
+}
+
+impl DpiAwareness {
+    /// Get the `DPI_AWARENESS_CONTEXT` for this mode.
+    fn context(self) -> DPI_AWARENESS_CONTEXT {
+        match self {
+            Self::Unaware => DPI_AWARENESS_CONTEXT_UNAWARE,
+            Self::UnawareGdiScaled => DPI_AWARENESS_CONTEXT_UNAWARE_GDISCALED,
+            Self::SystemAware => DPI_AWARENESS_CONTEXT_SYSTEM_AWARE,
+            Self::PerMonitor => DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE,
+            Self::PerMonitorV2 => DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
+        }
+    }
+}
+
+impl Client {
+    /// Set how the process handles displays with different DPIs.
+    ///
+    /// This should be called before any windows are created. It fails if the awareness has
+    /// already been set, for instance by the application manifest, or if the system is older
+    /// than Windows 10 version 1703.
+    pub fn set_process_dpi_awareness(&self, mode: DpiAwareness) -> Result<(), Error> {
+        const NAME: &str = "SetProcessDpiAwarenessContext";
+
+        let function = match user32_function(b"SetProcessDpiAwarenessContext\0") {
+            Some(function) => function,
+            None => {
+                return Err(Error::custom(
+                    ERROR_PROC_NOT_FOUND,
+                    NAME,
+                    "DPI awareness contexts are not supported on this system.",
+                ))
+            }
+        };
+
+        let result = unsafe {
+            let function: unsafe extern "system" fn(DPI_AWARENESS_CONTEXT) -> BOOL =
+                mem::transmute(function);
+            function(mode.context())
+        };
+
+        if result == 0 {
+            Err(Error::last_error(NAME))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Get the DPI of a window.
+///
+/// Returns [`DEFAULT_DPI`] on systems older than Windows 10 version 1607.
+pub(crate) fn window_dpi(hwnd: HWND) -> u32 {
+    let function = match user32_function(b"GetDpiForWindow\0") {
+        Some(function) => function,
+        None => return DEFAULT_DPI,
+    };
+
+    let dpi = unsafe {
+        let function: unsafe extern "system" fn(HWND) -> u32 = mem::transmute(function);
+        function(hwnd)
+    };
+
+    // GetDpiForWindow returns zero for invalid windows.
+    if dpi == 0 {
+        DEFAULT_DPI
+    } else {
+        dpi
+    }
+}
+
+/// Look up a function in `user32.dll` that may not exist on older systems.
+///
+/// `name` must be null-terminated.
+fn user32_function(name: &[u8]) -> FARPROC {
+    debug_assert_eq!(name.last(), Some(&0));
+
+    unsafe {
+        // user32.dll is always loaded, since we link to it.
+        let module = GetModuleHandleA(b"user32.dll\0".as_ptr());
+        if module == 0 {
+            return None;
+        }
+
+        GetProcAddress(module, name.as_ptr())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user32_function() {
+        assert!(user32_function(b"GetWindowRect\0").is_some());
+        assert!(user32_function(b"NotARealFunction\0").is_none());
+    }
+}
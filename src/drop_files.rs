@@ -0,0 +1,104 @@
+// Boost/Apache2 License
+
+//! Reading the files dropped onto a window.
+
+use crate::geometry::point_from_win32;
+
+use alloc::vec::Vec;
+
+use blood_geometry::Point;
+
+use core::mem::MaybeUninit;
+use core::ptr;
+
+use std::ffi::OsString;
+use std::os::windows::ffi::OsStringExt;
+use std::path::PathBuf;
+
+use windows_sys::Win32::UI::Shell::{DragFinish, DragQueryFileW, DragQueryPoint, HDROP};
+
+/// Read the paths and drop position out of a drop handle, then release it.
+///
+/// # Safety
+///
+/// `hdrop` must be a valid drop handle, such as the one passed with `WM_DROPFILES`. It is
+/// freed by this function and must not be used afterwards.
+pub(crate) unsafe fn take_dropped_files(hdrop: HDROP) -> (Vec<PathBuf>, Point<i32>) {
+    // Passing an index of 0xFFFFFFFF returns the number of files.
+    let count = DragQueryFileW(hdrop, u32::MAX, ptr::null_mut(), 0);
+    let mut paths = Vec::with_capacity(count as usize);
+    let mut buffer = Vec::new();
+
+    for i in 0..count {
+        // Get the length of the path, not including the null terminator.
+        let len = DragQueryFileW(hdrop, i, ptr::null_mut(), 0);
+        buffer.clear();
+        buffer.resize(len as usize + 1, 0u16);
+
+        let len = DragQueryFileW(hdrop, i, buffer.as_mut_ptr(), buffer.len() as u32);
+        paths.push(PathBuf::from(OsString::from_wide(&buffer[..len as usize])));
+    }
+
+    // Get the position of the drop, in client coordinates. The point is filled in even if the
+    // drop happened outside of the client area.
+    let mut point = MaybeUninit::uninit();
+    DragQueryPoint(hdrop, point.as_mut_ptr());
+    let position = point_from_win32(point.assume_init());
+
+    DragFinish(hdrop);
+
+    (paths, position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::mem;
+
+    use windows_sys::Win32::Foundation::POINT;
+    use windows_sys::Win32::System::Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GHND};
+    use windows_sys::Win32::UI::Shell::DROPFILES;
+
+    #[test]
+    fn test_take_dropped_files() {
+        let files = ["C:\\first.txt", "C:\\second directory\\sécond.txt"];
+
+        // A DROPFILES header, followed by a double null-terminated list of paths.
+        let mut list = Vec::new();
+        for file in files {
+            list.extend(file.encode_utf16());
+            list.push(0);
+        }
+        list.push(0);
+
+        let header = DROPFILES {
+            pFiles: mem::size_of::<DROPFILES>() as u32,
+            pt: POINT { x: 12, y: 34 },
+            fNC: 0,
+            fWide: 1,
+        };
+
+        let hdrop = unsafe {
+            let size = mem::size_of::<DROPFILES>() + list.len() * 2;
+            let handle = GlobalAlloc(GHND, size);
+            assert_ne!(handle, 0);
+
+            let data = GlobalLock(handle) as *mut u8;
+            assert!(!data.is_null());
+            ptr::write_unaligned(data as *mut DROPFILES, header);
+            ptr::copy_nonoverlapping(
+                list.as_ptr() as *const u8,
+                data.add(mem::size_of::<DROPFILES>()),
+                list.len() * 2,
+            );
+            GlobalUnlock(handle);
+
+            handle
+        };
+
+        let (paths, position) = unsafe { take_dropped_files(hdrop) };
+        assert_eq!(paths, files.iter().map(PathBuf::from).collect::<Vec<_>>());
+        assert_eq!(position, Point::new(12, 34));
+    }
+}
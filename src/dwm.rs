@@ -0,0 +1,91 @@
+// Boost/Apache2 License
+
+//! Integration with the Desktop Window Manager.
+
+use crate::Error;
+
+use windows_sys::core::HRESULT;
+use windows_sys::Win32::Foundation::HWND;
+use windows_sys::Win32::Graphics::Dwm::DwmExtendFrameIntoClientArea;
+use windows_sys::Win32::UI::Controls::MARGINS;
+
+/// How far the window frame extends into the client area on each side, in pixels.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Margins {
+    /// The width of the left margin.
+    pub left: i32,
+
+    /// The width of the right margin.
+    pub right: i32,
+
+    /// The height of the top margin.
+    pub top: i32,
+
+    /// The height of the bottom margin.
+    pub bottom: i32,
+}
+
+impl Margins {
+    /// Extend the frame over the whole client area, like a sheet of glass.
+    pub const SHEET_OF_GLASS: Self = Self::uniform(-1);
+
+    /// Create margins that are the same on every side.
+    pub const fn uniform(width: i32) -> Self {
+        Self {
+            left: width,
+            right: width,
+            top: width,
+            bottom: width,
+        }
+    }
+}
+
+/// Extend the frame of the window into its client area.
+pub(crate) fn extend_frame_into_client(hwnd: HWND, margins: Margins) -> Result<(), Error> {
+    let margins = MARGINS {
+        cxLeftWidth: margins.left,
+        cxRightWidth: margins.right,
+        cyTopHeight: margins.top,
+        cyBottomHeight: margins.bottom,
+    };
+
+    check_hresult(
+        unsafe { DwmExtendFrameIntoClientArea(hwnd, &margins) },
+        "DwmExtendFrameIntoClientArea",
+    )
+}
+
+/// Convert an `HRESULT` into a result.
+///
+/// Results that wrap a Win32 error code are unwrapped, so that they get the right
+/// [`ErrorKind`](crate::ErrorKind).
+fn check_hresult(result: HRESULT, function: &'static str) -> Result<(), Error> {
+    const FACILITY_WIN32: u32 = 0x8007_0000;
+
+    if result >= 0 {
+        return Ok(());
+    }
+
+    let result = result as u32;
+    let code = if result & 0xFFFF_0000 == FACILITY_WIN32 {
+        result & 0xFFFF
+    } else {
+        result
+    };
+
+    Err(Error::from_code(code, function))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_hresult() {
+        assert!(check_hresult(0, "Test").is_ok());
+
+        // E_INVALIDARG wraps ERROR_INVALID_PARAMETER.
+        let err = check_hresult(0x8007_0057_u32 as i32, "Test").unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::InvalidParameter);
+    }
+}
@@ -1,9 +1,636 @@
 // Boost/Apache2 License
 
+use crate::dc::{BorrowedReleaser, DeviceContext};
+use crate::geometry::{point_from_win32, point_to_win32, size_from_point, size_to_point};
+use crate::keyboard::KeyModifiers;
+use crate::window::{ScrollBar, WindowPosFlags};
+
+use blood_geometry::{Point, Rect, Size};
+
+use core::cell::Cell;
+
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+
+use windows_sys::Win32::UI::WindowsAndMessaging::MINMAXINFO;
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    ENDSESSION_CLOSEAPP, ENDSESSION_CRITICAL, ENDSESSION_LOGOFF,
+};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    HTBORDER, HTBOTTOM, HTBOTTOMLEFT, HTBOTTOMRIGHT, HTCAPTION, HTCLIENT, HTCLOSE, HTERROR,
+    HTGROWBOX, HTHELP, HTHSCROLL, HTLEFT, HTMAXBUTTON, HTMENU, HTMINBUTTON, HTNOWHERE, HTRIGHT,
+    HTSYSMENU, HTTOP, HTTOPLEFT, HTTOPRIGHT, HTTRANSPARENT, HTVSCROLL,
+};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    SB_BOTTOM, SB_ENDSCROLL, SB_LINEDOWN, SB_LINEUP, SB_PAGEDOWN, SB_PAGEUP, SB_THUMBPOSITION,
+    SB_THUMBTRACK, SB_TOP,
+};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    WMSZ_BOTTOM, WMSZ_BOTTOMLEFT, WMSZ_BOTTOMRIGHT, WMSZ_LEFT, WMSZ_RIGHT, WMSZ_TOP, WMSZ_TOPLEFT,
+    WMSZ_TOPRIGHT,
+};
+
 pub enum Event<'a> {
     /// The window has just been created.
+    ///
+    /// Unless the window is created from inside another event handler, this is handled before
+    /// the window is first shown or painted, so it is a good place to finish setting it up.
     Created,
 
+    /// The window is being destroyed.
+    ///
+    /// The window and its data are still valid, so this is the place to save state or release
+    /// resources tied to the window. Child windows are destroyed after this. The event is
+    /// delivered right away, even if the window is destroyed from inside another event handler.
+    /// The exception is a class built with
+    /// [`build_mut`](crate::class::ClassBuilder::build_mut), which doesn't get this event if the
+    /// window is destroyed while its handler is running.
+    Destroying,
+
+    /// A menu item or keyboard accelerator with the given command identifier was activated.
+    MenuCommand(u16),
+
+    /// The background of the window needs to be erased.
+    ///
+    /// Reply with `true` if the handler erased the background, or with `false` to leave it marked
+    /// as needing to be erased when painting. If there is no reply, the background is erased with
+    /// the class background brush.
+    EraseBackground {
+        /// The device context to erase the background with.
+        dc: &'a DeviceContext<BorrowedReleaser<'a>>,
+
+        /// The reply to the system.
+        reply: Reply<'a, bool>,
+    },
+
+    /// The size or position of the window is about to change.
+    ///
+    /// The limits in the `MinMaxInfo` can be changed to constrain how the window is resized.
+    GetMinMaxInfo(&'a mut MinMaxInfo),
+
+    /// Files were dragged from the shell and dropped onto the window.
+    ///
+    /// The window must have the [`ACCEPT_FILES`](crate::window::ExtendedStyle::ACCEPT_FILES)
+    /// extended style to receive this event.
+    #[cfg(feature = "std")]
+    FilesDropped {
+        /// The paths of the dropped files.
+        paths: Vec<PathBuf>,
+
+        /// The position of the drop, in client coordinates.
+        position: Point<i32>,
+    },
+
+    /// The user or the system is trying to end the session.
+    ///
+    /// Reply with `false` to ask the system not to end the session. Since the user can end the
+    /// session anyway, this should be paired with a [shutdown block
+    /// reason](crate::window::AsWindow::block_shutdown) explaining why.
+    QueryEndSession {
+        /// Why the session is ending.
+        reason: EndSessionReason,
+
+        /// The reply to the system.
+        reply: Reply<'a, bool>,
+    },
+
+    /// The session is ending, or an attempt to end it was cancelled.
+    EndSession {
+        /// Whether the session is actually ending.
+        ending: bool,
+    },
+
+    /// The DPI of the window changed, for instance because it moved to another monitor.
+    ///
+    /// This is only sent to applications that are [per-monitor DPI
+    /// aware](crate::dpi::DpiAwareness::PerMonitor).
+    DpiChanged {
+        /// The new DPI of the window.
+        dpi: u32,
+
+        /// The size and position the system suggests for the window at the new DPI.
+        suggested_rect: Rect<i32>,
+    },
+
+    /// An application-defined message was received.
+    ///
+    /// This covers messages in the `WM_USER` and `WM_APP` ranges, as well as messages registered
+    /// with [`Client::register_message`](crate::Client::register_message).
+    User {
+        /// The message identifier.
+        msg: u32,
+
+        /// The first message parameter.
+        wparam: usize,
+
+        /// The second message parameter.
+        lparam: isize,
+    },
+
+    /// The user interacted with a scroll bar.
+    ///
+    /// The scroll bar doesn't move by itself; the handler should update the position with
+    /// [`set_scroll_info`](crate::window::AsWindow::set_scroll_info).
+    Scroll {
+        /// The scroll bar that was used.
+        bar: ScrollBar,
+
+        /// What the user asked for.
+        request: ScrollRequest,
+
+        /// The position of the thumb, for [`ScrollRequest::ThumbPosition`] and
+        /// [`ScrollRequest::ThumbTrack`].
+        ///
+        /// This is limited to 16 bits. For larger ranges, use the track position from
+        /// [`scroll_info`](crate::window::AsWindow::scroll_info) instead.
+        position: Option<i32>,
+    },
+
+    /// The cursor left the client area.
+    ///
+    /// This is only sent after [`track_mouse`](crate::window::AsWindow::track_mouse) is called
+    /// with [`TrackMouseFlags::LEAVE`](crate::window::TrackMouseFlags::LEAVE). Tracking stops
+    /// once this is sent, so it has to be re-armed when the cursor comes back.
+    MouseLeft,
+
+    /// The cursor hovered over the client area.
+    ///
+    /// This is only sent after [`track_mouse`](crate::window::AsWindow::track_mouse) is called
+    /// with [`TrackMouseFlags::HOVER`](crate::window::TrackMouseFlags::HOVER). Tracking stops
+    /// once this is sent, so it has to be re-armed to detect the next hover.
+    MouseHover {
+        /// The position of the cursor, in client coordinates.
+        position: Point<i32>,
+    },
+
+    /// The user asked for a context menu, by right-clicking or with the keyboard.
+    ///
+    /// This is the place to show a context menu, since it also covers the menu key and
+    /// Shift+F10.
+    ContextMenu {
+        /// Where the user clicked, in screen coordinates.
+        ///
+        /// If the menu was requested with the keyboard, there is no click position and this is
+        /// `(-1, -1)`. The menu should then be shown next to the focused element instead.
+        position: Point<i32>,
+
+        /// Whether the menu was requested with the keyboard.
+        from_keyboard: bool,
+    },
+
+    /// The window was activated or deactivated.
+    Activated {
+        /// Whether the window is now active, either by a mouse click or otherwise.
+        active: bool,
+
+        /// Whether the window is minimized.
+        minimized: bool,
+    },
+
+    /// A window of this application was activated, or all of its windows were deactivated.
+    ///
+    /// This is a good place to pause a game or mute audio while the application is in the
+    /// background.
+    AppActivated(bool),
+
+    /// A hotkey registered with [`register_hotkey`](crate::window::AsWindow::register_hotkey)
+    /// was pressed, with the ID it was registered with.
+    HotKey(i32),
+
+    /// The cursor moved within the window, and its shape may need to be changed.
+    ///
+    /// Reply with `true` after setting the cursor with
+    /// [`Client::set_cursor`](crate::Client::set_cursor). Otherwise, the system uses the class
+    /// cursor for the client area and the standard cursors for the rest of the window.
+    SetCursor {
+        /// The part of the window the cursor is over.
+        hit_test: HitTest,
+
+        /// The reply to the system.
+        reply: Reply<'a, bool>,
+    },
+
+    /// The system wants to know which part of the window is at a position.
+    ///
+    /// Reply to make parts of the client area behave like the non-client area. For instance,
+    /// replying with [`HitTest::Caption`] lets the user drag a borderless window around, and
+    /// [`HitTest::Left`] lets them resize it. Without a reply, the system's default hit testing
+    /// is used.
+    HitTest {
+        /// The position, in screen coordinates.
+        position: Point<i32>,
+
+        /// The reply to the system.
+        reply: Reply<'a, HitTest>,
+    },
+
+    /// A mouse button was double-clicked in the client area.
+    ///
+    /// This is only sent if the window class has the
+    /// [`DOUBLE_CLICKS`](crate::class::Style::DOUBLE_CLICKS) style.
+    MouseDoubleClick {
+        /// The button that was double-clicked.
+        button: MouseButton,
+
+        /// The position of the cursor, in client coordinates.
+        position: Point<i32>,
+
+        /// The modifier keys that were held down.
+        modifiers: KeyModifiers,
+    },
+
+    /// The user is resizing the window by dragging one of its edges.
+    ///
+    /// The rectangle is the proposed window rectangle, in screen coordinates, and can be
+    /// changed to constrain the size, for instance to keep an aspect ratio.
+    Sizing {
+        /// The edge or corner that is being dragged.
+        edge: SizingEdge,
+
+        /// The proposed window rectangle.
+        rect: &'a mut Rect<i32>,
+    },
+
+    /// The user is moving the window.
+    ///
+    /// The rectangle is the proposed window rectangle, in screen coordinates, and can be
+    /// changed to constrain the position.
+    Moving {
+        /// The proposed window rectangle.
+        rect: &'a mut Rect<i32>,
+    },
+
+    /// The window was moved.
+    ///
+    /// This is the new position of the top-left corner of the client area. It is in screen
+    /// coordinates for top-level windows and in parent client coordinates for child windows, so
+    /// it can be negative.
+    Moved(Point<i32>),
+
+    /// The size, position or Z order of the window changed.
+    ///
+    /// This reports the whole new geometry at once, before the separate [`Event::Moved`] and
+    /// resize events. The position is in parent client coordinates for child windows. The flags
+    /// tell what changed; for instance, the position is unchanged if
+    /// [`WindowPosFlags::NO_MOVE`] is set.
+    GeometryChanged {
+        /// The position of the window.
+        position: Point<i32>,
+
+        /// The size of the window.
+        size: Size<i32>,
+
+        /// The flags passed to `SetWindowPos`.
+        flags: WindowPosFlags,
+    },
+
+    /// A message that this crate doesn't handle was received.
+    ///
+    /// This is only delivered for window classes built with
+    /// [`raw_events`](crate::class::ClassBuilder::raw_events). Reply with the result to return
+    /// for the message; without a reply, the message is passed to the default window procedure.
+    Raw {
+        /// The message identifier.
+        msg: u32,
+
+        /// The first message parameter.
+        wparam: usize,
+
+        /// The second message parameter.
+        lparam: isize,
+
+        /// The reply to the system.
+        reply: Reply<'a, isize>,
+    },
+
     #[doc(hidden)]
     __NonExhaustive(&'a ()),
 }
+
+/// A request from the user to scroll.
+///
+/// For horizontal scroll bars, "up" is left and "down" is right.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScrollRequest {
+    /// Scroll up by one line.
+    LineUp,
+
+    /// Scroll down by one line.
+    LineDown,
+
+    /// Scroll up by one page.
+    PageUp,
+
+    /// Scroll down by one page.
+    PageDown,
+
+    /// The user released the thumb after dragging it.
+    ThumbPosition,
+
+    /// The user is dragging the thumb.
+    ThumbTrack,
+
+    /// Scroll to the top.
+    Top,
+
+    /// Scroll to the bottom.
+    Bottom,
+
+    /// The user finished scrolling.
+    EndScroll,
+}
+
+impl ScrollRequest {
+    /// Decode the request from the low word of a scroll message's `wparam`.
+    pub(crate) fn from_code(code: u16) -> Option<Self> {
+        Some(match i32::from(code) {
+            SB_LINEUP => Self::LineUp,
+            SB_LINEDOWN => Self::LineDown,
+            SB_PAGEUP => Self::PageUp,
+            SB_PAGEDOWN => Self::PageDown,
+            SB_THUMBPOSITION => Self::ThumbPosition,
+            SB_THUMBTRACK => Self::ThumbTrack,
+            SB_TOP => Self::Top,
+            SB_BOTTOM => Self::Bottom,
+            SB_ENDSCROLL => Self::EndScroll,
+            _ => return None,
+        })
+    }
+
+    /// Whether the request comes with a thumb position.
+    pub(crate) fn has_position(self) -> bool {
+        matches!(self, Self::ThumbPosition | Self::ThumbTrack)
+    }
+}
+
+/// The edge or corner of a window that is being dragged to resize it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SizingEdge {
+    /// The left edge.
+    Left,
+
+    /// The right edge.
+    Right,
+
+    /// The top edge.
+    Top,
+
+    /// The top-left corner.
+    TopLeft,
+
+    /// The top-right corner.
+    TopRight,
+
+    /// The bottom edge.
+    Bottom,
+
+    /// The bottom-left corner.
+    BottomLeft,
+
+    /// The bottom-right corner.
+    BottomRight,
+}
+
+impl SizingEdge {
+    /// Decode the edge from the `wparam` of `WM_SIZING`.
+    pub(crate) fn from_code(code: u32) -> Option<Self> {
+        Some(match code {
+            WMSZ_LEFT => Self::Left,
+            WMSZ_RIGHT => Self::Right,
+            WMSZ_TOP => Self::Top,
+            WMSZ_TOPLEFT => Self::TopLeft,
+            WMSZ_TOPRIGHT => Self::TopRight,
+            WMSZ_BOTTOM => Self::Bottom,
+            WMSZ_BOTTOMLEFT => Self::BottomLeft,
+            WMSZ_BOTTOMRIGHT => Self::BottomRight,
+            _ => return None,
+        })
+    }
+}
+
+/// A mouse button.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MouseButton {
+    /// The left mouse button.
+    Left,
+
+    /// The right mouse button.
+    Right,
+
+    /// The middle mouse button.
+    Middle,
+}
+
+/// A part of a window, as determined by hit testing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HitTest {
+    /// The screen background or a dividing line between windows.
+    Nowhere,
+
+    /// The client area.
+    Client,
+
+    /// The title bar.
+    Caption,
+
+    /// The window menu, or the close button of a child window.
+    SystemMenu,
+
+    /// The size box in the bottom-right corner.
+    SizeBox,
+
+    /// The menu bar.
+    Menu,
+
+    /// The horizontal scroll bar.
+    HorizontalScroll,
+
+    /// The vertical scroll bar.
+    VerticalScroll,
+
+    /// The minimize button.
+    MinimizeButton,
+
+    /// The maximize button.
+    MaximizeButton,
+
+    /// The left border.
+    Left,
+
+    /// The right border.
+    Right,
+
+    /// The top border.
+    Top,
+
+    /// The top-left corner of the border.
+    TopLeft,
+
+    /// The top-right corner of the border.
+    TopRight,
+
+    /// The bottom border.
+    Bottom,
+
+    /// The bottom-left corner of the border.
+    BottomLeft,
+
+    /// The bottom-right corner of the border.
+    BottomRight,
+
+    /// A border that can't be used to resize the window.
+    Border,
+
+    /// The close button.
+    Close,
+
+    /// The help button.
+    Help,
+
+    /// A part covered by another window of the same thread.
+    Transparent,
+
+    /// The screen background or a dividing line between windows, where the system beeps on
+    /// clicks.
+    Error,
+}
+
+/// The `HT*` code for each part of a window.
+const HIT_TEST_CODES: &[(HitTest, i32)] = &[
+    (HitTest::Nowhere, HTNOWHERE as i32),
+    (HitTest::Client, HTCLIENT as i32),
+    (HitTest::Caption, HTCAPTION as i32),
+    (HitTest::SystemMenu, HTSYSMENU as i32),
+    (HitTest::SizeBox, HTGROWBOX as i32),
+    (HitTest::Menu, HTMENU as i32),
+    (HitTest::HorizontalScroll, HTHSCROLL as i32),
+    (HitTest::VerticalScroll, HTVSCROLL as i32),
+    (HitTest::MinimizeButton, HTMINBUTTON as i32),
+    (HitTest::MaximizeButton, HTMAXBUTTON as i32),
+    (HitTest::Left, HTLEFT as i32),
+    (HitTest::Right, HTRIGHT as i32),
+    (HitTest::Top, HTTOP as i32),
+    (HitTest::TopLeft, HTTOPLEFT as i32),
+    (HitTest::TopRight, HTTOPRIGHT as i32),
+    (HitTest::Bottom, HTBOTTOM as i32),
+    (HitTest::BottomLeft, HTBOTTOMLEFT as i32),
+    (HitTest::BottomRight, HTBOTTOMRIGHT as i32),
+    (HitTest::Border, HTBORDER as i32),
+    (HitTest::Close, HTCLOSE as i32),
+    (HitTest::Help, HTHELP as i32),
+    (HitTest::Transparent, HTTRANSPARENT),
+    (HitTest::Error, HTERROR),
+];
+
+impl HitTest {
+    /// Decode a hit test code, such as the low word of `WM_SETCURSOR`'s `lparam`.
+    pub(crate) fn from_code(code: i16) -> Option<Self> {
+        HIT_TEST_CODES
+            .iter()
+            .find(|(_, c)| *c == i32::from(code))
+            .map(|(hit_test, _)| *hit_test)
+    }
+
+    /// Get the hit test code for this part.
+    pub(crate) fn to_code(self) -> i32 {
+        HIT_TEST_CODES
+            .iter()
+            .find(|(hit_test, _)| *hit_test == self)
+            .map_or(HTNOWHERE as i32, |(_, code)| *code)
+    }
+}
+
+bitflags::bitflags! {
+    /// The reasons for a session ending.
+    ///
+    /// If no flags are set, the system is shutting down or restarting.
+    pub struct EndSessionReason : u32 {
+        /// The application is being closed to service an update or to free resources.
+        const CLOSE_APP = ENDSESSION_CLOSEAPP;
+
+        /// The application is being forced to shut down.
+        const CRITICAL = ENDSESSION_CRITICAL;
+
+        /// The user is logging off.
+        const LOGOFF = ENDSESSION_LOGOFF;
+    }
+}
+
+/// A way to answer an event that the system expects an answer for.
+///
+/// If the handler doesn't reply, the system's default handling is used.
+pub struct Reply<'a, T> {
+    /// The slot to store the answer in.
+    slot: &'a Cell<Option<T>>,
+}
+
+impl<'a, T> Reply<'a, T> {
+    /// Create a new `Reply` that stores its answer in `slot`.
+    pub(crate) fn new(slot: &'a Cell<Option<T>>) -> Self {
+        Self { slot }
+    }
+
+    /// Answer the event.
+    ///
+    /// If this is called more than once, the last answer is used.
+    pub fn send(&self, value: T) {
+        self.slot.set(Some(value));
+    }
+}
+
+/// The limits on the size and position of a window.
+#[repr(transparent)]
+pub struct MinMaxInfo(MINMAXINFO);
+
+impl MinMaxInfo {
+    /// Convert a `MINMAXINFO` pointer into a `MinMaxInfo` reference.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid and unaliased for the lifetime `'a`.
+    pub(crate) unsafe fn from_ptr<'a>(ptr: *mut MINMAXINFO) -> &'a mut Self {
+        &mut *(ptr as *mut Self)
+    }
+
+    /// Get the size of the window when it is maximized.
+    pub fn max_size(&self) -> Size<i32> {
+        size_from_point(self.0.ptMaxSize)
+    }
+
+    /// Set the size of the window when it is maximized.
+    pub fn set_max_size(&mut self, size: Size<i32>) {
+        self.0.ptMaxSize = size_to_point(size);
+    }
+
+    /// Get the position of the window when it is maximized.
+    pub fn max_position(&self) -> Point<i32> {
+        point_from_win32(self.0.ptMaxPosition)
+    }
+
+    /// Set the position of the window when it is maximized.
+    pub fn set_max_position(&mut self, position: Point<i32>) {
+        self.0.ptMaxPosition = point_to_win32(position);
+    }
+
+    /// Get the minimum size the user can resize the window to.
+    pub fn min_track_size(&self) -> Size<i32> {
+        size_from_point(self.0.ptMinTrackSize)
+    }
+
+    /// Set the minimum size the user can resize the window to.
+    pub fn set_min_track_size(&mut self, size: Size<i32>) {
+        self.0.ptMinTrackSize = size_to_point(size);
+    }
+
+    /// Get the maximum size the user can resize the window to.
+    pub fn max_track_size(&self) -> Size<i32> {
+        size_from_point(self.0.ptMaxTrackSize)
+    }
+
+    /// Set the maximum size the user can resize the window to.
+    pub fn set_max_track_size(&mut self, size: Size<i32>) {
+        self.0.ptMaxTrackSize = size_to_point(size);
+    }
+}
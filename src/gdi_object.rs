@@ -82,6 +82,11 @@ impl<'a> BorrowedGdiObject<'a> {
             _marker: PhantomData,
         }
     }
+
+    /// Get the raw handle to the GDI object.
+    pub(crate) fn as_raw(&self) -> RawGdiObject {
+        self.handle.get() as _
+    }
 }
 
 /// A trait that allows one to borrow a GDI object.
@@ -0,0 +1,135 @@
+// Boost/Apache2 License
+
+//! Conversions between `blood_geometry` types and their Win32 equivalents.
+//!
+//! These convert field by field, so they don't depend on the layout of the geometry types.
+
+use blood_geometry::{Point, Rect, Size};
+
+use core::mem;
+
+use windows_sys::Win32::Foundation::{POINT, RECT, SIZE};
+
+// The Win32 types are passed to the system by pointer, so make sure they have the layout it
+// expects: plain `i32` fields with no padding.
+const _: [(); 16] = [(); mem::size_of::<RECT>()];
+const _: [(); 8] = [(); mem::size_of::<POINT>()];
+const _: [(); 8] = [(); mem::size_of::<SIZE>()];
+const _: [(); 4] = [(); mem::align_of::<RECT>()];
+const _: [(); 4] = [(); mem::align_of::<POINT>()];
+const _: [(); 4] = [(); mem::align_of::<SIZE>()];
+
+/// Convert a rectangle into a Win32 `RECT`.
+pub(crate) fn rect_to_win32(rect: Rect<i32>) -> RECT {
+    let origin = rect.origin();
+    let size = rect.size();
+
+    RECT {
+        left: origin.x(),
+        top: origin.y(),
+        right: origin.x() + size.width(),
+        bottom: origin.y() + size.height(),
+    }
+}
+
+/// Convert a Win32 `RECT` into a rectangle.
+pub(crate) fn rect_from_win32(rect: RECT) -> Rect<i32> {
+    Rect::new(
+        Point::new(rect.left, rect.top),
+        Size::new(rect.right - rect.left, rect.bottom - rect.top),
+    )
+}
+
+/// Convert a point into a Win32 `POINT`.
+pub(crate) fn point_to_win32(point: Point<i32>) -> POINT {
+    POINT {
+        x: point.x(),
+        y: point.y(),
+    }
+}
+
+/// Convert a Win32 `POINT` into a point.
+pub(crate) fn point_from_win32(point: POINT) -> Point<i32> {
+    Point::new(point.x, point.y)
+}
+
+/// Convert a size into a Win32 `SIZE`.
+pub(crate) fn size_to_win32(size: Size<i32>) -> SIZE {
+    SIZE {
+        cx: size.width(),
+        cy: size.height(),
+    }
+}
+
+/// Convert a Win32 `SIZE` into a size.
+pub(crate) fn size_from_win32(size: SIZE) -> Size<i32> {
+    Size::new(size.cx, size.cy)
+}
+
+/// Convert a size into a Win32 `POINT`, for structures like `MINMAXINFO` that store sizes as
+/// points.
+pub(crate) fn size_to_point(size: Size<i32>) -> POINT {
+    POINT {
+        x: size.width(),
+        y: size.height(),
+    }
+}
+
+/// Convert a Win32 `POINT` that holds a size into a size.
+pub(crate) fn size_from_point(point: POINT) -> Size<i32> {
+    Size::new(point.x, point.y)
+}
+
+/// Decode a point packed into the `lparam` of a message.
+///
+/// The coordinates are signed 16-bit values, since they can be left of or above the origin.
+pub(crate) fn point_from_lparam(lparam: isize) -> Point<i32> {
+    Point::new(lparam as i16 as i32, (lparam >> 16) as i16 as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rect_conversion() {
+        let rect = Rect::new(Point::new(-10, 20), Size::new(30, 40));
+        let win32 = rect_to_win32(rect);
+
+        assert_eq!(
+            (win32.left, win32.top, win32.right, win32.bottom),
+            (-10, 20, 20, 60)
+        );
+        assert_eq!(rect_from_win32(win32), rect);
+    }
+
+    #[test]
+    fn test_point_conversion() {
+        let point = Point::new(3, -4);
+        let win32 = point_to_win32(point);
+        assert_eq!((win32.x, win32.y), (3, -4));
+        assert_eq!(point_from_win32(win32), point);
+    }
+
+    #[test]
+    fn test_size_conversion() {
+        let size = size_from_win32(SIZE { cx: 5, cy: 6 });
+        assert_eq!(size, Size::new(5, 6));
+
+        let win32 = size_to_win32(size);
+        assert_eq!((win32.cx, win32.cy), (5, 6));
+
+        let point = size_to_point(size);
+        assert_eq!((point.x, point.y), (5, 6));
+        assert_eq!(size_from_point(point), size);
+    }
+
+    #[test]
+    fn test_point_from_lparam() {
+        assert_eq!(point_from_lparam((20 << 16) | 10), Point::new(10, 20));
+        assert_eq!(
+            point_from_lparam((0xFFFE << 16) | 0xFFFF),
+            Point::new(-1, -2)
+        );
+    }
+}
@@ -0,0 +1,87 @@
+// Boost/Apache2 License
+
+//! Window icons.
+
+use crate::Error;
+
+use core::cell::Cell;
+use core::marker::PhantomData;
+
+use windows_sys::core::PCWSTR;
+use windows_sys::Win32::UI::WindowsAndMessaging::LoadIconW;
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    HICON, IDI_APPLICATION, IDI_ASTERISK, IDI_EXCLAMATION, IDI_HAND, IDI_QUESTION, IDI_SHIELD,
+    IDI_WINLOGO,
+};
+
+/// An icon.
+pub struct Icon {
+    /// The handle to the icon.
+    handle: HICON,
+
+    /// This handle is `Send` but `!Sync`.
+    _thread_safety: PhantomData<Cell<()>>,
+}
+
+/// The icons provided by the system.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StandardIcon {
+    /// The default application icon.
+    Application,
+
+    /// The stop sign, used for errors.
+    Error,
+
+    /// The question mark.
+    Question,
+
+    /// The exclamation point, used for warnings.
+    Warning,
+
+    /// The "i" in a circle, used for information.
+    Information,
+
+    /// The Windows logo.
+    WinLogo,
+
+    /// The security shield.
+    Shield,
+}
+
+impl StandardIcon {
+    /// Get the resource identifier for this icon.
+    fn resource(self) -> PCWSTR {
+        match self {
+            Self::Application => IDI_APPLICATION,
+            Self::Error => IDI_HAND,
+            Self::Question => IDI_QUESTION,
+            Self::Warning => IDI_EXCLAMATION,
+            Self::Information => IDI_ASTERISK,
+            Self::WinLogo => IDI_WINLOGO,
+            Self::Shield => IDI_SHIELD,
+        }
+    }
+}
+
+impl Icon {
+    /// Load one of the icons provided by the system.
+    ///
+    /// System icons are shared, so they are never destroyed. This makes them safe to hand to a
+    /// window class, which may outlive the `Icon`.
+    pub fn load(icon: StandardIcon) -> Result<Self, Error> {
+        let handle = unsafe { LoadIconW(0, icon.resource()) };
+
+        if handle == 0 {
+            Err(Error::last_error("LoadIconW"))
+        } else {
+            Ok(Self {
+                handle,
+                _thread_safety: PhantomData,
+            })
+        }
+    }
+
+    pub(crate) fn handle(&self) -> HICON {
+        self.handle
+    }
+}
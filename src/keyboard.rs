@@ -0,0 +1,267 @@
+// Boost/Apache2 License
+
+//! Keyboard keys and modifiers.
+
+use crate::client::Client;
+
+use windows_sys::Win32::System::SystemServices::{MK_CONTROL, MK_SHIFT};
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::GetKeyState;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+    HOT_KEY_MODIFIERS, MOD_ALT, MOD_CONTROL, MOD_SHIFT,
+};
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+    VK_BACK, VK_CAPITAL, VK_CONTROL, VK_DELETE, VK_DOWN, VK_END, VK_ESCAPE, VK_F1, VK_F10, VK_F11,
+    VK_F12, VK_F2, VK_F3, VK_F4, VK_F5, VK_F6, VK_F7, VK_F8, VK_F9, VK_HOME, VK_INSERT, VK_LEFT,
+    VK_MENU, VK_NEXT, VK_NUMLOCK, VK_PAUSE, VK_PRIOR, VK_RETURN, VK_RIGHT, VK_SCROLL, VK_SHIFT,
+    VK_SPACE, VK_TAB, VK_UP,
+};
+
+bitflags::bitflags! {
+    /// Modifier keys held down alongside another key.
+    pub struct KeyModifiers : u32 {
+        /// The SHIFT key.
+        const SHIFT = 1 << 0;
+
+        /// The CTRL key.
+        const CONTROL = 1 << 1;
+
+        /// The ALT key.
+        const ALT = 1 << 2;
+    }
+}
+
+impl KeyModifiers {
+    /// Decode the modifiers from the key flags of a mouse message.
+    ///
+    /// Mouse messages don't include the ALT key, so its current state is queried instead.
+    pub(crate) fn from_mouse_keys(keys: u32) -> Self {
+        let mut modifiers = Self::empty();
+        modifiers.set(Self::SHIFT, keys & MK_SHIFT != 0);
+        modifiers.set(Self::CONTROL, keys & MK_CONTROL != 0);
+
+        modifiers.set(Self::ALT, KeyState::of(VirtualKey::ALT).is_pressed());
+
+        modifiers
+    }
+
+    /// Get the `MOD_*` flags for `RegisterHotKey`.
+    pub(crate) fn to_hotkey_modifiers(self) -> HOT_KEY_MODIFIERS {
+        let mut modifiers = 0;
+
+        if self.contains(Self::SHIFT) {
+            modifiers |= MOD_SHIFT;
+        }
+        if self.contains(Self::CONTROL) {
+            modifiers |= MOD_CONTROL;
+        }
+        if self.contains(Self::ALT) {
+            modifiers |= MOD_ALT;
+        }
+
+        modifiers
+    }
+}
+
+/// The state of a key, as of the message currently being processed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KeyState(i16);
+
+impl KeyState {
+    /// Get the state of a key from `GetKeyState`.
+    fn of(key: VirtualKey) -> Self {
+        Self(unsafe { GetKeyState(i32::from(key.raw())) })
+    }
+
+    /// Whether the key is held down.
+    pub fn is_pressed(self) -> bool {
+        // The high bit is set while the key is held down.
+        self.0 < 0
+    }
+
+    /// Whether the key is toggled on, for keys like CAPS LOCK and NUM LOCK.
+    pub fn is_toggled(self) -> bool {
+        self.0 & 1 != 0
+    }
+}
+
+impl Client {
+    /// Get the state of a key.
+    ///
+    /// This reflects the input messages this thread has processed so far, rather than the
+    /// physical keyboard, so it is consistent with the message being handled.
+    pub fn key_state(&self, key: VirtualKey) -> KeyState {
+        KeyState::of(key)
+    }
+
+    /// Get the modifier keys that are currently held down.
+    ///
+    /// This is useful while handling messages that don't carry the modifier state.
+    pub fn modifiers_now(&self) -> KeyModifiers {
+        let mut modifiers = KeyModifiers::empty();
+        modifiers.set(
+            KeyModifiers::SHIFT,
+            KeyState::of(VirtualKey::SHIFT).is_pressed(),
+        );
+        modifiers.set(
+            KeyModifiers::CONTROL,
+            KeyState::of(VirtualKey::CONTROL).is_pressed(),
+        );
+        modifiers.set(
+            KeyModifiers::ALT,
+            KeyState::of(VirtualKey::ALT).is_pressed(),
+        );
+        modifiers
+    }
+}
+
+/// A virtual-key code.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct VirtualKey(u16);
+
+impl VirtualKey {
+    /// The BACKSPACE key.
+    pub const BACKSPACE: Self = Self(VK_BACK);
+
+    /// The TAB key.
+    pub const TAB: Self = Self(VK_TAB);
+
+    /// The ENTER key.
+    pub const ENTER: Self = Self(VK_RETURN);
+
+    /// The SHIFT key.
+    pub const SHIFT: Self = Self(VK_SHIFT);
+
+    /// The CTRL key.
+    pub const CONTROL: Self = Self(VK_CONTROL);
+
+    /// The ALT key.
+    pub const ALT: Self = Self(VK_MENU);
+
+    /// The PAUSE key.
+    pub const PAUSE: Self = Self(VK_PAUSE);
+
+    /// The CAPS LOCK key.
+    pub const CAPS_LOCK: Self = Self(VK_CAPITAL);
+
+    /// The ESC key.
+    pub const ESCAPE: Self = Self(VK_ESCAPE);
+
+    /// The SPACEBAR.
+    pub const SPACE: Self = Self(VK_SPACE);
+
+    /// The PAGE UP key.
+    pub const PAGE_UP: Self = Self(VK_PRIOR);
+
+    /// The PAGE DOWN key.
+    pub const PAGE_DOWN: Self = Self(VK_NEXT);
+
+    /// The END key.
+    pub const END: Self = Self(VK_END);
+
+    /// The HOME key.
+    pub const HOME: Self = Self(VK_HOME);
+
+    /// The LEFT ARROW key.
+    pub const LEFT: Self = Self(VK_LEFT);
+
+    /// The UP ARROW key.
+    pub const UP: Self = Self(VK_UP);
+
+    /// The RIGHT ARROW key.
+    pub const RIGHT: Self = Self(VK_RIGHT);
+
+    /// The DOWN ARROW key.
+    pub const DOWN: Self = Self(VK_DOWN);
+
+    /// The INS key.
+    pub const INSERT: Self = Self(VK_INSERT);
+
+    /// The DEL key.
+    pub const DELETE: Self = Self(VK_DELETE);
+
+    /// The F1 key.
+    pub const F1: Self = Self(VK_F1);
+
+    /// The F2 key.
+    pub const F2: Self = Self(VK_F2);
+
+    /// The F3 key.
+    pub const F3: Self = Self(VK_F3);
+
+    /// The F4 key.
+    pub const F4: Self = Self(VK_F4);
+
+    /// The F5 key.
+    pub const F5: Self = Self(VK_F5);
+
+    /// The F6 key.
+    pub const F6: Self = Self(VK_F6);
+
+    /// The F7 key.
+    pub const F7: Self = Self(VK_F7);
+
+    /// The F8 key.
+    pub const F8: Self = Self(VK_F8);
+
+    /// The F9 key.
+    pub const F9: Self = Self(VK_F9);
+
+    /// The F10 key.
+    pub const F10: Self = Self(VK_F10);
+
+    /// The F11 key.
+    pub const F11: Self = Self(VK_F11);
+
+    /// The F12 key.
+    pub const F12: Self = Self(VK_F12);
+
+    /// The NUM LOCK key.
+    pub const NUM_LOCK: Self = Self(VK_NUMLOCK);
+
+    /// The SCROLL LOCK key.
+    pub const SCROLL_LOCK: Self = Self(VK_SCROLL);
+
+    /// Create a `VirtualKey` from a raw virtual-key code.
+    pub const fn from_raw(code: u16) -> Self {
+        Self(code)
+    }
+
+    /// Get the virtual-key code for an ASCII letter or digit.
+    ///
+    /// Returns `None` if `c` is not alphanumeric.
+    pub fn from_ascii(c: u8) -> Option<Self> {
+        // The virtual-key codes for letters and digits are their uppercase ASCII values.
+        if c.is_ascii_alphanumeric() {
+            Some(Self(c.to_ascii_uppercase() as u16))
+        } else {
+            None
+        }
+    }
+
+    /// Get the raw virtual-key code.
+    pub const fn raw(self) -> u16 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_state() {
+        assert!(KeyState(-128).is_pressed());
+        assert!(!KeyState(-128).is_toggled());
+        assert!(KeyState(1).is_toggled());
+        assert!(!KeyState(1).is_pressed());
+
+        // The actual state depends on the environment, but it should be readable.
+        let client = Client::new();
+        let caps_lock = client.key_state(VirtualKey::CAPS_LOCK);
+        assert_eq!(
+            caps_lock.is_toggled(),
+            KeyState::of(VirtualKey::CAPS_LOCK).is_toggled()
+        );
+        let _ = client.modifiers_now();
+    }
+}
@@ -20,17 +20,34 @@ macro_rules! abort {
 }
 
 // Public modules.
+pub mod accelerator;
 pub mod bitmap;
+pub mod brush;
 pub mod class;
+pub mod color;
+pub mod cursor;
 pub mod dc;
+pub mod dpi;
+#[cfg(feature = "dwm")]
+pub mod dwm;
 pub mod event;
 pub mod gdi_object;
+pub mod icon;
+pub mod keyboard;
 pub mod menu;
+pub mod message;
+pub mod message_box;
+pub mod metrics;
+pub mod monitor;
 pub mod reactor;
 pub mod region;
 pub mod window;
 
 // Private modules.
+mod clipboard;
+#[cfg(feature = "std")]
+mod drop_files;
+mod geometry;
 mod module;
 mod wndproc;
 
@@ -38,8 +55,16 @@ mod client;
 pub use client::Client;
 
 use core::fmt;
+use core::sync::atomic::{AtomicU32, Ordering};
 
 use windows_sys::Win32::Foundation::GetLastError;
+use windows_sys::Win32::Foundation::{
+    ERROR_ACCESS_DENIED, ERROR_CLASS_ALREADY_EXISTS, ERROR_CLASS_DOES_NOT_EXIST,
+    ERROR_FILE_NOT_FOUND, ERROR_HOTKEY_ALREADY_REGISTERED, ERROR_INVALID_HANDLE,
+    ERROR_INVALID_PARAMETER, ERROR_INVALID_WINDOW_HANDLE, ERROR_NOT_ENOUGH_MEMORY, ERROR_NOT_FOUND,
+    ERROR_OUTOFMEMORY,
+};
+use windows_sys::Win32::System::SystemServices::{LANG_ENGLISH, SUBLANG_ENGLISH_US};
 
 // On post-1.64, CStr is in core.
 #[cfg(not(porcupine_no_cstr_in_core))]
@@ -56,6 +81,20 @@ mod cstr {
     pub(crate) use std::ffi::{CStr, CString};
 }
 
+/// The language used to format error messages.
+///
+/// This defaults to `MAKELANGID(LANG_ENGLISH, SUBLANG_ENGLISH_US)` so that messages are consistent
+/// regardless of the system locale.
+static ERROR_LANGUAGE: AtomicU32 = AtomicU32::new((SUBLANG_ENGLISH_US << 10) | LANG_ENGLISH);
+
+/// Set the language used to format error messages.
+pub(crate) fn set_error_language(language: u32) {
+    ERROR_LANGUAGE.store(language, Ordering::Relaxed);
+}
+
+/// A result type that defaults to this crate's [`Error`].
+pub type Result<T> = core::result::Result<T, Error>;
+
 /// The error type for the Win32 windowing system.
 #[derive(Debug)]
 pub struct Error {
@@ -73,52 +112,71 @@ pub struct Error {
 impl Error {
     /// Get the latest error code.
     fn last_error(function: &'static str) -> Self {
-        // Fetch the error code.
-        let code = unsafe { GetLastError() };
+        Self::from_code(unsafe { GetLastError() }, function)
+    }
 
+    /// Create an error from a Win32 error code, as if `function` had failed with it.
+    ///
+    /// The message for the code is looked up from the system.
+    pub fn from_code(code: u32, function: &'static str) -> Self {
         // If applicable, fetch the error message.
         #[cfg(feature = "alloc")]
         let message = {
             use core::ptr;
-            use windows_sys::Win32::System::Diagnostics::Debug::FormatMessageA;
+            use windows_sys::Win32::System::Diagnostics::Debug::FormatMessageW;
             use windows_sys::Win32::System::Diagnostics::Debug::{
-                FORMAT_MESSAGE_ARGUMENT_ARRAY, FORMAT_MESSAGE_FROM_SYSTEM,
-                FORMAT_MESSAGE_IGNORE_INSERTS,
+                FORMAT_MESSAGE_ALLOCATE_BUFFER, FORMAT_MESSAGE_ARGUMENT_ARRAY,
+                FORMAT_MESSAGE_FROM_SYSTEM, FORMAT_MESSAGE_IGNORE_INSERTS,
             };
+            use windows_sys::Win32::System::Memory::LocalFree;
 
-            // Allocate a buffer for the message.
-            const BUF_SIZE: usize = 1024;
-            let mut buffer = [0u8; BUF_SIZE];
+            // The system allocates a buffer for the message, in UTF-16 code units.
+            let mut buffer: *mut u16 = ptr::null_mut();
 
             // Fetch the message.
-            let mut chars_written = unsafe {
-                FormatMessageA(
-                    FORMAT_MESSAGE_IGNORE_INSERTS
+            let mut format_message = |language| unsafe {
+                FormatMessageW(
+                    FORMAT_MESSAGE_ALLOCATE_BUFFER
+                        | FORMAT_MESSAGE_IGNORE_INSERTS
                         | FORMAT_MESSAGE_FROM_SYSTEM
                         | FORMAT_MESSAGE_ARGUMENT_ARRAY,
                     ptr::null(),
                     code,
+                    language,
+                    // With FORMAT_MESSAGE_ALLOCATE_BUFFER, this is where the buffer is stored.
+                    &mut buffer as *mut *mut u16 as *mut u16,
                     0,
-                    buffer.as_mut_ptr(),
-                    BUF_SIZE as u32,
                     ptr::null(),
                 )
             };
 
+            // If the requested language isn't installed, fall back to the neutral language.
+            let language = ERROR_LANGUAGE.load(Ordering::Relaxed);
+            let chars_written = match format_message(language) {
+                0 if language != 0 => format_message(0),
+                chars_written => chars_written,
+            };
+
             // If we failed to fetch the message, return None.
-            if chars_written == 0 {
+            if chars_written == 0 || buffer.is_null() {
                 None
             } else {
-                // Trim the trailing newline.
-                chars_written = chars_written.saturating_sub(2);
-                let buffer = &buffer[..chars_written as usize];
-
-                // Convert the buffer to a string.
-                Some(
-                    String::from_utf8_lossy(buffer)
-                        .into_owned()
-                        .into_boxed_str(),
-                )
+                let message = unsafe {
+                    let chars = core::slice::from_raw_parts(buffer, chars_written as usize);
+
+                    // Trim the trailing newline.
+                    let len = chars
+                        .iter()
+                        .rposition(|&c| c != u16::from(b'\r') && c != u16::from(b'\n'))
+                        .map_or(0, |last| last + 1);
+
+                    // Convert the buffer to a string.
+                    let message = alloc::string::String::from_utf16_lossy(&chars[..len]);
+                    LocalFree(strict::expose(buffer.cast()));
+                    message
+                };
+
+                Some(message.into_boxed_str())
             }
         };
 
@@ -129,6 +187,81 @@ impl Error {
             function,
         }
     }
+
+    /// Create an error for a failure that was detected by this crate rather than by Win32.
+    fn custom(code: u32, function: &'static str, message: &'static str) -> Self {
+        #[cfg(not(feature = "alloc"))]
+        let _ = message;
+
+        Self {
+            code,
+            #[cfg(feature = "alloc")]
+            message: Some(message.into()),
+            function,
+        }
+    }
+}
+
+impl Error {
+    /// Get the category of this error.
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::from_code(self.code)
+    }
+
+    /// Get the Win32 error code, such as `ERROR_ACCESS_DENIED`.
+    pub fn code(&self) -> u32 {
+        self.code
+    }
+
+    /// Get the name of the function that failed.
+    pub fn function(&self) -> &'static str {
+        self.function
+    }
+}
+
+/// A category of Win32 errors.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Access to a resource was denied.
+    AccessDenied,
+
+    /// A handle, such as a window handle, was invalid.
+    InvalidHandle,
+
+    /// The requested resource, such as a file or window class, could not be found.
+    NotFound,
+
+    /// A window class with the same name has already been registered.
+    ClassAlreadyExists,
+
+    /// The system ran out of memory.
+    OutOfMemory,
+
+    /// A parameter passed to the function was invalid.
+    InvalidParameter,
+
+    /// The keys of a hotkey are already registered, possibly by another application.
+    HotKeyAlreadyRegistered,
+
+    /// Another error, with the given Win32 error code.
+    Other(u32),
+}
+
+impl ErrorKind {
+    /// Categorize a Win32 error code.
+    fn from_code(code: u32) -> Self {
+        match code {
+            ERROR_ACCESS_DENIED => Self::AccessDenied,
+            ERROR_INVALID_HANDLE | ERROR_INVALID_WINDOW_HANDLE => Self::InvalidHandle,
+            ERROR_FILE_NOT_FOUND | ERROR_NOT_FOUND | ERROR_CLASS_DOES_NOT_EXIST => Self::NotFound,
+            ERROR_CLASS_ALREADY_EXISTS => Self::ClassAlreadyExists,
+            ERROR_NOT_ENOUGH_MEMORY | ERROR_OUTOFMEMORY => Self::OutOfMemory,
+            ERROR_INVALID_PARAMETER => Self::InvalidParameter,
+            ERROR_HOTKEY_ALREADY_REGISTERED => Self::HotKeyAlreadyRegistered,
+            code => Self::Other(code),
+        }
+    }
 }
 
 impl fmt::Display for Error {
@@ -147,6 +280,19 @@ impl fmt::Display for Error {
 #[cfg(feature = "std")]
 impl std::error::Error for Error {}
 
+/// Convert into an I/O error that wraps this one.
+///
+/// The kind is the one the standard library picks for the Win32 error code. The original error,
+/// including the name of the function that failed, can be recovered with
+/// [`get_ref`](std::io::Error::get_ref) or [`into_inner`](std::io::Error::into_inner).
+#[cfg(feature = "std")]
+impl From<Error> for std::io::Error {
+    fn from(err: Error) -> Self {
+        let kind = std::io::Error::from_raw_os_error(err.code as i32).kind();
+        std::io::Error::new(kind, err)
+    }
+}
+
 mod strict {
     #![allow(clippy::useless_transmute, clippy::transmutes_expressible_as_ptr_casts)]
 
@@ -228,3 +374,71 @@ fn abort_on_panic<R>(f: impl FnOnce() -> R) -> R {
     core::mem::forget(_abort_on_panic);
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kind() {
+        assert_eq!(
+            ErrorKind::from_code(ERROR_ACCESS_DENIED),
+            ErrorKind::AccessDenied
+        );
+        assert_eq!(
+            ErrorKind::from_code(ERROR_INVALID_WINDOW_HANDLE),
+            ErrorKind::InvalidHandle
+        );
+        assert_eq!(
+            ErrorKind::from_code(ERROR_CLASS_ALREADY_EXISTS),
+            ErrorKind::ClassAlreadyExists
+        );
+        assert_eq!(
+            ErrorKind::from_code(ERROR_HOTKEY_ALREADY_REGISTERED),
+            ErrorKind::HotKeyAlreadyRegistered
+        );
+        assert_eq!(ErrorKind::from_code(1234), ErrorKind::Other(1234));
+    }
+
+    #[test]
+    fn test_error_code() {
+        use windows_sys::Win32::UI::WindowsAndMessaging::DestroyWindow;
+
+        // Destroying a null window always fails.
+        assert_eq!(unsafe { DestroyWindow(0) }, 0);
+        let err = Error::last_error("DestroyWindow");
+
+        assert_eq!(err.code(), ERROR_INVALID_WINDOW_HANDLE);
+        assert_eq!(err.function(), "DestroyWindow");
+        assert_eq!(err.kind(), ErrorKind::InvalidHandle);
+
+        let constructed = Error::from_code(ERROR_INVALID_WINDOW_HANDLE, "DestroyWindow");
+        assert_eq!(constructed.code(), err.code());
+        assert_eq!(constructed.to_string(), err.to_string());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_io_error() {
+        let err = Error::from_code(ERROR_ACCESS_DENIED, "DestroyWindow");
+        let io_err = std::io::Error::from(err);
+        assert_eq!(io_err.kind(), std::io::ErrorKind::PermissionDenied);
+
+        let inner = io_err
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<Error>())
+            .expect("The original error should be kept");
+        assert_eq!(inner.code(), ERROR_ACCESS_DENIED);
+        assert_eq!(inner.function(), "DestroyWindow");
+    }
+
+    #[test]
+    fn test_error_message() {
+        let err = Error::from_code(ERROR_FILE_NOT_FOUND, "CreateFileW");
+        let message = err.message.as_deref().expect("No message for a known code");
+
+        assert!(!message.is_empty());
+        assert!(!message.ends_with('\n'));
+        assert!(err.to_string().starts_with("CreateFileW failed: "));
+    }
+}
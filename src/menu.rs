@@ -7,13 +7,18 @@ use crate::cstr::CStr;
 use crate::Error;
 use core::mem;
 
-use windows_sys::Win32::UI::WindowsAndMessaging::{CreateMenu, DestroyMenu, InsertMenuItemA};
+use windows_sys::Win32::Foundation::ERROR_NOT_FOUND;
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    CheckMenuItem, CheckMenuRadioItem, CreateMenu, DeleteMenu, DestroyMenu, EnableMenuItem,
+    InsertMenuItemA, RemoveMenu, SetMenuItemInfoA,
+};
 use windows_sys::Win32::UI::WindowsAndMessaging::{
     HBMMENU_MBAR_CLOSE, HBMMENU_MBAR_CLOSE_D, HBMMENU_MBAR_MINIMIZE, HBMMENU_MBAR_MINIMIZE_D,
     HBMMENU_MBAR_RESTORE, HBMMENU_POPUP_CLOSE, HBMMENU_POPUP_MAXIMIZE, HBMMENU_POPUP_MINIMIZE,
     HBMMENU_POPUP_RESTORE, MFS_CHECKED, MFS_DEFAULT, MFS_DISABLED, MFS_HILITE, MFT_MENUBARBREAK,
-    MFT_MENUBREAK, MFT_RADIOCHECK, MFT_RIGHTJUSTIFY, MFT_RIGHTORDER, MFT_SEPARATOR, MIIM_BITMAP,
-    MIIM_CHECKMARKS, MIIM_FTYPE, MIIM_STATE, MIIM_STRING, MIIM_SUBMENU,
+    MFT_MENUBREAK, MFT_RADIOCHECK, MFT_RIGHTJUSTIFY, MFT_RIGHTORDER, MFT_SEPARATOR, MF_BYCOMMAND,
+    MF_BYPOSITION, MF_CHECKED, MF_ENABLED, MF_GRAYED, MF_UNCHECKED, MIIM_BITMAP, MIIM_CHECKMARKS,
+    MIIM_FTYPE, MIIM_ID, MIIM_STATE, MIIM_STRING, MIIM_SUBMENU,
 };
 use windows_sys::Win32::UI::WindowsAndMessaging::{HMENU, MENUITEMINFOA};
 
@@ -49,6 +54,9 @@ pub struct MenuItem<'a> {
     /// The item type.
     item_type: MenuItemType<'a>,
 
+    /// The command identifier for this item.
+    id: Option<u32>,
+
     /// Whether or not this item is a checkmark.
     checkmark: Option<CheckmarksInfo>,
 
@@ -163,6 +171,7 @@ impl<'a> MenuItem<'a> {
         Self {
             bitmap: None,
             item_type,
+            id: None,
             checkmark: None,
             fstate: None,
             ftype: None,
@@ -176,6 +185,12 @@ impl<'a> MenuItem<'a> {
         self
     }
 
+    /// Set the command identifier for this menu item.
+    pub fn id(&mut self, id: u32) -> &mut Self {
+        self.id = Some(id);
+        self
+    }
+
     /// Set the checkmark information for this menu item.
     pub fn checkbox(
         &mut self,
@@ -191,6 +206,14 @@ impl<'a> MenuItem<'a> {
         self
     }
 
+    /// Set the drop-down menu that this menu item opens.
+    ///
+    /// The submenu is owned by the menu that this item is inserted into.
+    pub fn submenu(&mut self, menu: Menu) -> &mut Self {
+        self.submenu = Some(menu);
+        self
+    }
+
     /// Convert this menu item into a menu item info.
     fn take_info(&mut self) -> MenuItemInfo {
         let mut info: MENUITEMINFOA = unsafe { mem::zeroed() };
@@ -235,6 +258,12 @@ impl<'a> MenuItem<'a> {
             };
         }
 
+        // Set the command identifier.
+        if let Some(id) = self.id {
+            info.fMask |= MIIM_ID;
+            info.wID = id;
+        }
+
         // Set additional FTYPE information.
         if let Some(ftype) = self.ftype {
             info.fMask |= MIIM_FTYPE;
@@ -340,6 +369,116 @@ impl Menu {
         self.insert(self.len as _, item)
     }
 
+    /// Push a new item that opens a submenu onto the menu.
+    ///
+    /// `build` is called with the empty submenu to add its items.
+    pub fn add_submenu(
+        &mut self,
+        label: &CStr,
+        build: impl FnOnce(&mut Menu) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let mut submenu = Menu::new()?;
+        build(&mut submenu)?;
+
+        self.push(MenuItem::string(label).submenu(submenu))
+    }
+
+    /// Remove the item at the given index from the menu.
+    ///
+    /// If the item opens a submenu, the submenu is not destroyed. Use [`Menu::delete`] to remove
+    /// the item and destroy its submenu.
+    pub fn remove(&mut self, index: u32) -> Result<(), Error> {
+        let result = unsafe { RemoveMenu(self.handle, index, MF_BYPOSITION) };
+
+        if result == 0 {
+            Err(Error::last_error("RemoveMenu"))
+        } else {
+            self.len -= 1;
+            Ok(())
+        }
+    }
+
+    /// Delete the item at the given index from the menu, destroying its submenu if it has one.
+    pub fn delete(&mut self, index: u32) -> Result<(), Error> {
+        let result = unsafe { DeleteMenu(self.handle, index, MF_BYPOSITION) };
+
+        if result == 0 {
+            Err(Error::last_error("DeleteMenu"))
+        } else {
+            self.len -= 1;
+            Ok(())
+        }
+    }
+
+    /// Set the state of the item at the given index.
+    ///
+    /// This replaces the item's current state, so flags that aren't in `state` are cleared.
+    pub fn set_item_state(&mut self, index: u32, state: Fstate) -> Result<(), Error> {
+        let mut info: MENUITEMINFOA = unsafe { mem::zeroed() };
+        info.cbSize = mem::size_of::<MENUITEMINFOA>() as _;
+        info.fMask = MIIM_STATE;
+        info.fState = state.bits();
+
+        let result = unsafe { SetMenuItemInfoA(self.handle, index, 1, &info) };
+
+        if result == 0 {
+            Err(Error::last_error("SetMenuItemInfoA"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check or uncheck the item with the given command identifier.
+    pub fn check_item(&mut self, id: u32, checked: bool) -> Result<(), Error> {
+        let flags = MF_BYCOMMAND | if checked { MF_CHECKED } else { MF_UNCHECKED };
+        let result = unsafe { CheckMenuItem(self.handle, id, flags) };
+
+        // CheckMenuItem returns the previous state, or -1 if the item does not exist. It doesn't
+        // set the last error in that case.
+        if result == u32::MAX {
+            Err(Error::custom(
+                ERROR_NOT_FOUND,
+                "CheckMenuItem",
+                "The menu has no item with this identifier.",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Enable or disable the item with the given command identifier.
+    pub fn enable_item(&mut self, id: u32, enabled: bool) -> Result<(), Error> {
+        let flags = MF_BYCOMMAND | if enabled { MF_ENABLED } else { MF_GRAYED };
+        let result = unsafe { EnableMenuItem(self.handle, id, flags) };
+
+        // EnableMenuItem returns the previous state, or -1 if the item does not exist. It doesn't
+        // set the last error in that case.
+        if result == -1 {
+            Err(Error::custom(
+                ERROR_NOT_FOUND,
+                "EnableMenuItem",
+                "The menu has no item with this identifier.",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check the item with the `selected` command identifier, and uncheck all other items in the
+    /// range of identifiers from `first` to `last`.
+    ///
+    /// The checked item is displayed with a radio-button bullet instead of a checkmark.
+    pub fn check_radio_item(&mut self, first: u32, last: u32, selected: u32) -> Result<(), Error> {
+        let result =
+            unsafe { CheckMenuRadioItem(self.handle, first, last, selected, MF_BYCOMMAND) };
+
+        if result == 0 {
+            Err(Error::last_error("CheckMenuRadioItem"))
+        } else {
+            Ok(())
+        }
+    }
+
     /// Number of items in the menu.
     pub fn len(&self) -> usize {
         self.len
@@ -350,6 +489,10 @@ impl Menu {
         self.len == 0
     }
 
+    pub(crate) fn handle(&self) -> HMENU {
+        self.handle
+    }
+
     pub(crate) fn into_handle(self) -> HMENU {
         let handle = self.handle;
         mem::forget(self);
@@ -366,6 +509,7 @@ impl Drop for Menu {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{GetMenuItemCount, GetSubMenu};
 
     #[test]
     fn test_menu() {
@@ -374,4 +518,62 @@ mod tests {
         menu.push(&mut item).unwrap();
         assert_eq!(menu.len(), 1);
     }
+
+    #[test]
+    fn test_menu_remove() {
+        let mut menu = Menu::new().unwrap();
+        for label in [&b"One\0"[..], b"Two\0", b"Three\0"] {
+            let mut item = MenuItem::string(CStr::from_bytes_with_nul(label).unwrap());
+            menu.push(&mut item).unwrap();
+        }
+        assert_eq!(menu.len(), 3);
+
+        menu.set_item_state(1, Fstate::CHECKED).unwrap();
+        menu.remove(1).unwrap();
+        assert_eq!(menu.len(), 2);
+
+        menu.delete(0).unwrap();
+        assert_eq!(menu.len(), 1);
+    }
+
+    #[test]
+    fn test_menu_check_by_id() {
+        let mut menu = Menu::new().unwrap();
+        for (id, label) in [(1, &b"One\0"[..]), (2, b"Two\0"), (3, b"Three\0")] {
+            let mut item = MenuItem::string(CStr::from_bytes_with_nul(label).unwrap());
+            item.id(id);
+            menu.push(&mut item).unwrap();
+        }
+
+        menu.check_radio_item(1, 3, 2).unwrap();
+        menu.check_item(1, true).unwrap();
+        menu.enable_item(3, false).unwrap();
+
+        // Items that don't exist should fail.
+        let err = menu.check_item(4, true).unwrap_err();
+        assert_eq!(err.code(), ERROR_NOT_FOUND);
+        let err = menu.enable_item(4, false).unwrap_err();
+        assert_eq!(err.code(), ERROR_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_menu_submenu() {
+        let mut menu = Menu::new().unwrap();
+        menu.add_submenu(CStr::from_bytes_with_nul(b"File\0").unwrap(), |submenu| {
+            for label in [&b"Open\0"[..], b"Save\0"] {
+                let mut item = MenuItem::string(CStr::from_bytes_with_nul(label).unwrap());
+                submenu.push(&mut item)?;
+            }
+
+            assert_eq!(submenu.len(), 2);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(menu.len(), 1);
+
+        // The submenu should be attached to the item.
+        let submenu = unsafe { GetSubMenu(menu.handle, 0) };
+        assert_ne!(submenu, 0);
+        assert_eq!(unsafe { GetMenuItemCount(submenu) }, 2);
+    }
 }
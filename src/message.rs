@@ -0,0 +1,362 @@
+// Boost/Apache2 License
+
+//! Typed window messages that can be awaited.
+
+use crate::geometry::point_from_lparam;
+#[cfg(feature = "std")]
+use crate::Error;
+
+use blood_geometry::{Point, Size};
+
+#[cfg(feature = "std")]
+use alloc::rc::{Rc, Weak};
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::cell::{Cell, RefCell};
+#[cfg(feature = "std")]
+use core::future::Future;
+#[cfg(feature = "std")]
+use core::pin::Pin;
+#[cfg(feature = "std")]
+use core::task::{Context, Poll, Waker};
+#[cfg(feature = "std")]
+use core::time::Duration;
+
+#[cfg(feature = "std")]
+use futures_lite::Stream;
+
+use windows_sys::Win32::Foundation::HWND;
+#[cfg(feature = "std")]
+use windows_sys::Win32::UI::WindowsAndMessaging::{KillTimer, SetTimer, WM_TIMER};
+use windows_sys::Win32::UI::WindowsAndMessaging::{WM_CLOSE, WM_DESTROY, WM_MOVE, WM_SIZE};
+
+/// A window message that can be parsed from its raw parameters.
+///
+/// This is used with [`BorrowedWindow::wait_for`](crate::window::BorrowedWindow::wait_for) to
+/// wait for a specific message.
+pub trait FromMessage: Sized + 'static {
+    /// Parse the message, returning `None` if it is a different message.
+    fn from_message(msg: u32, wparam: usize, lparam: isize) -> Option<Self>;
+}
+
+/// The window was resized.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Resized {
+    /// The new size of the client area.
+    pub size: Size<i32>,
+}
+
+impl FromMessage for Resized {
+    fn from_message(msg: u32, _wparam: usize, lparam: isize) -> Option<Self> {
+        if msg != WM_SIZE {
+            return None;
+        }
+
+        Some(Self {
+            size: Size::new((lparam & 0xFFFF) as i32, ((lparam >> 16) & 0xFFFF) as i32),
+        })
+    }
+}
+
+/// The window was moved.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Moved {
+    /// The new position of the top-left corner of the client area.
+    ///
+    /// This is in screen coordinates for top-level windows and in parent client coordinates for
+    /// child windows, so it can be negative.
+    pub position: Point<i32>,
+}
+
+impl FromMessage for Moved {
+    fn from_message(msg: u32, _wparam: usize, lparam: isize) -> Option<Self> {
+        if msg != WM_MOVE {
+            return None;
+        }
+
+        Some(Self {
+            position: point_from_lparam(lparam),
+        })
+    }
+}
+
+/// The user asked to close the window.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CloseRequested;
+
+impl FromMessage for CloseRequested {
+    fn from_message(msg: u32, _wparam: usize, _lparam: isize) -> Option<Self> {
+        if msg == WM_CLOSE {
+            Some(Self)
+        } else {
+            None
+        }
+    }
+}
+
+/// The window is being destroyed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Destroyed;
+
+impl FromMessage for Destroyed {
+    fn from_message(msg: u32, _wparam: usize, _lparam: isize) -> Option<Self> {
+        if msg == WM_DESTROY {
+            Some(Self)
+        } else {
+            None
+        }
+    }
+}
+
+// Futures waiting for a message on one of this thread's windows.
+#[cfg(feature = "std")]
+std::thread_local! {
+    static WAITERS: RefCell<Vec<(HWND, Weak<dyn Waiter>)>> = RefCell::new(Vec::new());
+}
+
+/// Something waiting for a message.
+#[cfg(feature = "std")]
+trait Waiter {
+    /// Offer a message to the waiter.
+    fn offer(&self, msg: u32, wparam: usize, lparam: isize);
+
+    /// Whether the waiter has accepted a message.
+    fn is_done(&self) -> bool;
+}
+
+/// The state shared between a [`WaitFor`] future and the registry.
+#[cfg(feature = "std")]
+struct WaitState<M> {
+    /// The message, once it has arrived.
+    message: Cell<Option<M>>,
+
+    /// The waker to wake once the message arrives.
+    waker: Cell<Option<Waker>>,
+
+    /// Whether the message has arrived.
+    done: Cell<bool>,
+}
+
+#[cfg(feature = "std")]
+impl<M: FromMessage> Waiter for WaitState<M> {
+    fn offer(&self, msg: u32, wparam: usize, lparam: isize) {
+        if self.done.get() {
+            return;
+        }
+
+        if let Some(message) = M::from_message(msg, wparam, lparam) {
+            self.message.set(Some(message));
+            self.done.set(true);
+
+            if let Some(waker) = self.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.done.get()
+    }
+}
+
+/// A future that resolves once a message arrives at a window.
+#[cfg(feature = "std")]
+pub(crate) struct WaitFor<M> {
+    /// The shared state.
+    state: Rc<WaitState<M>>,
+}
+
+#[cfg(feature = "std")]
+impl<M: FromMessage> WaitFor<M> {
+    /// Start waiting for a message on the window.
+    ///
+    /// The waiter is registered right away, so messages that arrive before the future is first
+    /// polled aren't missed.
+    pub(crate) fn new(hwnd: HWND) -> Self {
+        let state = Rc::new(WaitState {
+            message: Cell::new(None),
+            waker: Cell::new(None),
+            done: Cell::new(false),
+        });
+
+        let waiter: Rc<dyn Waiter> = state.clone();
+        WAITERS.with(|waiters| waiters.borrow_mut().push((hwnd, Rc::downgrade(&waiter))));
+
+        Self { state }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<M: FromMessage> Future for WaitFor<M> {
+    type Output = M;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<M> {
+        match self.state.message.take() {
+            Some(message) => Poll::Ready(message),
+            None => {
+                self.state.waker.set(Some(cx.waker().clone()));
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// The state shared between a [`WindowTimer`] and the registry.
+///
+/// The address of the state is used as the timer ID, since it is unique for as long as the
+/// timer lives.
+#[cfg(feature = "std")]
+struct TickState {
+    /// The number of ticks that haven't been consumed yet.
+    ticks: Cell<usize>,
+
+    /// The waker to wake on the next tick.
+    waker: Cell<Option<Waker>>,
+}
+
+#[cfg(feature = "std")]
+impl TickState {
+    /// Get the ID of the timer.
+    fn id(&self) -> usize {
+        self as *const Self as usize
+    }
+}
+
+#[cfg(feature = "std")]
+impl Waiter for TickState {
+    fn offer(&self, msg: u32, wparam: usize, _lparam: isize) {
+        if msg == WM_TIMER && wparam == self.id() {
+            self.ticks.set(self.ticks.get() + 1);
+
+            if let Some(waker) = self.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        // The timer keeps ticking until it is dropped.
+        false
+    }
+}
+
+/// A timer on a window, created by [`BorrowedWindow::timer`](crate::window::BorrowedWindow::timer).
+///
+/// This is a [`Stream`] that yields each time the timer elapses. Like the window's other
+/// messages, ticks are only delivered while the messages are being processed, and ticks that
+/// elapse before the stream is polled are combined. The timer is killed when this is dropped.
+#[cfg(feature = "std")]
+pub struct WindowTimer {
+    /// The window that the timer belongs to.
+    hwnd: HWND,
+
+    /// The shared state.
+    state: Rc<TickState>,
+}
+
+#[cfg(feature = "std")]
+impl WindowTimer {
+    /// Start a timer on the window that elapses every `interval`.
+    pub(crate) fn new(hwnd: HWND, interval: Duration) -> Result<Self, Error> {
+        let state = Rc::new(TickState {
+            ticks: Cell::new(0),
+            waker: Cell::new(None),
+        });
+
+        // SetTimer clamps the interval to the range it supports, so saturate rather than wrap.
+        let interval = interval.as_millis().min(u128::from(u32::MAX)) as u32;
+        if unsafe { SetTimer(hwnd, state.id(), interval, None) } == 0 {
+            return Err(Error::last_error("SetTimer"));
+        }
+
+        let waiter: Rc<dyn Waiter> = state.clone();
+        WAITERS.with(|waiters| waiters.borrow_mut().push((hwnd, Rc::downgrade(&waiter))));
+
+        Ok(Self { hwnd, state })
+    }
+}
+
+#[cfg(feature = "std")]
+impl Stream for WindowTimer {
+    type Item = ();
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<()>> {
+        if self.state.ticks.get() > 0 {
+            self.state.ticks.set(0);
+            Poll::Ready(Some(()))
+        } else {
+            self.state.waker.set(Some(cx.waker().clone()));
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for WindowTimer {
+    fn drop(&mut self) {
+        // This fails if the window is already gone, which also kills the timer.
+        unsafe { KillTimer(self.hwnd, self.state.id()) };
+    }
+}
+
+/// Offer a message to the futures waiting on this window.
+///
+/// Each waiter only accepts a single message, after which it is removed.
+#[cfg(feature = "std")]
+pub(crate) fn notify_waiters(hwnd: HWND, msg: u32, wparam: usize, lparam: isize) {
+    // Collect the waiters first, since waking them may register new ones.
+    let waiters = WAITERS.with(|waiters| {
+        let mut waiters = waiters.borrow_mut();
+
+        // Drop the waiters whose futures are gone or that already have their message.
+        waiters.retain(|(_, waiter)| matches!(waiter.upgrade(), Some(waiter) if !waiter.is_done()));
+
+        waiters
+            .iter()
+            .filter(|(handle, _)| *handle == hwnd)
+            .filter_map(|(_, waiter)| waiter.upgrade())
+            .collect::<Vec<_>>()
+    });
+
+    for waiter in waiters {
+        waiter.offer(msg, wparam, lparam);
+    }
+}
+
+/// Without libstd, there is nothing to wait on messages.
+#[cfg(not(feature = "std"))]
+#[inline]
+pub(crate) fn notify_waiters(_hwnd: HWND, _msg: u32, _wparam: usize, _lparam: isize) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_message() {
+        let lparam = (20 << 16) | 10;
+        assert_eq!(
+            Resized::from_message(WM_SIZE, 0, lparam),
+            Some(Resized {
+                size: Size::new(10, 20)
+            })
+        );
+        assert_eq!(Resized::from_message(WM_MOVE, 0, lparam), None);
+
+        // Moves can go into negative coordinates.
+        let lparam = (0xFFFE << 16) | 0xFFFF;
+        assert_eq!(
+            Moved::from_message(WM_MOVE, 0, lparam),
+            Some(Moved {
+                position: Point::new(-1, -2)
+            })
+        );
+
+        assert_eq!(
+            CloseRequested::from_message(WM_CLOSE, 0, 0),
+            Some(CloseRequested)
+        );
+        assert_eq!(Destroyed::from_message(WM_CLOSE, 0, 0), None);
+    }
+}
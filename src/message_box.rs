@@ -0,0 +1,189 @@
+// Boost/Apache2 License
+
+//! Simple modal message boxes.
+
+use crate::client::Client;
+use crate::cstr::CStr;
+use crate::window::BorrowedWindow;
+use crate::Error;
+
+use windows_sys::Win32::UI::WindowsAndMessaging::MessageBoxA;
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    IDABORT, IDCANCEL, IDCONTINUE, IDIGNORE, IDNO, IDOK, IDRETRY, IDTRYAGAIN, IDYES,
+};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    MB_ABORTRETRYIGNORE, MB_CANCELTRYCONTINUE, MB_DEFBUTTON2, MB_DEFBUTTON3, MB_ICONERROR,
+    MB_ICONINFORMATION, MB_ICONQUESTION, MB_ICONWARNING, MB_OK, MB_OKCANCEL, MB_RETRYCANCEL,
+    MB_SETFOREGROUND, MB_SYSTEMMODAL, MB_TASKMODAL, MB_TOPMOST, MB_YESNO, MB_YESNOCANCEL,
+};
+
+impl Client {
+    /// Show a modal message box and wait for the user to dismiss it.
+    ///
+    /// If `owner` is given, it is disabled while the message box is open.
+    pub fn message_box(
+        &self,
+        owner: Option<BorrowedWindow<'_>>,
+        text: &CStr,
+        caption: &CStr,
+        flags: MessageBoxFlags,
+    ) -> Result<MessageBoxResult, Error> {
+        let result = unsafe {
+            MessageBoxA(
+                owner.map_or(0, |owner| owner.handle()),
+                text.as_ptr().cast(),
+                caption.as_ptr().cast(),
+                flags.bits(),
+            )
+        };
+
+        if result == 0 {
+            return Err(Error::last_error("MessageBoxA"));
+        }
+
+        match MessageBoxResult::from_raw(result) {
+            Some(result) => Ok(result),
+            None => {
+                // This shouldn't happen for the buttons we expose.
+                tracing::warn!("Unexpected MessageBoxA result: {}", result);
+                Ok(MessageBoxResult::Cancel)
+            }
+        }
+    }
+}
+
+bitflags::bitflags! {
+    /// The buttons, icon and behavior of a message box.
+    pub struct MessageBoxFlags : u32 {
+        /// The message box has an OK button.
+        const OK = MB_OK;
+
+        /// The message box has OK and Cancel buttons.
+        const OK_CANCEL = MB_OKCANCEL;
+
+        /// The message box has Abort, Retry and Ignore buttons.
+        const ABORT_RETRY_IGNORE = MB_ABORTRETRYIGNORE;
+
+        /// The message box has Yes, No and Cancel buttons.
+        const YES_NO_CANCEL = MB_YESNOCANCEL;
+
+        /// The message box has Yes and No buttons.
+        const YES_NO = MB_YESNO;
+
+        /// The message box has Retry and Cancel buttons.
+        const RETRY_CANCEL = MB_RETRYCANCEL;
+
+        /// The message box has Cancel, Try Again and Continue buttons.
+        const CANCEL_TRY_CONTINUE = MB_CANCELTRYCONTINUE;
+
+        /// The message box shows a stop-sign icon.
+        const ICON_ERROR = MB_ICONERROR;
+
+        /// The message box shows a question-mark icon.
+        const ICON_QUESTION = MB_ICONQUESTION;
+
+        /// The message box shows an exclamation-point icon.
+        const ICON_WARNING = MB_ICONWARNING;
+
+        /// The message box shows an "i" icon.
+        const ICON_INFORMATION = MB_ICONINFORMATION;
+
+        /// The second button is the default button.
+        const DEFAULT_BUTTON_2 = MB_DEFBUTTON2;
+
+        /// The third button is the default button.
+        const DEFAULT_BUTTON_3 = MB_DEFBUTTON3;
+
+        /// All top-level windows of the thread are disabled while the message box is open.
+        const TASK_MODAL = MB_TASKMODAL;
+
+        /// The message box is modal and has the topmost style.
+        const SYSTEM_MODAL = MB_SYSTEMMODAL;
+
+        /// The message box is brought to the foreground.
+        const SET_FOREGROUND = MB_SETFOREGROUND;
+
+        /// The message box has the topmost style.
+        const TOPMOST = MB_TOPMOST;
+    }
+}
+
+/// The button that was used to dismiss a message box.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MessageBoxResult {
+    /// The OK button.
+    Ok,
+
+    /// The Cancel button, or the message box was closed.
+    Cancel,
+
+    /// The Abort button.
+    Abort,
+
+    /// The Retry button.
+    Retry,
+
+    /// The Ignore button.
+    Ignore,
+
+    /// The Yes button.
+    Yes,
+
+    /// The No button.
+    No,
+
+    /// The Try Again button.
+    TryAgain,
+
+    /// The Continue button.
+    Continue,
+}
+
+impl MessageBoxResult {
+    /// Decode the value returned by `MessageBoxA`.
+    fn from_raw(result: i32) -> Option<Self> {
+        match result {
+            IDOK => Some(Self::Ok),
+            IDCANCEL => Some(Self::Cancel),
+            IDABORT => Some(Self::Abort),
+            IDRETRY => Some(Self::Retry),
+            IDIGNORE => Some(Self::Ignore),
+            IDYES => Some(Self::Yes),
+            IDNO => Some(Self::No),
+            IDTRYAGAIN => Some(Self::TryAgain),
+            IDCONTINUE => Some(Self::Continue),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_box_result() {
+        assert_eq!(MessageBoxResult::from_raw(IDOK), Some(MessageBoxResult::Ok));
+        assert_eq!(
+            MessageBoxResult::from_raw(IDCANCEL),
+            Some(MessageBoxResult::Cancel)
+        );
+        assert_eq!(
+            MessageBoxResult::from_raw(IDYES),
+            Some(MessageBoxResult::Yes)
+        );
+        assert_eq!(MessageBoxResult::from_raw(IDNO), Some(MessageBoxResult::No));
+        assert_eq!(
+            MessageBoxResult::from_raw(IDCONTINUE),
+            Some(MessageBoxResult::Continue)
+        );
+        assert_eq!(MessageBoxResult::from_raw(0), None);
+    }
+
+    #[test]
+    fn test_message_box_flags() {
+        let flags = MessageBoxFlags::YES_NO | MessageBoxFlags::ICON_WARNING;
+        assert_eq!(flags.bits(), MB_YESNO | MB_ICONWARNING);
+        assert!(MessageBoxFlags::OK.is_empty());
+    }
+}
@@ -0,0 +1,209 @@
+// Boost/Apache2 License
+
+//! Dimensions of display elements and other system settings.
+
+use crate::client::Client;
+
+use blood_geometry::{Point, Rect, Size};
+
+use windows_sys::Win32::UI::WindowsAndMessaging::GetSystemMetrics;
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    SM_CMONITORS, SM_CMOUSEBUTTONS, SM_CXBORDER, SM_CXCURSOR, SM_CXDOUBLECLK, SM_CXDRAG,
+    SM_CXFULLSCREEN, SM_CXICON, SM_CXMINTRACK, SM_CXPADDEDBORDER, SM_CXSCREEN, SM_CXSIZEFRAME,
+    SM_CXSMICON, SM_CXVIRTUALSCREEN, SM_CXVSCROLL, SM_CYBORDER, SM_CYCAPTION, SM_CYCURSOR,
+    SM_CYDOUBLECLK, SM_CYDRAG, SM_CYFULLSCREEN, SM_CYHSCROLL, SM_CYICON, SM_CYMENU, SM_CYMINTRACK,
+    SM_CYSCREEN, SM_CYSIZEFRAME, SM_CYSMICON, SM_CYVIRTUALSCREEN, SM_XVIRTUALSCREEN,
+    SM_YVIRTUALSCREEN, SYSTEM_METRICS_INDEX,
+};
+
+impl Client {
+    /// Get a system metric.
+    ///
+    /// Metrics that are dimensions are in pixels. Many of them depend on the DPI of the primary
+    /// monitor at the time the application started; for windows on other monitors, the
+    /// per-monitor `GetSystemMetricsForDpi` gives the correct values. Returns `0` if the metric
+    /// is unavailable.
+    pub fn system_metric(&self, metric: SystemMetric) -> i32 {
+        unsafe { GetSystemMetrics(metric.index()) }
+    }
+
+    /// Get the bounding rectangle of all monitors, in virtual screen coordinates.
+    ///
+    /// The primary monitor's top-left corner is the origin, so monitors to its left or above it
+    /// have negative coordinates. Parts of this rectangle may not be covered by any monitor.
+    pub fn virtual_screen(&self) -> Rect<i32> {
+        Rect::new(
+            Point::new(
+                self.system_metric(SystemMetric::VirtualScreenX),
+                self.system_metric(SystemMetric::VirtualScreenY),
+            ),
+            Size::new(
+                self.system_metric(SystemMetric::VirtualScreenWidth),
+                self.system_metric(SystemMetric::VirtualScreenHeight),
+            ),
+        )
+    }
+}
+
+/// A system metric that can be queried with [`Client::system_metric`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SystemMetric {
+    /// The width of the primary monitor.
+    ScreenWidth,
+
+    /// The height of the primary monitor.
+    ScreenHeight,
+
+    /// The left side of the virtual screen, which spans all monitors.
+    VirtualScreenX,
+
+    /// The top of the virtual screen, which spans all monitors.
+    VirtualScreenY,
+
+    /// The width of the virtual screen, which spans all monitors.
+    VirtualScreenWidth,
+
+    /// The height of the virtual screen, which spans all monitors.
+    VirtualScreenHeight,
+
+    /// The width of the client area of a full-screen window on the primary monitor.
+    FullScreenWidth,
+
+    /// The height of the client area of a full-screen window on the primary monitor.
+    FullScreenHeight,
+
+    /// The height of a caption area.
+    CaptionHeight,
+
+    /// The height of a single-line menu bar.
+    MenuHeight,
+
+    /// The width of a window border.
+    BorderWidth,
+
+    /// The height of a window border.
+    BorderHeight,
+
+    /// The width of the sizing border around a resizable window.
+    SizeFrameWidth,
+
+    /// The height of the sizing border around a resizable window.
+    SizeFrameHeight,
+
+    /// The amount of border padding for captioned windows.
+    PaddedBorderWidth,
+
+    /// The minimum tracking width of a window.
+    MinTrackWidth,
+
+    /// The minimum tracking height of a window.
+    MinTrackHeight,
+
+    /// The width of the rectangle the second click of a double-click must be in.
+    DoubleClickWidth,
+
+    /// The height of the rectangle the second click of a double-click must be in.
+    DoubleClickHeight,
+
+    /// The number of pixels the mouse can move horizontally before a drag starts.
+    DragWidth,
+
+    /// The number of pixels the mouse can move vertically before a drag starts.
+    DragHeight,
+
+    /// The width of a cursor.
+    CursorWidth,
+
+    /// The height of a cursor.
+    CursorHeight,
+
+    /// The default width of an icon.
+    IconWidth,
+
+    /// The default height of an icon.
+    IconHeight,
+
+    /// The recommended width of a small icon.
+    SmallIconWidth,
+
+    /// The recommended height of a small icon.
+    SmallIconHeight,
+
+    /// The width of a vertical scroll bar.
+    VerticalScrollWidth,
+
+    /// The height of a horizontal scroll bar.
+    HorizontalScrollHeight,
+
+    /// The number of display monitors on the desktop.
+    MonitorCount,
+
+    /// The number of buttons on the mouse, or zero if there is no mouse.
+    MouseButtonCount,
+}
+
+impl SystemMetric {
+    /// Get the `SM_*` index for this metric.
+    fn index(self) -> SYSTEM_METRICS_INDEX {
+        match self {
+            Self::ScreenWidth => SM_CXSCREEN,
+            Self::ScreenHeight => SM_CYSCREEN,
+            Self::VirtualScreenX => SM_XVIRTUALSCREEN,
+            Self::VirtualScreenY => SM_YVIRTUALSCREEN,
+            Self::VirtualScreenWidth => SM_CXVIRTUALSCREEN,
+            Self::VirtualScreenHeight => SM_CYVIRTUALSCREEN,
+            Self::FullScreenWidth => SM_CXFULLSCREEN,
+            Self::FullScreenHeight => SM_CYFULLSCREEN,
+            Self::CaptionHeight => SM_CYCAPTION,
+            Self::MenuHeight => SM_CYMENU,
+            Self::BorderWidth => SM_CXBORDER,
+            Self::BorderHeight => SM_CYBORDER,
+            Self::SizeFrameWidth => SM_CXSIZEFRAME,
+            Self::SizeFrameHeight => SM_CYSIZEFRAME,
+            Self::PaddedBorderWidth => SM_CXPADDEDBORDER,
+            Self::MinTrackWidth => SM_CXMINTRACK,
+            Self::MinTrackHeight => SM_CYMINTRACK,
+            Self::DoubleClickWidth => SM_CXDOUBLECLK,
+            Self::DoubleClickHeight => SM_CYDOUBLECLK,
+            Self::DragWidth => SM_CXDRAG,
+            Self::DragHeight => SM_CYDRAG,
+            Self::CursorWidth => SM_CXCURSOR,
+            Self::CursorHeight => SM_CYCURSOR,
+            Self::IconWidth => SM_CXICON,
+            Self::IconHeight => SM_CYICON,
+            Self::SmallIconWidth => SM_CXSMICON,
+            Self::SmallIconHeight => SM_CYSMICON,
+            Self::VerticalScrollWidth => SM_CXVSCROLL,
+            Self::HorizontalScrollHeight => SM_CYHSCROLL,
+            Self::MonitorCount => SM_CMONITORS,
+            Self::MouseButtonCount => SM_CMOUSEBUTTONS,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_metric() {
+        let client = Client::new();
+
+        assert!(client.system_metric(SystemMetric::ScreenWidth) > 0);
+        assert!(client.system_metric(SystemMetric::ScreenHeight) > 0);
+        assert!(client.system_metric(SystemMetric::MonitorCount) >= 1);
+    }
+
+    #[test]
+    fn test_virtual_screen() {
+        let client = Client::new();
+        let screen = client.virtual_screen();
+        assert!(screen.size().width() > 0);
+        assert!(screen.size().height() > 0);
+
+        // The primary monitor's origin is inside the virtual screen.
+        let origin = screen.origin();
+        assert!(origin.x() <= 0 && origin.x() + screen.size().width() > 0);
+        assert!(origin.y() <= 0 && origin.y() + screen.size().height() > 0);
+    }
+}
@@ -0,0 +1,125 @@
+// Boost/Apache2 License
+
+//! Display monitors.
+
+use crate::client::Client;
+use crate::geometry::rect_from_win32;
+use crate::Error;
+
+use alloc::vec::Vec;
+
+use blood_geometry::Rect;
+
+use core::mem::{self, MaybeUninit};
+use core::ptr;
+
+use windows_sys::Win32::Foundation::{BOOL, LPARAM, RECT};
+use windows_sys::Win32::Graphics::Gdi::{EnumDisplayMonitors, GetMonitorInfoW};
+use windows_sys::Win32::Graphics::Gdi::{HDC, HMONITOR, MONITORINFO};
+use windows_sys::Win32::UI::WindowsAndMessaging::MONITORINFOF_PRIMARY;
+
+/// A display monitor.
+///
+/// Monitors are owned by the system, so this is only an identifier. It becomes invalid if the
+/// monitor is disconnected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Monitor {
+    /// The handle to the monitor.
+    handle: HMONITOR,
+}
+
+impl Monitor {
+    /// Create a `Monitor` from a raw handle.
+    pub(crate) fn from_raw(handle: HMONITOR) -> Self {
+        Self { handle }
+    }
+
+    /// Get the rectangle of the monitor, in virtual screen coordinates.
+    pub fn bounds(&self) -> Result<Rect<i32>, Error> {
+        self.info().map(|info| rect_from_win32(info.rcMonitor))
+    }
+
+    /// Get the part of the monitor that isn't covered by the taskbar and other docked toolbars,
+    /// in virtual screen coordinates.
+    pub fn work_area(&self) -> Result<Rect<i32>, Error> {
+        self.info().map(|info| rect_from_win32(info.rcWork))
+    }
+
+    /// Whether this is the primary monitor.
+    pub fn is_primary(&self) -> Result<bool, Error> {
+        self.info()
+            .map(|info| info.dwFlags & MONITORINFOF_PRIMARY != 0)
+    }
+
+    /// Get the information about this monitor.
+    fn info(&self) -> Result<MONITORINFO, Error> {
+        let mut info = MaybeUninit::<MONITORINFO>::zeroed();
+
+        unsafe {
+            (*info.as_mut_ptr()).cbSize = mem::size_of::<MONITORINFO>() as u32;
+
+            if GetMonitorInfoW(self.handle, info.as_mut_ptr()) == 0 {
+                Err(Error::last_error("GetMonitorInfoW"))
+            } else {
+                Ok(info.assume_init())
+            }
+        }
+    }
+}
+
+impl Client {
+    /// Get all of the display monitors.
+    pub fn monitors(&self) -> Result<Vec<Monitor>, Error> {
+        unsafe extern "system" fn push_monitor(
+            handle: HMONITOR,
+            _dc: HDC,
+            _rect: *mut RECT,
+            monitors: LPARAM,
+        ) -> BOOL {
+            let monitors = &mut *(monitors as *mut Vec<Monitor>);
+            monitors.push(Monitor::from_raw(handle));
+
+            // Continue the enumeration.
+            1
+        }
+
+        let mut monitors = Vec::new();
+        let result = unsafe {
+            EnumDisplayMonitors(
+                0,
+                ptr::null(),
+                Some(push_monitor),
+                &mut monitors as *mut Vec<Monitor> as LPARAM,
+            )
+        };
+
+        if result == 0 {
+            Err(Error::last_error("EnumDisplayMonitors"))
+        } else {
+            Ok(monitors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monitors() {
+        let client = Client::new();
+        let monitors = client.monitors().expect("Failed to enumerate monitors");
+        assert!(!monitors.is_empty());
+
+        let primary = monitors
+            .iter()
+            .find(|monitor| monitor.is_primary().unwrap())
+            .expect("No primary monitor");
+        let bounds = primary.bounds().expect("Failed to get bounds");
+        assert!(bounds.size().width() > 0);
+        assert!(bounds.size().height() > 0);
+
+        // The primary monitor's top-left corner is always the origin.
+        assert_eq!(bounds.origin(), blood_geometry::Point::new(0, 0));
+    }
+}
@@ -2,16 +2,22 @@
 
 //! The reactor used to process Win32 messages.
 
+use crate::client::Client;
 use crate::{strict, Error};
 
+#[cfg(feature = "std")]
+use alloc::boxed::Box;
 use alloc::sync::Arc;
+use alloc::vec::Vec;
 use alloc::task::{Wake};
+use core::cell::RefCell;
 use core::convert::Infallible;
 use core::future::Future;
 use core::mem::{self, ManuallyDrop, MaybeUninit};
+#[cfg(feature = "std")]
 use core::pin::Pin;
 use core::ptr;
-use core::sync::atomic::{AtomicU32, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
 
 use event_listener::Event as Signal;
@@ -19,15 +25,22 @@ use futures_lite::{future, pin};
 
 use windows_sys::Win32::Foundation::HANDLE;
 use windows_sys::Win32::Foundation::{CloseHandle, DuplicateHandle, GetLastError};
-use windows_sys::Win32::Foundation::{DUPLICATE_SAME_ACCESS, ERROR_SUCCESS, WAIT_FAILED};
+use windows_sys::Win32::Foundation::{
+    DUPLICATE_SAME_ACCESS, ERROR_INVALID_PARAMETER, ERROR_INVALID_THREAD_ID, ERROR_SUCCESS,
+    WAIT_FAILED,
+};
 
-use windows_sys::Win32::System::Threading::{CreateEventW, GetCurrentProcess, SetEvent};
+use windows_sys::Win32::System::SystemServices::MAXIMUM_WAIT_OBJECTS;
+use windows_sys::Win32::System::Threading::{
+    CreateEventW, CreateWaitableTimerW, GetCurrentProcess, GetCurrentThreadId, SetEvent,
+    SetWaitableTimer,
+};
 
 use windows_sys::Win32::System::WindowsProgramming::INFINITE;
 
 use windows_sys::Win32::UI::WindowsAndMessaging::MSG;
 use windows_sys::Win32::UI::WindowsAndMessaging::{
-    DispatchMessageA, MsgWaitForMultipleObjectsEx, PeekMessageA, TranslateMessage,
+    DispatchMessageW, MsgWaitForMultipleObjectsEx, PeekMessageW, TranslateMessage,
 };
 use windows_sys::Win32::UI::WindowsAndMessaging::{PM_REMOVE, QS_ALLINPUT, WM_QUIT};
 
@@ -44,29 +57,270 @@ pub(crate) async fn wait_for_message() {
     MESSAGE_RECEIVED.listen().await;
 }
 
+// Panics caught in the window procedures of this thread, waiting to be propagated.
+#[cfg(feature = "std")]
+std::thread_local! {
+    static PANICS: core::cell::RefCell<alloc::collections::VecDeque<Box<dyn core::any::Any + Send>>> =
+        core::cell::RefCell::new(alloc::collections::VecDeque::new());
+}
+
+/// Queue a panic caught in a window procedure.
+#[cfg(feature = "std")]
+pub(crate) fn push_panic(panic: Box<dyn core::any::Any + Send>) {
+    PANICS.with(|panics| panics.borrow_mut().push_back(panic));
+}
+
+/// Resume the first panic caught in a window procedure, if any.
+///
+/// Panics caught after the first one, possibly from other windows, are logged and discarded.
+#[cfg(feature = "std")]
+pub(crate) fn propagate_panics() {
+    let (first, rest) = PANICS.with(|panics| {
+        let mut panics = panics.borrow_mut();
+        (panics.pop_front(), mem::take(&mut *panics))
+    });
+
+    if let Some(first) = first {
+        for panic in rest {
+            let message = panic
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| panic.downcast_ref::<String>().map(|s| s.as_str()))
+                .unwrap_or("Box<dyn Any>");
+
+            tracing::error!(
+                "Discarding additional panic from window procedure: {}",
+                message
+            );
+        }
+
+        std::panic::resume_unwind(first);
+    }
+}
+
+// Tasks spawned onto this thread's reactor, polled alongside the main future.
+#[cfg(feature = "std")]
+std::thread_local! {
+    static TASKS: RefCell<Vec<Pin<Box<dyn Future<Output = ()>>>>> = RefCell::new(Vec::new());
+}
+
+/// Poll the tasks spawned on this thread once, dropping the ones that have completed.
+#[cfg(feature = "std")]
+fn poll_tasks(context: &mut Context<'_>) {
+    // Take the tasks out, so they can spawn new tasks while being polled.
+    let mut tasks = TASKS.with(|tasks| mem::take(&mut *tasks.borrow_mut()));
+
+    let mut i = 0;
+    while i < tasks.len() {
+        if tasks[i].as_mut().poll(context).is_ready() {
+            drop(tasks.swap_remove(i));
+        } else {
+            i += 1;
+        }
+    }
+
+    TASKS.with(|spawned| {
+        let mut spawned = spawned.borrow_mut();
+        tasks.append(&mut spawned);
+        *spawned = tasks;
+    });
+}
+
+/// Without libstd, there are no spawned tasks.
+#[cfg(not(feature = "std"))]
+#[inline]
+fn poll_tasks(_context: &mut Context<'_>) {}
+
+/// Without libstd, panics abort before they can reach the reactor.
+#[cfg(not(feature = "std"))]
+#[inline]
+pub(crate) fn propagate_panics() {}
+
 /// The reactor used to process Win32 messages.
 pub struct Reactor {
     /// An event that can be signalled to wake up the reactor.
     notify: Arc<Event>,
+
+    /// Additional handles to wait on.
+    handles: RefCell<Vec<WaitEntry>>,
+
+    /// The thread that created the reactor.
+    ///
+    /// Message queues are per-thread, so the reactor only sees the messages of this thread.
+    thread_id: u32,
+}
+
+/// A handle that the reactor waits on.
+struct WaitEntry {
+    /// The handle to wait on.
+    handle: HANDLE,
+
+    /// The state shared with the token.
+    state: Arc<HandleState>,
+}
+
+/// The state of a handle registered with the reactor.
+struct HandleState {
+    /// Whether the handle has been signalled.
+    signalled: AtomicBool,
+
+    /// Listeners waiting for the handle to be signalled.
+    signal: Signal,
+
+    /// Whether the token was dropped, so the handle should no longer be waited on.
+    cancelled: AtomicBool,
+}
+
+/// A token for a handle registered with [`Reactor::add_handle`].
+///
+/// Dropping the token removes the handle from the reactor.
+pub struct HandleToken {
+    /// The state shared with the reactor.
+    state: Arc<HandleState>,
+
+    /// The event used to wake up the reactor, so it stops waiting on the handle.
+    notify: Arc<Event>,
+}
+
+impl HandleToken {
+    /// Whether the handle has been signalled.
+    pub fn is_signalled(&self) -> bool {
+        self.state.signalled.load(Ordering::Acquire)
+    }
+
+    /// Wait until the handle is signalled.
+    ///
+    /// This only completes while the reactor that the handle was registered with is running.
+    pub async fn wait(&self) {
+        loop {
+            if self.is_signalled() {
+                return;
+            }
+
+            // Register a listener, then check again to avoid missing a notification.
+            let listener = self.state.signal.listen();
+            if self.is_signalled() {
+                return;
+            }
+
+            listener.await;
+        }
+    }
+}
+
+impl Drop for HandleToken {
+    fn drop(&mut self) {
+        self.state.cancelled.store(true, Ordering::Release);
+
+        // If the reactor is waiting on the handle, wake it up so it rebuilds its wait array.
+        if let Err(e) = self.notify.set() {
+            tracing::error!("Failed to wake up the reactor: {}", e)
+        }
+    }
 }
 
+/// The number of handles that can be added to a reactor.
+///
+/// `MsgWaitForMultipleObjectsEx` can wait on one less than `MAXIMUM_WAIT_OBJECTS` handles, and
+/// one of those is used to wake up the reactor.
+const MAX_HANDLES: usize = MAXIMUM_WAIT_OBJECTS as usize - 2;
+
 impl Reactor {
     /// Create a new reactor for this variant.
     pub fn new() -> Result<Self, Error> {
         Ok(Self {
             notify: Arc::new(Event::new()?),
+            handles: RefCell::new(Vec::new()),
+            thread_id: unsafe { GetCurrentThreadId() },
         })
     }
 
+    /// Make sure that the reactor is being run on the thread that created it.
+    fn check_thread(&self, function: &'static str) -> Result<(), Error> {
+        if unsafe { GetCurrentThreadId() } == self.thread_id {
+            Ok(())
+        } else {
+            Err(Error::custom(
+                ERROR_INVALID_THREAD_ID,
+                function,
+                "The reactor must be run on the thread that created it.",
+            ))
+        }
+    }
+
+    /// Wake up the reactor when `handle` is signalled.
+    ///
+    /// `handle` can be anything that `WaitForMultipleObjects` can wait on, such as an event,
+    /// semaphore or process handle. The handle is only waited on once: after it is signalled,
+    /// it is removed from the reactor and the returned token completes. Up to 62 handles can be
+    /// waited on at once.
+    ///
+    /// Since [`block_on`](Self::block_on) borrows the reactor, handles can also be added from
+    /// the future it is running. The handle must stay open until it is signalled or the token
+    /// is dropped. If the token is dropped on another thread, the handle is only removed once the
+    /// reactor wakes up, so it should stay open until then.
+    pub fn add_handle(&self, handle: HANDLE) -> Result<HandleToken, Error> {
+        let mut handles = self.handles.borrow_mut();
+        prune_cancelled(&mut handles);
+
+        if handles.len() >= MAX_HANDLES {
+            return Err(Error::custom(
+                ERROR_INVALID_PARAMETER,
+                "add_handle",
+                "Too many handles are registered with the reactor.",
+            ));
+        }
+
+        let state = Arc::new(HandleState {
+            signalled: AtomicBool::new(false),
+            signal: Signal::new(),
+            cancelled: AtomicBool::new(false),
+        });
+        handles.push(WaitEntry {
+            handle,
+            state: state.clone(),
+        });
+
+        Ok(HandleToken {
+            state,
+            notify: self.notify.clone(),
+        })
+    }
+
+    /// Spawn a task that runs on the reactor's thread.
+    ///
+    /// The task is polled alongside the future passed to [`block_on`](Self::block_on) or
+    /// [`run`](Self::run), so it can await window messages and other events concurrently with
+    /// the message loop. Since the task doesn't need to be `Send`, this fails if called from a
+    /// thread other than the one that created the reactor. Tasks that haven't completed when the
+    /// reactor returns are kept until it is run again on this thread.
+    #[cfg(feature = "std")]
+    pub fn spawn(&self, future: impl Future<Output = ()> + 'static) -> Result<(), Error> {
+        self.check_thread("spawn")?;
+        TASKS.with(|tasks| tasks.borrow_mut().push(Box::pin(future)));
+        Ok(())
+    }
+
     /// Block on this reactor and run the given future.
-    pub fn block_on<R>(self, future: impl Future<Output = R>) -> Result<Option<R>, Error> {
-        // Pin ourselves to the stack.
-        let this = self;
-        pin!(this);
+    ///
+    /// Returns `None` if the application quit before the future completed. This fails if called
+    /// from a thread other than the one that created the reactor, since it would never see the
+    /// messages for that thread's windows.
+    pub fn block_on<R>(&self, future: impl Future<Output = R>) -> Result<Option<R>, Error> {
+        self.block_on_until_quit(future, "block_on").map(Result::ok)
+    }
+
+    /// Block on this reactor and run the given future, or return the exit code if the
+    /// application quits first.
+    fn block_on_until_quit<R>(
+        &self,
+        future: impl Future<Output = R>,
+        function: &'static str,
+    ) -> Result<Result<R, i32>, Error> {
+        self.check_thread(function)?;
 
         // Get the waker for this reactor.
-        let notify = &this.as_mut().into_ref().notify;
-        let waker = Waker::from(notify.clone());
+        let waker = Waker::from(self.notify.clone());
 
         // Use this context to poll the event.
         let mut context = Context::from_waker(&waker);
@@ -76,25 +330,40 @@ impl Reactor {
         loop {
             // Poll the future to see if it's ready.
             if let Poll::Ready(result) = future.as_mut().poll(&mut context) {
-                return Ok(Some(result));
+                return Ok(Ok(result));
             }
 
+            // Poll the spawned tasks as well.
+            poll_tasks(&mut context);
+
             // Otherwise, wait for and process window messages.
             loop {
                 // Drain all messages from the queue.
-                let status = this.as_mut().drain_queue()?;
+                let status = drain_queue()?;
 
                 // If we need to quit, then we're done.
                 if status.quit {
-                    return Ok(None);
+                    return Ok(Err(status.exit_code));
                 }
 
-                // Re-project to get the notify handle.
-                let notify = &this.as_mut().into_ref().notify;
+                // Stop waiting on handles whose tokens were dropped.
+                prune_cancelled(&mut self.handles.borrow_mut());
+
+                // The notify event comes first, followed by the registered handles.
+                let wait_handles = core::iter::once(self.notify.handle())
+                    .chain(self.handles.borrow().iter().map(|entry| entry.handle))
+                    .collect::<Vec<_>>();
+                let count = wait_handles.len() as u32;
 
-                // Wait for either a new message or the notify event.
+                // Wait for either a new message, the notify event or a registered handle.
                 let result = unsafe {
-                    MsgWaitForMultipleObjectsEx(1, &notify.handle(), INFINITE, QS_ALLINPUT, 0)
+                    MsgWaitForMultipleObjectsEx(
+                        count,
+                        wait_handles.as_ptr(),
+                        INFINITE,
+                        QS_ALLINPUT,
+                        0,
+                    )
                 };
 
                 match result {
@@ -102,7 +371,17 @@ impl Reactor {
                         // The future's waker woke us up. Poll the future again.
                         break;
                     }
-                    1 => {
+                    index if index < count => {
+                        // A registered handle was signalled. Stop waiting on it and wake up
+                        // anyone waiting on its token.
+                        let entry = self.handles.borrow_mut().remove(index as usize - 1);
+                        entry.state.signalled.store(true, Ordering::Release);
+                        entry.state.signal.notify(core::usize::MAX);
+
+                        // Poll the future again.
+                        break;
+                    }
+                    index if index == count => {
                         // We have new window messages. Drain the queue again.
                         continue;
                     }
@@ -118,57 +397,210 @@ impl Reactor {
         }
     }
 
+    /// Process the messages waiting in the queue without blocking.
+    ///
+    /// This allows message processing to be interleaved with other work, such as in an existing
+    /// game loop. Futures are not polled.
+    pub fn pump(&mut self) -> Result<PumpStatus, Error> {
+        self.check_thread("pump")?;
+        drain_queue()
+    }
+
     /// Continuously run this reactor until it is shut down.
-    pub fn run(self) -> Result<(), Error> {
-        self.block_on(future::pending::<Infallible>())
+    ///
+    /// Returns the exit code passed to [`Client::quit_with_code`], which is zero if the
+    /// application quit for another reason.
+    pub fn run(&self) -> Result<i32, Error> {
+        self.block_on_until_quit(future::pending::<Infallible>(), "run")
             .map(|t| match t {
-                None => {}
-                Some(inf) => match inf {},
+                Err(code) => code,
+                Ok(inf) => match inf {},
             })
     }
 
-    /// Drains the message queue for the current thread.
+    /// Run this reactor, calling `frame` at the given number of frames per second.
     ///
-    /// Returns the number of messages processed.
+    /// Messages are processed in between frames. This returns once `frame` returns
+    /// [`ControlFlow::Break`] or the application quits.
     ///
-    /// Since we can't be moved once we start processing messages, we must be pinned to this thread.
-    fn drain_queue(self: Pin<&mut Self>) -> Result<DrainStatus, Error> {
-        let mut status = DrainStatus {
-            messages: 0,
-            quit: false,
-        };
-        let mut msg_buffer = MaybeUninit::<MSG>::uninit();
+    /// # Panics
+    ///
+    /// Panics if `fps` is zero.
+    pub fn run_at_fps(
+        &self,
+        fps: u32,
+        mut frame: impl FnMut() -> ControlFlow,
+    ) -> Result<(), Error> {
+        assert_ne!(fps, 0, "cannot run at zero frames per second");
+        self.check_thread("run_at_fps")?;
+
+        // The timer period is in milliseconds, so this is as precise as we can get.
+        let period = (1000 / fps).max(1);
+        let timer = Timer::new(period)?;
 
         loop {
-            // Peek at the next message.
-            let has_message = unsafe { PeekMessageA(msg_buffer.as_mut_ptr(), 0, 0, 0, PM_REMOVE) };
-
-            // If there's no message, we're done.
-            if has_message <= 0 {
-                break;
+            // Drain all messages from the queue.
+            if drain_queue()?.quit {
+                return Ok(());
             }
 
-            // The message is valid, so we can read it.
-            let msg = unsafe { &*msg_buffer.as_ptr() };
-            status.messages += 1;
+            // Wait for either a new message or the next frame.
+            let result =
+                unsafe { MsgWaitForMultipleObjectsEx(1, &timer.handle, INFINITE, QS_ALLINPUT, 0) };
 
-            if msg.message == WM_QUIT {
-                // If this is a quit message, quit.
-                status.quit = true;
-                break;
+            match result {
+                0 => {
+                    // It's time for the next frame.
+                    if let ControlFlow::Break = frame() {
+                        return Ok(());
+                    }
+                }
+                1 => {
+                    // We have new window messages. Drain the queue again.
+                    continue;
+                }
+                WAIT_FAILED => {
+                    // We failed to wait for the timer.
+                    return Err(Error::last_error("MsgWaitForMultipleObjectsEx"));
+                }
+                other => {
+                    tracing::warn!("Unexpected MsgWaitForMultipleObjectsEx result: {:x}", other)
+                }
             }
+        }
+    }
+}
 
-            // Process the message.
-            unsafe {
-                TranslateMessage(msg);
-                DispatchMessageA(msg);
-            }
+/// Remove the handles whose tokens were dropped.
+fn prune_cancelled(handles: &mut Vec<WaitEntry>) {
+    handles.retain(|entry| !entry.state.cancelled.load(Ordering::Acquire));
+}
+
+/// Whether a frame loop should keep running.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ControlFlow {
+    /// Keep running.
+    Continue,
+
+    /// Stop running.
+    Break,
+}
+
+/// Drains the message queue for the current thread.
+///
+/// Returns the number of messages processed and whether a quit message was seen.
+pub(crate) fn drain_queue() -> Result<PumpStatus, Error> {
+    let mut status = PumpStatus {
+        messages: 0,
+        quit: false,
+        exit_code: 0,
+    };
+    let mut msg_buffer = MaybeUninit::<MSG>::uninit();
+
+    loop {
+        // Peek at the next message.
+        let has_message = unsafe { PeekMessageW(msg_buffer.as_mut_ptr(), 0, 0, 0, PM_REMOVE) };
+
+        // If there's no message, we're done.
+        if has_message <= 0 {
+            break;
+        }
+
+        // The message is valid, so we can read it.
+        let msg = unsafe { &*msg_buffer.as_ptr() };
+        status.messages += 1;
 
-            // Indicate to listeners that we have processed a message.
-            signal_new_message();
+        if msg.message == WM_QUIT {
+            // If this is a quit message, quit with the code passed to PostQuitMessage.
+            status.quit = true;
+            status.exit_code = msg.wParam as i32;
+            break;
         }
 
-        Ok(status)
+        unsafe { dispatch_message(msg) };
+    }
+
+    Ok(status)
+}
+
+/// Translate and dispatch a message taken from the queue.
+///
+/// # Safety
+///
+/// The message must have been retrieved by `GetMessageW` or `PeekMessageW`.
+unsafe fn dispatch_message(msg: &MSG) {
+    // If the message is a keyboard accelerator, it's already been dispatched.
+    if !crate::accelerator::translate_accelerator(msg) {
+        // Process the message.
+        TranslateMessage(msg);
+        DispatchMessageW(msg);
+
+        // If a window procedure panicked, propagate it.
+        propagate_panics();
+    }
+
+    // Indicate to listeners that we have processed a message.
+    signal_new_message();
+}
+
+impl Client {
+    /// Dispatch a message from a message loop that isn't run by this crate.
+    ///
+    /// This is for hosts that already run their own `GetMessage` loop, such as plugins. It does
+    /// everything that [`Reactor`] does with a message: keyboard accelerators are translated,
+    /// panics from the window procedure are propagated and waiting futures are woken up.
+    /// `WM_QUIT` should be handled by the caller instead of being passed here.
+    ///
+    /// # Safety
+    ///
+    /// The message must have been retrieved by `GetMessageW` or `PeekMessageW` on this thread. The
+    /// windows of this crate are Unicode, so character messages from the ANSI functions would be
+    /// misread.
+    pub unsafe fn dispatch_current_message(&self, msg: &MSG) {
+        dispatch_message(msg);
+    }
+}
+
+/// A periodic waitable timer.
+struct Timer {
+    /// The timer handle.
+    handle: HANDLE,
+}
+
+impl Timer {
+    /// Create a timer that is signalled every `period` milliseconds.
+    fn new(period: u32) -> Result<Self, Error> {
+        let handle = unsafe { CreateWaitableTimerW(ptr::null(), 0, ptr::null()) };
+
+        if handle == 0 {
+            return Err(Error::last_error("CreateWaitableTimerW"));
+        }
+
+        let timer = Self { handle };
+
+        // A negative due time is relative, in 100-nanosecond intervals.
+        let due = -(period as i64 * 10_000);
+        let result =
+            unsafe { SetWaitableTimer(timer.handle, &due, period as i32, None, ptr::null(), 0) };
+
+        if result == 0 {
+            Err(Error::last_error("SetWaitableTimer"))
+        } else {
+            Ok(timer)
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        unsafe {
+            if CloseHandle(self.handle) == 0 {
+                tracing::warn!(
+                    "Failed to close the timer handle: {}",
+                    Error::last_error("CloseHandle")
+                )
+            }
+        }
     }
 }
 
@@ -286,12 +718,38 @@ impl Drop for Event {
     }
 }
 
-struct DrainStatus {
+/// The result of processing the messages waiting in the queue.
+#[derive(Debug, Copy, Clone)]
+pub struct PumpStatus {
     /// The number of messages processed.
-    messages: usize,
+    pub(crate) messages: usize,
 
     /// Whether we need to quit.
-    quit: bool,
+    pub(crate) quit: bool,
+
+    /// The exit code of the quit message.
+    pub(crate) exit_code: i32,
+}
+
+impl PumpStatus {
+    /// Get the number of messages that were processed.
+    pub fn messages(&self) -> usize {
+        self.messages
+    }
+
+    /// Whether a quit message was received.
+    pub fn quit_requested(&self) -> bool {
+        self.quit
+    }
+
+    /// The exit code of the quit message, if one was received.
+    pub fn exit_code(&self) -> Option<i32> {
+        if self.quit {
+            Some(self.exit_code)
+        } else {
+            None
+        }
+    }
 }
 
 #[cfg(test)]
@@ -305,7 +763,7 @@ mod tests {
     use std::time::Duration;
 
     use windows_sys::Win32::System::Threading::WaitForSingleObject;
-    use windows_sys::Win32::UI::WindowsAndMessaging::PostQuitMessage;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{PostMessageA, PostQuitMessage, WM_APP};
 
     #[test]
     fn test_event() {
@@ -376,6 +834,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_panic_queue() {
+        // Queue panics as if they came from two different windows.
+        push_panic(Box::new("first"));
+        push_panic(Box::new("second"));
+
+        // The first panic should be resumed, and the rest discarded.
+        let panic = std::panic::catch_unwind(propagate_panics).expect_err("to resume a panic");
+        assert_eq!(panic.downcast_ref::<&str>(), Some(&"first"));
+
+        // The queue should now be empty.
+        propagate_panics();
+    }
+
     #[test]
     fn test_reactor() {
         let reactor = || Reactor::new().expect("to create a new reactor");
@@ -412,4 +884,269 @@ mod tests {
             "timer future should return None on quit"
         );
     }
+
+    #[test]
+    fn test_pump() {
+        let mut reactor = Reactor::new().expect("to create a new reactor");
+
+        // Nothing is waiting, so this should return immediately.
+        let status = reactor.pump().expect("to pump messages");
+        assert!(!status.quit_requested());
+
+        // Post a message and it should be processed.
+        assert_ne!(unsafe { PostMessageA(0, WM_APP, 0, 0) }, 0);
+        let status = reactor.pump().expect("to pump messages");
+        assert!(status.messages() >= 1);
+        assert!(!status.quit_requested());
+        assert_eq!(status.exit_code(), None);
+    }
+
+    #[test]
+    fn test_quit_with_code() {
+        let client = crate::Client::new();
+
+        client.quit_with_code(3);
+        let code = Reactor::new()
+            .expect("to create a new reactor")
+            .run()
+            .expect("to run without errors");
+        assert_eq!(code, 3);
+
+        client.quit_with_code(4);
+        let status = Reactor::new()
+            .expect("to create a new reactor")
+            .pump()
+            .expect("to pump messages");
+        assert_eq!(status.exit_code(), Some(4));
+    }
+
+    #[test]
+    fn test_spawn() {
+        let client = crate::Client::new();
+        let reactor = Reactor::new().expect("to create a new reactor");
+        let flag = std::rc::Rc::new(std::cell::Cell::new(false));
+
+        let task_flag = flag.clone();
+        reactor
+            .spawn(async move {
+                async_io::Timer::after(Duration::from_millis(10)).await;
+                task_flag.set(true);
+                client.quit();
+            })
+            .expect("to spawn a task");
+
+        reactor.run().expect("to run without errors");
+        assert!(flag.get());
+    }
+
+    #[test]
+    fn test_dispatch_current_message() {
+        use crate::cstr::CString;
+        use crate::event::Event as WindowEvent;
+        use crate::window::{AsWindow, ExtendedStyle, WindowStyle};
+
+        use blood_geometry::{Point, Rect, Size};
+        use windows_sys::Win32::Foundation::POINT;
+        use windows_sys::Win32::UI::WindowsAndMessaging::WM_HOTKEY;
+
+        let client = crate::Client::new();
+        let class_name = CString::new("test_dispatch_current_message").unwrap();
+        let hotkey = std::cell::Cell::new(None);
+        let class = client
+            .create_class(&class_name)
+            .build(|_, &(), _, ev| {
+                if let WindowEvent::HotKey(id) = ev {
+                    hotkey.set(Some(id));
+                }
+            })
+            .expect("to create a window class");
+
+        let window = client
+            .create_window(
+                &class,
+                &class_name,
+                None,
+                None,
+                WindowStyle::empty(),
+                ExtendedStyle::empty(),
+                Rect::new(Point::new(0, 0), Size::new(10, 10)),
+                (),
+            )
+            .expect("to create a window");
+
+        let msg = MSG {
+            hwnd: window.as_window().handle(),
+            message: WM_HOTKEY,
+            wParam: 3,
+            lParam: 0,
+            time: 0,
+            pt: POINT { x: 0, y: 0 },
+        };
+        unsafe { client.dispatch_current_message(&msg) };
+        assert_eq!(hotkey.get(), Some(3));
+    }
+
+    #[test]
+    fn test_add_handle() {
+        let reactor = Reactor::new().expect("to create a new reactor");
+
+        // A manual-reset event, signalled from another thread.
+        let event = unsafe { CreateEventW(ptr::null(), 1, 0, ptr::null()) };
+        assert_ne!(event, 0);
+
+        let token = reactor.add_handle(event).expect("to add handle");
+        assert!(!token.is_signalled());
+
+        let thread = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            assert_ne!(unsafe { SetEvent(event) }, 0);
+        });
+
+        // The reactor should wake up once the event is signalled.
+        assert!(
+            reactor
+                .block_on(token.wait())
+                .expect("to block on handle")
+                .is_some(),
+            "handle future should return value"
+        );
+        assert!(token.is_signalled());
+
+        thread.join().unwrap();
+        unsafe {
+            CloseHandle(event);
+        }
+    }
+
+    #[test]
+    fn test_add_handle_while_running() {
+        let reactor = Reactor::new().expect("to create a new reactor");
+
+        let event = unsafe { CreateEventW(ptr::null(), 1, 0, ptr::null()) };
+        assert_ne!(event, 0);
+
+        let thread = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            assert_ne!(unsafe { SetEvent(event) }, 0);
+        });
+
+        // The reactor is borrowed, so the handle can be added from the running future.
+        let signalled = reactor
+            .block_on(async {
+                let token = reactor.add_handle(event).expect("to add handle");
+                token.wait().await;
+                token.is_signalled()
+            })
+            .expect("to block on handle");
+        assert_eq!(signalled, Some(true));
+
+        thread.join().unwrap();
+        unsafe {
+            CloseHandle(event);
+        }
+    }
+
+    #[test]
+    fn test_add_too_many_handles() {
+        let reactor = Reactor::new().expect("to create a new reactor");
+        let event = Event::new().expect("to create event");
+
+        let tokens = (0..MAX_HANDLES)
+            .map(|_| reactor.add_handle(event.handle()).expect("to add handle"))
+            .collect::<Vec<_>>();
+        assert_eq!(tokens.len(), MAX_HANDLES);
+
+        let err = reactor
+            .add_handle(event.handle())
+            .err()
+            .expect("to fail past the limit");
+        assert_eq!(err.kind(), crate::ErrorKind::InvalidParameter);
+
+        // Dropping the tokens unregisters their handles.
+        drop(tokens);
+        reactor
+            .add_handle(event.handle())
+            .expect("to add handle after the tokens are dropped");
+        assert_eq!(reactor.handles.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_wrong_thread() {
+        use crate::cstr::CString;
+        use crate::window::{ExtendedStyle, WindowStyle};
+
+        use blood_geometry::{Point, Rect, Size};
+        use std::sync::mpsc;
+
+        let (reactor_tx, reactor_rx) = mpsc::channel();
+        let (done_tx, done_rx) = mpsc::channel::<()>();
+
+        // Create a window and a reactor on one thread...
+        let owner = std::thread::spawn(move || {
+            let client = crate::Client::new();
+            let class_name = CString::new("test_wrong_thread").unwrap();
+            let class = client
+                .create_class(&class_name)
+                .build(|_, &(), _, _| {})
+                .expect("to create a window class");
+
+            let window = client
+                .create_window(
+                    &class,
+                    &class_name,
+                    None,
+                    None,
+                    WindowStyle::empty(),
+                    ExtendedStyle::empty(),
+                    Rect::new(Point::new(0, 0), Size::new(10, 10)),
+                    (),
+                )
+                .expect("to create a window");
+
+            reactor_tx
+                .send(Reactor::new().expect("to create a new reactor"))
+                .unwrap();
+
+            // Keep the window alive until the other thread is done.
+            done_rx.recv().unwrap();
+            drop(window);
+        });
+
+        // ...then try to run the reactor on another.
+        let mut reactor = reactor_rx.recv().unwrap();
+        let kind = crate::ErrorKind::Other(ERROR_INVALID_THREAD_ID);
+
+        let err = reactor
+            .block_on(future::ready(()))
+            .expect_err("to fail on another thread");
+        assert_eq!(err.kind(), kind);
+
+        let err = reactor.run().expect_err("to fail on another thread");
+        assert_eq!(err.kind(), kind);
+
+        let err = reactor.pump().expect_err("to fail on another thread");
+        assert_eq!(err.kind(), kind);
+
+        done_tx.send(()).unwrap();
+        owner.join().unwrap();
+    }
+
+    #[test]
+    fn test_run_at_fps() {
+        let mut frames = 0;
+
+        Reactor::new()
+            .expect("to create a new reactor")
+            .run_at_fps(100, || {
+                frames += 1;
+                if frames == 5 {
+                    ControlFlow::Break
+                } else {
+                    ControlFlow::Continue
+                }
+            })
+            .expect("to run without errors");
+
+        assert_eq!(frames, 5);
+    }
 }
@@ -2,11 +2,17 @@
 
 //! Win32 regions.
 
+use crate::geometry::rect_from_win32;
+use crate::Error;
+
+use blood_geometry::Rect;
+
 use core::cell::Cell;
 use core::marker::PhantomData;
+use core::mem::MaybeUninit;
 
-use windows_sys::Win32::Graphics::Gdi::DeleteObject;
-use windows_sys::Win32::Graphics::Gdi::HRGN;
+use windows_sys::Win32::Graphics::Gdi::{CreateRectRgn, DeleteObject, GetRgnBox};
+use windows_sys::Win32::Graphics::Gdi::{HRGN, RGN_ERROR};
 
 /// A Win32 region.
 pub struct Region {
@@ -18,6 +24,38 @@ pub struct Region {
 }
 
 impl Region {
+    /// Create an empty region.
+    pub(crate) fn empty() -> Result<Self, Error> {
+        let handle = unsafe { CreateRectRgn(0, 0, 0, 0) };
+
+        if handle == 0 {
+            Err(Error::last_error("CreateRectRgn"))
+        } else {
+            Ok(Self {
+                handle,
+                thread_safety: PhantomData,
+            })
+        }
+    }
+
+    /// Get the smallest rectangle that contains the region.
+    ///
+    /// The rectangle is empty if the region is empty.
+    pub fn bounds(&self) -> Result<Rect<i32>, Error> {
+        let mut rect = MaybeUninit::uninit();
+        let result = unsafe { GetRgnBox(self.handle, rect.as_mut_ptr()) };
+
+        if result == RGN_ERROR {
+            Err(Error::last_error("GetRgnBox"))
+        } else {
+            Ok(rect_from_win32(unsafe { rect.assume_init() }))
+        }
+    }
+
+    pub(crate) fn handle(&self) -> HRGN {
+        self.handle
+    }
+
     pub(crate) fn into_handle(self) -> HRGN {
         let handle = self.handle;
         core::mem::forget(self);
@@ -1,12 +1,20 @@
 // Boost/Apache2 License
 
+use crate::bitmap::Bitmap;
 use crate::class::{ClassData, ErasedClassData, WindowClass};
 use crate::client::Client;
-use crate::cstr::CStr;
+use crate::cstr::{CStr, CString};
 use crate::dc::{DeviceContext, GetReleaser};
+#[cfg(feature = "dwm")]
+use crate::dwm::Margins;
 use crate::event::Event;
+use crate::geometry::{point_from_win32, point_to_win32, rect_from_win32, rect_to_win32};
+use crate::keyboard::{KeyModifiers, VirtualKey};
 use crate::menu::Menu;
+#[cfg(feature = "std")]
+use crate::message::{FromMessage, WaitFor, WindowTimer};
 use crate::module::current_module;
+use crate::monitor::Monitor;
 use crate::region::Region;
 use crate::{strict, Error};
 
@@ -14,26 +22,57 @@ use blood_geometry::{Point, Rect, Size};
 
 use alloc::collections::VecDeque;
 use alloc::rc::Rc;
+use alloc::vec::Vec;
 
-use core::any::Any;
 use core::cell::{Cell, RefCell};
 use core::convert::Infallible;
 use core::fmt;
+#[cfg(feature = "std")]
+use core::future::Future;
 use core::marker::PhantomData;
-use core::mem::MaybeUninit;
+use core::mem::{self, MaybeUninit};
 use core::num::NonZeroU32;
 use core::ptr;
+use core::time::Duration;
 
-use windows_sys::Win32::Foundation::{HWND, RECT};
+use windows_sys::Win32::Foundation::{
+    ERROR_ACCESS_DENIED, ERROR_INVALID_HANDLE, ERROR_INVALID_PARAMETER, ERROR_INVALID_THREAD_ID,
+    ERROR_NOT_FOUND, HWND, RECT,
+};
 
-use windows_sys::Win32::Graphics::Gdi::{ClientToScreen, InvalidateRect, ScreenToClient};
+use windows_sys::Win32::Graphics::Gdi::{
+    ClientToScreen, GetUpdateRect, InvalidateRect, MonitorFromWindow, RedrawWindow, ScreenToClient,
+    UpdateWindow, MONITOR_DEFAULTTONEAREST, RGN_ERROR,
+};
 use windows_sys::Win32::Graphics::Gdi::{
     DCX_CACHE, DCX_CLIPCHILDREN, DCX_CLIPSIBLINGS, DCX_LOCKWINDOWUPDATE, DCX_PARENTCLIP, DCX_WINDOW,
 };
+use windows_sys::Win32::Graphics::Gdi::{
+    RDW_ALLCHILDREN, RDW_ERASE, RDW_ERASENOW, RDW_FRAME, RDW_INTERNALPAINT, RDW_INVALIDATE,
+    RDW_NOCHILDREN, RDW_NOERASE, RDW_NOFRAME, RDW_NOINTERNALPAINT, RDW_UPDATENOW, RDW_VALIDATE,
+};
+
+use windows_sys::Win32::Foundation::{GetLastError, SetLastError};
+use windows_sys::Win32::Storage::Xps::{PrintWindow, PW_CLIENTONLY};
+use windows_sys::Win32::System::Shutdown::{ShutdownBlockReasonCreate, ShutdownBlockReasonDestroy};
+use windows_sys::Win32::System::Threading::GetCurrentThreadId;
 
 use windows_sys::Win32::UI::WindowsAndMessaging::{
-    CreateWindowExA, DestroyWindow, GetClientRect, GetDesktopWindow, GetWindowLongPtrA,
-    GetWindowRect, SetWindowPos, SetWindowTextA, ShowWindow,
+    CreateWindowExA, DestroyMenu, DestroyWindow, DrawMenuBar, GetClientRect, GetDesktopWindow,
+    GetMenu, GetWindowLongPtrA, GetWindowRect, GetWindowThreadProcessId, PostMessageA,
+    ScrollWindowEx, SendMessageA, SetMenu, SetWindowPos, SetWindowTextA, SetWindowTextW,
+    ShowWindow,
+};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    EnableMenuItem, FlashWindowEx, GetClassNameA, GetSystemMenu,
+};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    GetScrollInfo, SB_CTL, SB_HORZ, SB_VERT, SCROLLBAR_CONSTANTS, SCROLLINFO, SIF_ALL, SIF_PAGE,
+    SIF_POS, SIF_RANGE,
+};
+use windows_sys::Win32::UI::WindowsAndMessaging::{IsWindow, IsWindowVisible};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    FLASHWINFO, FLASHW_ALL, FLASHW_CAPTION, FLASHW_TIMER, FLASHW_TIMERNOFG, FLASHW_TRAY,
 };
 use windows_sys::Win32::UI::WindowsAndMessaging::{
     GWLP_USERDATA, HWND_BOTTOM, HWND_NOTOPMOST, HWND_TOP, HWND_TOPMOST, SWP_DEFERERASE,
@@ -52,6 +91,26 @@ use windows_sys::Win32::UI::WindowsAndMessaging::{
     WS_MINIMIZE, WS_MINIMIZEBOX, WS_OVERLAPPED, WS_OVERLAPPEDWINDOW, WS_POPUP, WS_POPUPWINDOW,
     WS_SIZEBOX, WS_TABSTOP, WS_THICKFRAME, WS_VISIBLE, WS_VSCROLL,
 };
+use windows_sys::Win32::UI::WindowsAndMessaging::{MF_BYCOMMAND, MF_ENABLED, MF_GRAYED, SC_CLOSE};
+use windows_sys::Win32::UI::WindowsAndMessaging::{PW_RENDERFULLCONTENT, WM_SETREDRAW};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    SW_ERASE, SW_INVALIDATE, SW_SCROLLCHILDREN, SW_SMOOTHSCROLL,
+};
+
+use windows_sys::Win32::UI::Controls::{SetScrollInfo, HOVER_DEFAULT};
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+    RegisterHotKey, TrackMouseEvent, UnregisterHotKey, TME_CANCEL, TME_HOVER, TME_LEAVE,
+    TRACKMOUSEEVENT,
+};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    AdjustWindowRectEx, BeginDeferWindowPos, DeferWindowPos, EndDeferWindowPos,
+};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    GetLayeredWindowAttributes, SetLayeredWindowAttributes, LWA_ALPHA,
+};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    SetWindowLongPtrA, GWL_EXSTYLE, GWL_STYLE, WINDOW_LONG_PTR_INDEX,
+};
 
 impl Client {
     /// Get the top-level window.
@@ -60,6 +119,11 @@ impl Client {
     }
 
     /// Create a new window.
+    ///
+    /// A window with [`ExtendedStyle::LAYERED`] isn't drawn at all until its layered attributes
+    /// are set with [`set_opacity`](AsWindow::set_opacity) or its contents are supplied with
+    /// `UpdateLayeredWindow`. This leaves that to the caller, since the two can't be mixed;
+    /// [`window_builder`](Client::window_builder) sets the opacity right away.
     pub fn create_window<'a, T>(
         &self,
         class: &WindowClass<'a, T>,
@@ -71,9 +135,22 @@ impl Client {
         rectangle: Rect<i32>,
         window_data: T,
     ) -> Result<Window<'a, T>, Error> {
-        // Box the window data to pass it in.
-        let window_data = Box::into_raw(Box::new(window_data));
-        assert!(!window_data.is_null());
+        // Win32 allows a parent on another thread by attaching the input queues of both threads,
+        // but then each thread can block on the other. This crate rejects that as a policy. An
+        // invalid parent is still reported by CreateWindowEx.
+        if let Some(Ok(owner)) = parent.map(|p| p.thread_id()) {
+            if owner != unsafe { GetCurrentThreadId() } {
+                return Err(Error::custom(
+                    ERROR_INVALID_THREAD_ID,
+                    "CreateWindowEx",
+                    "The parent window belongs to another thread.",
+                ));
+            }
+        }
+
+        // Box the window data to pass it in. The window procedure takes it out of this slot once
+        // the window data has been installed in WM_NCCREATE.
+        let mut window_data = Some(Box::new(window_data));
 
         // Create the window.
         let hwnd = unsafe {
@@ -89,23 +166,18 @@ impl Client {
                 parent.map_or(0, |p| p.hwnd),
                 menu.map_or(0, |m| m.into_handle()),
                 current_module(),
-                window_data as *mut _ as *const _,
+                &mut window_data as *mut Option<Box<T>> as *const _,
             )
         };
 
         // Check for errors.
+        //
+        // If WM_NCCREATE took the window data, then WM_NCDESTROY has already freed it and undone
+        // the window count. Otherwise, it's dropped along with the slot.
         if hwnd == 0 {
-            // Free the window data.
-            unsafe {
-                drop(Box::from_raw(window_data));
-            }
-
             return Err(Error::last_error("CreateWindowEx"));
         }
 
-        // Bump the window count.
-        self.increment_window_count();
-
         // Return the window.
         let window = Window {
             hwnd,
@@ -115,7 +187,189 @@ impl Client {
         };
 
         // If a panic happened during window creation, we need to propagate it.
-        window.as_window().propagate_panic();
+        crate::reactor::propagate_panics();
+
+        Ok(window)
+    }
+
+    /// Start building a window with extra setup that [`create_window`](Client::create_window)
+    /// doesn't do.
+    pub fn window_builder<'p>(&self, rectangle: Rect<i32>) -> WindowBuilder<'p> {
+        WindowBuilder {
+            client: self.clone(),
+            parent: None,
+            style: WindowStyle::empty(),
+            extended_style: ExtendedStyle::empty(),
+            rectangle,
+            initial_opacity: None,
+        }
+    }
+
+    /// Create a new window without showing it, even if `style` includes
+    /// [`WindowStyle::VISIBLE`].
+    ///
+    /// This allows the window to be configured before it is shown with
+    /// [`show`](AsWindow::show). Unless this is called from inside an event handler,
+    /// [`Event::Created`] is handled before this returns, so configuration done in the handler
+    /// also happens before the window is shown or painted.
+    pub fn create_window_hidden<'a, T>(
+        &self,
+        class: &WindowClass<'a, T>,
+        title: &'a CStr,
+        menu: Option<Menu>,
+        parent: Option<BorrowedWindow<'_>>,
+        style: WindowStyle,
+        extended_style: ExtendedStyle,
+        rectangle: Rect<i32>,
+        window_data: T,
+    ) -> Result<Window<'a, T>, Error> {
+        self.create_window(
+            class,
+            title,
+            menu,
+            parent,
+            style - WindowStyle::VISIBLE,
+            extended_style,
+            rectangle,
+            window_data,
+        )
+    }
+
+    /// Get the window rectangle needed for a window with the given client rectangle.
+    ///
+    /// The result accounts for the borders, caption and menu bar implied by the styles, and can
+    /// be passed to [`create_window`](Client::create_window). Menu bars that wrap onto several
+    /// lines aren't accounted for.
+    pub fn adjust_window_rect(
+        &self,
+        client: Rect<i32>,
+        style: WindowStyle,
+        extended_style: ExtendedStyle,
+        has_menu: bool,
+    ) -> Result<Rect<i32>, Error> {
+        let mut rect = rect_to_win32(client);
+        let result = unsafe {
+            AdjustWindowRectEx(
+                &mut rect,
+                style.bits(),
+                has_menu.into(),
+                extended_style.bits(),
+            )
+        };
+
+        if result == 0 {
+            Err(Error::last_error("AdjustWindowRectEx"))
+        } else {
+            Ok(rect_from_win32(rect))
+        }
+    }
+
+    /// Start moving several windows at once.
+    ///
+    /// `count` is the number of windows that will be moved; the batch grows if more are added.
+    /// The windows are moved together once the batch is committed, which avoids the flicker of
+    /// moving them one at a time.
+    pub fn defer_window_pos(&self, count: usize) -> Result<DeferredPositions, Error> {
+        let handle = unsafe { BeginDeferWindowPos(count.min(i32::MAX as usize) as i32) };
+
+        if handle == 0 {
+            Err(Error::last_error("BeginDeferWindowPos"))
+        } else {
+            Ok(DeferredPositions {
+                handle,
+                _thread_unsafe: PhantomData,
+            })
+        }
+    }
+}
+
+/// A builder for a window, created by [`Client::window_builder`].
+///
+/// Layered windows built with this are given an opacity as soon as they are created, so they
+/// are drawn without a separate call to [`set_opacity`](AsWindow::set_opacity). After that,
+/// `UpdateLayeredWindow` fails until [`ExtendedStyle::LAYERED`] is cleared and set again, so
+/// windows that supply per-pixel alpha should be created with
+/// [`create_window`](Client::create_window) instead.
+pub struct WindowBuilder<'p> {
+    /// The client.
+    client: Client,
+
+    /// The parent window.
+    parent: Option<BorrowedWindow<'p>>,
+
+    /// The window style.
+    style: WindowStyle,
+
+    /// The extended window style.
+    extended_style: ExtendedStyle,
+
+    /// The window rectangle.
+    rectangle: Rect<i32>,
+
+    /// The opacity of a layered window.
+    initial_opacity: Option<u8>,
+}
+
+impl<'p> WindowBuilder<'p> {
+    /// Set the parent window.
+    pub fn parent(&mut self, parent: BorrowedWindow<'p>) -> &mut Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    /// Set the window style.
+    pub fn style(&mut self, style: WindowStyle) -> &mut Self {
+        self.style = style;
+        self
+    }
+
+    /// Set the extended window style.
+    pub fn extended_style(&mut self, extended_style: ExtendedStyle) -> &mut Self {
+        self.extended_style = extended_style;
+        self
+    }
+
+    /// Set the opacity of a layered window, from `0` for invisible to `255` for opaque.
+    ///
+    /// Layered windows default to opaque. This requires [`ExtendedStyle::LAYERED`].
+    pub fn initial_opacity(&mut self, opacity: u8) -> &mut Self {
+        self.initial_opacity = Some(opacity);
+        self
+    }
+
+    /// Create the window.
+    ///
+    /// A menu can be attached afterwards with [`set_menu`](AsWindow::set_menu).
+    pub fn build<'a, T>(
+        &self,
+        class: &WindowClass<'a, T>,
+        title: &'a CStr,
+        window_data: T,
+    ) -> Result<Window<'a, T>, Error> {
+        let layered = self.extended_style.contains(ExtendedStyle::LAYERED);
+
+        if self.initial_opacity.is_some() && !layered {
+            return Err(Error::custom(
+                ERROR_INVALID_PARAMETER,
+                "WindowBuilder::build",
+                "An initial opacity requires ExtendedStyle::LAYERED.",
+            ));
+        }
+
+        let window = self.client.create_window(
+            class,
+            title,
+            None,
+            self.parent,
+            self.style,
+            self.extended_style,
+            self.rectangle,
+            window_data,
+        )?;
+
+        if layered {
+            window.set_opacity(self.initial_opacity.unwrap_or(u8::MAX))?;
+        }
 
         Ok(window)
     }
@@ -150,22 +404,104 @@ impl<T: fmt::Debug> fmt::Debug for Window<'_, T> {
             }
         }
 
-        // Get a pointer to the user data.
-        let index = unsafe { GetWindowLongPtrA(self.hwnd, GWLP_USERDATA) };
-        let user_data = strict::reconstitute(index).cast::<T>();
+        let mut s = f.debug_struct("Window");
+        s.field("hwnd", &HexDebug(self.hwnd));
 
-        f.debug_struct("Window")
-            .field("hwnd", &HexDebug(self.hwnd))
-            .field("user_data", unsafe { &*user_data })
-            .finish()
+        // SAFETY: The window was created with user data of type `T`.
+        unsafe {
+            self.as_window()
+                .with_user_data(|user_data: &T| s.field("user_data", user_data));
+        }
+
+        s.finish()
+    }
+}
+
+impl<'a, T> Window<'a, T> {
+    /// Destroy the window.
+    ///
+    /// `DestroyWindow` runs the handler for the messages sent while the window is torn down
+    /// before this returns, and the window data is freed once they have been processed. Panics
+    /// from the handler are propagated. Dropping the window does the same thing, but only logs
+    /// failures, and does nothing if the window was already destroyed along with its parent.
+    ///
+    /// This fails if called from a thread other than the one that created the window.
+    pub fn destroy(self) -> Result<(), Error> {
+        let this = mem::ManuallyDrop::new(self);
+        let result = this.destroy_handle(false);
+
+        // The handler may have panicked while the window was being destroyed.
+        crate::reactor::propagate_panics();
+
+        result
+    }
+
+    /// Give up ownership of the window without destroying it, and return its handle.
+    ///
+    /// The window can be reclaimed with [`Window::from_raw`], or destroyed with `DestroyWindow`.
+    pub fn into_raw(self) -> HWND {
+        let hwnd = self.hwnd;
+        mem::forget(self);
+        hwnd
+    }
+
+    /// Destroy the underlying window handle.
+    ///
+    /// If `gone_ok` is true, a window that has already been destroyed isn't an error.
+    fn destroy_handle(&self, gone_ok: bool) -> Result<(), Error> {
+        // Only the thread that created the window can destroy it. Report this clearly, since the
+        // window and its data are leaked otherwise.
+        let owner = unsafe { GetWindowThreadProcessId(self.hwnd, ptr::null_mut()) };
+
+        // The window may already be gone, for instance if it was destroyed through its handle or
+        // it was a child of a window that was destroyed first.
+        if owner == 0 {
+            return if gone_ok {
+                Ok(())
+            } else {
+                Err(Error::last_error("GetWindowThreadProcessId"))
+            };
+        }
+
+        if owner != unsafe { GetCurrentThreadId() } {
+            return Err(Error::custom(
+                ERROR_ACCESS_DENIED,
+                "DestroyWindow",
+                "The window can only be destroyed by the thread that created it.",
+            ));
+        }
+
+        // Destroy the window, and the window proc will take care of the rest.
+        if unsafe { DestroyWindow(self.hwnd) } == 0 {
+            Err(Error::last_error("DestroyWindow"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<T> Window<'static, T> {
+    /// Take ownership of a window handle returned by [`Window::into_raw`].
+    ///
+    /// # Safety
+    ///
+    /// `hwnd` must have been created by this crate on the current thread with user data of type
+    /// `T`, and must not be owned by another `Window`. Its window class must stay registered
+    /// until the window is destroyed.
+    pub unsafe fn from_raw(hwnd: HWND) -> Self {
+        Self {
+            hwnd,
+            _window_class: PhantomData,
+            _window_data: PhantomData,
+            _thread_unsafe: PhantomData,
+        }
     }
 }
 
 impl<'a, T> Drop for Window<'a, T> {
     fn drop(&mut self) {
-        // Destroy the window, and the window proc will take care of the rest.
-        unsafe {
-            DestroyWindow(self.hwnd);
+        if let Err(err) = self.destroy_handle(true) {
+            tracing::error!("Failed to destroy window: {}", err);
         }
     }
 }
@@ -197,16 +533,76 @@ impl<'a> BorrowedWindow<'a> {
         self.hwnd
     }
 
-    /// Propagate a panic, if one exists.
-    fn propagate_panic(&self) {
-        // Get the window data.
-        let index = unsafe { GetWindowLongPtrA(self.hwnd, GWLP_USERDATA) };
-        let user_data = strict::reconstitute(index).cast::<fn(*const ())>();
+    /// Whether the handle still refers to a window.
+    ///
+    /// Windows can be destroyed by other code while they are borrowed. The system may reuse the
+    /// handle of a destroyed window for a new one, so this can't tell those apart.
+    pub fn is_valid(&self) -> bool {
+        unsafe { IsWindow(self.hwnd) != 0 }
+    }
 
-        // Call the panic handler.
-        unsafe {
-            (*user_data)(user_data.cast());
+    /// Get the name of the window's class.
+    ///
+    /// This also works for windows that weren't created by this crate, such as system dialogs.
+    pub fn class_name(&self) -> Result<CString, Error> {
+        // Class names are at most 256 characters, but grow the buffer if a name fills it anyway,
+        // since GetClassNameA silently truncates.
+        let mut buffer = alloc::vec![0u8; 257];
+
+        loop {
+            let len = unsafe { GetClassNameA(self.hwnd, buffer.as_mut_ptr(), buffer.len() as i32) };
+
+            if len == 0 {
+                return Err(Error::last_error("GetClassNameA"));
+            }
+
+            let len = len as usize;
+            if len + 1 < buffer.len() {
+                buffer.truncate(len);
+                return Ok(CString::new(buffer).expect("class names can't contain nul bytes"));
+            }
+
+            let new_len = buffer.len() * 2;
+            buffer.resize(new_len, 0);
+        }
+    }
+
+    /// Run `f` with the user data that the window was created with.
+    ///
+    /// Returns `None` if the window's user data has already been released, for instance because
+    /// it is being destroyed.
+    ///
+    /// # Safety
+    ///
+    /// The window must have been created by this crate, with user data of type `T`.
+    pub unsafe fn with_user_data<T, R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        let ptr = GetWindowLongPtrA(self.hwnd, GWLP_USERDATA);
+
+        if ptr == 0 {
+            return None;
         }
+
+        let window_data = &*(strict::reconstitute(ptr) as *const WindowData<'_, T>);
+        Some(f(&window_data.user_data))
+    }
+
+    /// Wait for the next message of type `M` to be delivered to this window.
+    ///
+    /// The returned future starts listening immediately, rather than when it is first polled.
+    /// It only resolves while the window's messages are being processed, for instance by a
+    /// [`Reactor`](crate::reactor::Reactor), and never resolves if the window is destroyed first.
+    #[cfg(feature = "std")]
+    pub fn wait_for<M: FromMessage>(&self) -> impl Future<Output = M> {
+        WaitFor::new(self.hwnd)
+    }
+
+    /// Start a timer that elapses every `interval`, as a stream of ticks.
+    ///
+    /// The interval is rounded down to milliseconds, and the system raises it to at least 10
+    /// milliseconds. The timer is killed once the returned [`WindowTimer`] is dropped.
+    #[cfg(feature = "std")]
+    pub fn timer(&self, interval: Duration) -> Result<WindowTimer, Error> {
+        WindowTimer::new(self.hwnd, interval)
     }
 }
 
@@ -238,6 +634,37 @@ pub trait AsWindow {
         }
     }
 
+    /// Whether the window and its ancestors have the [`WindowStyle::VISIBLE`] style.
+    ///
+    /// The window may still be covered by other windows.
+    fn is_visible(&self) -> bool {
+        unsafe { IsWindowVisible(self.as_window().hwnd) != 0 }
+    }
+
+    /// Get the IDs of the thread that created the window and of the process it belongs to.
+    fn thread_process_ids(&self) -> Result<(u32, u32), Error> {
+        let mut process_id = 0;
+        let thread_id = unsafe { GetWindowThreadProcessId(self.as_window().hwnd, &mut process_id) };
+
+        if thread_id == 0 {
+            Err(Error::last_error("GetWindowThreadProcessId"))
+        } else {
+            Ok((thread_id, process_id))
+        }
+    }
+
+    /// Get the ID of the thread that created the window.
+    ///
+    /// Only this thread receives the window's messages.
+    fn thread_id(&self) -> Result<u32, Error> {
+        self.thread_process_ids().map(|(thread_id, _)| thread_id)
+    }
+
+    /// Get the ID of the process that the window belongs to.
+    fn process_id(&self) -> Result<u32, Error> {
+        self.thread_process_ids().map(|(_, process_id)| process_id)
+    }
+
     /// Set the title of the window.
     fn set_title(&self, title: &CStr) -> Result<(), Error> {
         let result = unsafe { SetWindowTextA(self.as_window().hwnd, title.as_ptr().cast()) };
@@ -249,134 +676,521 @@ pub trait AsWindow {
         }
     }
 
-    /// Get the rectangle for the client area of the window.
-    fn client_rect(&self) -> Result<Rect<i32>, Error> {
-        unsafe {
-            // The blood geometry rectangle and RECT have the same layout.
-            let mut rect = MaybeUninit::<Rect<i32>>::zeroed();
-            let result = GetClientRect(self.as_window().hwnd, &mut rect as *mut _ as *mut _);
-
-            // Check for errors.
-            if result == 0 {
-                Err(Error::last_error("GetClientRect"))
-            } else {
-                Ok(rect.assume_init())
-            }
+    /// Set the title of the window from a Rust string.
+    ///
+    /// Titles that contain nul bytes are rejected with an [`ErrorKind::InvalidParameter`]
+    /// error. Non-ASCII titles are set through the wide-character API. The windows of this crate
+    /// are Unicode, so characters outside of the ANSI code page are kept.
+    ///
+    /// [`ErrorKind::InvalidParameter`]: crate::ErrorKind::InvalidParameter
+    fn set_title_str(&self, title: &str) -> Result<(), Error> {
+        if title.contains('\0') {
+            return Err(Error::custom(
+                ERROR_INVALID_PARAMETER,
+                "set_title_str",
+                "The title contains a nul byte.",
+            ));
         }
-    }
 
-    /// Get the rectangle for the window.
-    fn window_rect(&self) -> Rect<i32> {
-        unsafe {
-            // The blood geometry rectangle and RECT have the same layout.
-            let mut rect = MaybeUninit::<Rect<i32>>::zeroed();
-            GetWindowRect(self.as_window().hwnd, &mut rect as *mut _ as *mut _);
-            rect.assume_init()
+        if title.is_ascii() {
+            let title = CString::new(title).expect("nul bytes were already rejected");
+            return self.set_title(&title);
         }
-    }
 
-    /// Invalidate the window.
-    fn invalidate(&self, rect: Option<Rect<i32>>, erase: bool) -> Result<(), Error> {
-        let result = unsafe {
-            InvalidateRect(
-                self.as_window().hwnd,
-                rect.as_ref()
-                    .map(|r| r as *const _ as *const _)
-                    .unwrap_or(ptr::null()),
-                erase as _,
-            )
-        };
+        let mut wide = title.encode_utf16().collect::<Vec<_>>();
+        wide.push(0);
+
+        let result = unsafe { SetWindowTextW(self.as_window().hwnd, wide.as_ptr()) };
 
         if result == 0 {
-            Err(Error::last_error("InvalidateRect"))
+            Err(Error::last_error("SetWindowTextW"))
         } else {
             Ok(())
         }
     }
 
-    /// Set the window's position.
-    fn set_window_pos(
-        &self,
-        insert_after: Option<InsertAfter<'_>>,
-        position: Option<Point<i32>>,
-        size: Option<Size<i32>>,
-        flags: WindowPosFlags,
-    ) -> Result<(), Error> {
-        let mut flags = flags.bits();
-
-        // Determine the insert after field/flag.
-        let insert_after = match insert_after {
-            Some(InsertAfter::Window(hwnd)) => hwnd.hwnd,
-            Some(InsertAfter::Bottom) => HWND_BOTTOM,
-            Some(InsertAfter::Top) => HWND_TOP,
-            Some(InsertAfter::TopMost) => HWND_TOPMOST,
-            Some(InsertAfter::NoTopMost) => HWND_NOTOPMOST,
-            None => {
-                flags |= SWP_NOZORDER;
-                0
-            }
-        };
+    /// Set the menu of the window, or remove it if `menu` is `None`.
+    ///
+    /// The window takes ownership of the new menu. The previous menu of the window, if any, is
+    /// destroyed.
+    fn set_menu(&self, menu: Option<Menu>) -> Result<(), Error> {
+        let hwnd = self.as_window().hwnd;
+        let old_menu = unsafe { GetMenu(hwnd) };
+
+        // Keep ownership of the new menu until it is attached, so it is destroyed on failure.
+        let result = unsafe { SetMenu(hwnd, menu.as_ref().map_or(0, |m| m.handle())) };
+        if result == 0 {
+            return Err(Error::last_error("SetMenu"));
+        }
 
-        // Determine the position field/flag.
-        let [x, y] = match position {
-            Some(posn) => posn.into(),
-            None => {
-                flags |= SWP_NOMOVE;
-                [0, 0]
-            }
-        };
+        // The window destroys the new menu from now on.
+        if let Some(menu) = menu {
+            menu.into_handle();
+        }
 
-        // Determine the size field/flag.
-        let [width, height] = match size {
-            Some(size) => size.into(),
-            None => {
-                flags |= SWP_NOSIZE;
-                [0, 0]
+        // The old menu is no longer attached to the window, so we have to destroy it.
+        if old_menu != 0 {
+            unsafe {
+                DestroyMenu(old_menu);
             }
-        };
-
-        // Set the window position.
-        let result = unsafe {
-            SetWindowPos(
-                self.as_window().hwnd,
-                insert_after,
-                x,
-                y,
-                width,
-                height,
-                flags,
-            )
-        };
+        }
 
+        // Redraw the menu bar to make the change visible.
+        let result = unsafe { DrawMenuBar(hwnd) };
         if result == 0 {
-            Err(Error::last_error("SetWindowPos"))
+            Err(Error::last_error("DrawMenuBar"))
         } else {
             Ok(())
         }
     }
 
-    /// Convert a point from screen coordinates to client coordinates.
-    fn client_to_screen(&self, mut point: Point<i32>) -> Result<Point<i32>, Error> {
-        let result =
-            unsafe { ClientToScreen(self.as_window().hwnd, &mut point as *mut _ as *mut _) };
+    /// Enable or disable the close button and the Close item of the window's system menu.
+    ///
+    /// Unlike the `NO_CLOSE` class style, this only affects this window and can be changed at any
+    /// time.
+    fn set_close_enabled(&self, enabled: bool) -> Result<(), Error> {
+        let hwnd = self.as_window().hwnd;
+        let menu = unsafe { GetSystemMenu(hwnd, 0) };
+
+        if menu == 0 {
+            return Err(Error::custom(
+                ERROR_NOT_FOUND,
+                "GetSystemMenu",
+                "The window has no system menu.",
+            ));
+        }
+
+        let flags = MF_BYCOMMAND | if enabled { MF_ENABLED } else { MF_GRAYED };
+        let result = unsafe { EnableMenuItem(menu, SC_CLOSE, flags) };
+
+        // EnableMenuItem returns the previous state, or -1 if the item does not exist. It doesn't
+        // set the last error in that case.
+        if result == -1 {
+            return Err(Error::custom(
+                ERROR_NOT_FOUND,
+                "EnableMenuItem",
+                "The system menu has no close item.",
+            ));
+        }
 
+        // Redraw the title bar to update the close button.
+        let result = unsafe { DrawMenuBar(hwnd) };
         if result == 0 {
-            Err(Error::last_error("ClientToScreen"))
+            Err(Error::last_error("DrawMenuBar"))
         } else {
-            Ok(point)
+            Ok(())
         }
     }
 
-    /// Convert a point from client coordinates to screen coordinates.
-    fn screen_to_client(&self, mut point: Point<i32>) -> Result<Point<i32>, Error> {
-        let result =
-            unsafe { ScreenToClient(self.as_window().hwnd, &mut point as *mut _ as *mut _) };
+    /// Get the monitor that the window is on.
+    ///
+    /// If the window spans multiple monitors, this is the one with the largest intersection. If it
+    /// isn't on any monitor, this is the nearest one.
+    fn monitor(&self) -> Monitor {
+        let handle = unsafe { MonitorFromWindow(self.as_window().hwnd, MONITOR_DEFAULTTONEAREST) };
+        Monitor::from_raw(handle)
+    }
 
-        if result == 0 {
-            Err(Error::last_error("ScreenToClient"))
-        } else {
-            Ok(point)
-        }
+    /// Get the DPI of the window.
+    ///
+    /// On systems that don't support per-window DPI, this is always 96.
+    fn dpi(&self) -> u32 {
+        crate::dpi::window_dpi(self.as_window().hwnd)
+    }
+
+    /// Flash the window to get the user's attention.
+    ///
+    /// The window flashes `count` times, or continuously if one of the timer flags is given.
+    /// Passing empty flags stops the window from flashing. Returns whether the window was active
+    /// before the call.
+    fn flash(&self, flags: FlashFlags, count: u32) -> bool {
+        let info = FLASHWINFO {
+            cbSize: mem::size_of::<FLASHWINFO>() as u32,
+            hwnd: self.as_window().hwnd,
+            dwFlags: flags.bits(),
+            uCount: count,
+            dwTimeout: 0,
+        };
+
+        unsafe { FlashWindowEx(&info) != 0 }
+    }
+
+    /// Flash the window until it comes to the foreground.
+    ///
+    /// This is the usual way to tell the user that something happened while the application was
+    /// in the background, like a chat message arriving. `FlashWindowEx` doesn't report failure,
+    /// so there is no error to return.
+    fn start_attention(&self) {
+        self.flash(FlashFlags::ALL | FlashFlags::TIMER_NO_FOREGROUND, 0);
+    }
+
+    /// Stop flashing the window.
+    fn stop_attention(&self) {
+        self.flash(FlashFlags::empty(), 0);
+    }
+
+    /// Tell the user why the application needs to block the session from ending.
+    ///
+    /// This should only be used while the application is doing something that can't be
+    /// interrupted, such as saving data. The reason is shown to the user while they are
+    /// ending the session.
+    fn block_shutdown(&self, reason: &str) -> Result<(), Error> {
+        let mut reason = reason.encode_utf16().collect::<Vec<_>>();
+        reason.push(0);
+
+        let result = unsafe { ShutdownBlockReasonCreate(self.as_window().hwnd, reason.as_ptr()) };
+        if result == 0 {
+            Err(Error::last_error("ShutdownBlockReasonCreate"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Stop blocking the session from ending.
+    fn unblock_shutdown(&self) -> Result<(), Error> {
+        let result = unsafe { ShutdownBlockReasonDestroy(self.as_window().hwnd) };
+        if result == 0 {
+            Err(Error::last_error("ShutdownBlockReasonDestroy"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Get the rectangle for the client area of the window.
+    fn client_rect(&self) -> Result<Rect<i32>, Error> {
+        unsafe {
+            let mut rect = MaybeUninit::<RECT>::zeroed();
+            let result = GetClientRect(self.as_window().hwnd, rect.as_mut_ptr());
+
+            // Check for errors.
+            if result == 0 {
+                Err(Error::last_error("GetClientRect"))
+            } else {
+                Ok(rect_from_win32(rect.assume_init()))
+            }
+        }
+    }
+
+    /// Get the rectangle for the window.
+    fn window_rect(&self) -> Rect<i32> {
+        unsafe {
+            let mut rect = MaybeUninit::<RECT>::zeroed();
+            GetWindowRect(self.as_window().hwnd, rect.as_mut_ptr());
+            rect_from_win32(rect.assume_init())
+        }
+    }
+
+    /// Place a message in the window's message queue and return without waiting for it to be
+    /// processed.
+    ///
+    /// Application-defined messages in the `WM_APP` range are delivered to the handler as
+    /// [`Event::User`](crate::event::Event::User).
+    ///
+    /// # Safety
+    ///
+    /// Some messages interpret their parameters as pointers. The parameters must be valid for
+    /// `msg` by the time the message is processed.
+    unsafe fn post_message(&self, msg: u32, wparam: usize, lparam: isize) -> Result<(), Error> {
+        if PostMessageA(self.as_window().hwnd, msg, wparam, lparam) == 0 {
+            Err(Error::last_error("PostMessageA"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Send a message to the window and wait for it to be processed.
+    ///
+    /// Returns the result of the window procedure, whose meaning depends on the message.
+    ///
+    /// # Safety
+    ///
+    /// Some messages interpret their parameters as pointers. The parameters must be valid for
+    /// `msg`.
+    unsafe fn send_message(&self, msg: u32, wparam: usize, lparam: isize) -> isize {
+        SendMessageA(self.as_window().hwnd, msg, wparam, lparam)
+    }
+
+    /// Allow or prevent changes in the window from being redrawn.
+    ///
+    /// Once redrawing is allowed again, the window needs to be invalidated for the changes to be
+    /// painted. [`suspend_redraw`](AsWindow::suspend_redraw) takes care of this.
+    fn set_redraw(&self, enabled: bool) {
+        unsafe {
+            SendMessageA(self.as_window().hwnd, WM_SETREDRAW, enabled as usize, 0);
+        }
+    }
+
+    /// Run `f` with redrawing disabled, then redraw the window and its children.
+    ///
+    /// This avoids flickering while making many changes to the window, such as moving a lot of
+    /// child windows at once.
+    fn suspend_redraw<R>(&self, f: impl FnOnce() -> R) -> R
+    where
+        Self: Sized,
+    {
+        /// Re-enable redrawing, even if `f` panics.
+        struct ResumeRedraw<'a>(BorrowedWindow<'a>);
+
+        impl Drop for ResumeRedraw<'_> {
+            fn drop(&mut self) {
+                self.0.set_redraw(true);
+
+                let result = unsafe {
+                    RedrawWindow(
+                        self.0.hwnd,
+                        ptr::null(),
+                        0,
+                        RDW_ERASE | RDW_FRAME | RDW_INVALIDATE | RDW_ALLCHILDREN,
+                    )
+                };
+
+                if result == 0 {
+                    tracing::warn!(
+                        "Failed to redraw window: {}",
+                        Error::last_error("RedrawWindow")
+                    );
+                }
+            }
+        }
+
+        self.set_redraw(false);
+        let _resume = ResumeRedraw(self.as_window());
+        f()
+    }
+
+    /// Invalidate the window.
+    fn invalidate(&self, rect: Option<Rect<i32>>, erase: bool) -> Result<(), Error> {
+        let rect = rect.map(rect_to_win32);
+        let result = unsafe {
+            InvalidateRect(
+                self.as_window().hwnd,
+                rect.as_ref().map_or(ptr::null(), |r| r as *const RECT),
+                erase as _,
+            )
+        };
+
+        if result == 0 {
+            Err(Error::last_error("InvalidateRect"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Paint the invalid parts of the window right away, instead of waiting for the message queue
+    /// to be processed.
+    ///
+    /// This does nothing if no part of the window is invalid.
+    fn update(&self) -> Result<(), Error> {
+        if unsafe { UpdateWindow(self.as_window().hwnd) } == 0 {
+            Err(Error::last_error("UpdateWindow"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Get the smallest rectangle that contains the invalid parts of the window.
+    ///
+    /// Returns `None` if nothing needs to be painted. If `erase` is true and the background
+    /// needs to be erased, it is erased before this returns.
+    fn update_rect(&self, erase: bool) -> Result<Option<Rect<i32>>, Error> {
+        unsafe {
+            let mut rect = MaybeUninit::<RECT>::zeroed();
+
+            // An empty update region is only an error if the last error is set.
+            SetLastError(0);
+
+            if GetUpdateRect(self.as_window().hwnd, rect.as_mut_ptr(), erase as _) != 0 {
+                Ok(Some(rect_from_win32(rect.assume_init())))
+            } else if GetLastError() != 0 {
+                Err(Error::last_error("GetUpdateRect"))
+            } else {
+                Ok(None)
+            }
+        }
+    }
+
+    /// Invalidate the whole client area and paint it right away.
+    ///
+    /// This is [`invalidate`](Self::invalidate) with the background erased, followed by
+    /// [`update`](Self::update).
+    fn repaint(&self) -> Result<(), Error> {
+        self.invalidate(None, true)?;
+        self.update()
+    }
+
+    /// Invalidate, validate or repaint part of the window.
+    ///
+    /// If `rect` is `None`, the whole client area is affected.
+    fn redraw(&self, rect: Option<Rect<i32>>, flags: RedrawFlags) -> Result<(), Error> {
+        let rect = rect.map(rect_to_win32);
+        let result = unsafe {
+            RedrawWindow(
+                self.as_window().hwnd,
+                rect.as_ref().map_or(ptr::null(), |r| r as *const RECT),
+                0,
+                flags.bits(),
+            )
+        };
+
+        if result == 0 {
+            Err(Error::last_error("RedrawWindow"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Scroll the contents of the client area by `dx` and `dy` pixels.
+    ///
+    /// Only the part within `scroll_rect` is scrolled, or the whole client area if it is `None`.
+    /// Pixels outside of `clip_rect` are left untouched. Returns the region that was uncovered by
+    /// scrolling and needs to be repainted.
+    fn scroll(
+        &self,
+        dx: i32,
+        dy: i32,
+        scroll_rect: Option<Rect<i32>>,
+        clip_rect: Option<Rect<i32>>,
+        flags: ScrollFlags,
+    ) -> Result<Region, Error> {
+        let update = Region::empty()?;
+        let scroll_rect = scroll_rect.map(rect_to_win32);
+        let clip_rect = clip_rect.map(rect_to_win32);
+
+        let result = unsafe {
+            ScrollWindowEx(
+                self.as_window().hwnd,
+                dx,
+                dy,
+                scroll_rect
+                    .as_ref()
+                    .map_or(ptr::null(), |r| r as *const RECT),
+                clip_rect.as_ref().map_or(ptr::null(), |r| r as *const RECT),
+                update.handle(),
+                ptr::null_mut(),
+                flags.bits(),
+            )
+        };
+
+        if result == RGN_ERROR {
+            Err(Error::last_error("ScrollWindowEx"))
+        } else {
+            Ok(update)
+        }
+    }
+
+    /// Set the range, page size and position of a scroll bar.
+    ///
+    /// `page` is the size of the visible part of the content, which sets the size of the thumb.
+    /// `pos` is clamped to the range.
+    fn set_scroll_info(
+        &self,
+        bar: ScrollBar,
+        min: i32,
+        max: i32,
+        page: u32,
+        pos: i32,
+    ) -> Result<(), Error> {
+        if min > max {
+            return Err(Error::custom(
+                ERROR_INVALID_PARAMETER,
+                "set_scroll_info",
+                "The minimum scroll position is greater than the maximum.",
+            ));
+        }
+
+        let info = SCROLLINFO {
+            cbSize: mem::size_of::<SCROLLINFO>() as u32,
+            fMask: SIF_RANGE | SIF_PAGE | SIF_POS,
+            nMin: min,
+            nMax: max,
+            nPage: page,
+            nPos: pos,
+            nTrackPos: 0,
+        };
+
+        unsafe {
+            SetScrollInfo(self.as_window().hwnd, bar.to_win32(), &info, 1);
+        }
+
+        Ok(())
+    }
+
+    /// Get the range, page size and position of a scroll bar.
+    fn scroll_info(&self, bar: ScrollBar) -> Result<ScrollInfo, Error> {
+        let mut info = MaybeUninit::<SCROLLINFO>::zeroed();
+
+        unsafe {
+            (*info.as_mut_ptr()).cbSize = mem::size_of::<SCROLLINFO>() as u32;
+            (*info.as_mut_ptr()).fMask = SIF_ALL;
+
+            if GetScrollInfo(self.as_window().hwnd, bar.to_win32(), info.as_mut_ptr()) == 0 {
+                return Err(Error::last_error("GetScrollInfo"));
+            }
+
+            let info = info.assume_init();
+            Ok(ScrollInfo {
+                min: info.nMin,
+                max: info.nMax,
+                page: info.nPage,
+                position: info.nPos,
+                track_position: info.nTrackPos,
+            })
+        }
+    }
+
+    /// Set the window's position.
+    fn set_window_pos(
+        &self,
+        insert_after: Option<InsertAfter<'_>>,
+        position: Option<Point<i32>>,
+        size: Option<Size<i32>>,
+        flags: WindowPosFlags,
+    ) -> Result<(), Error> {
+        let (insert_after, [x, y], [width, height], flags) =
+            window_pos_args(insert_after, position, size, flags, "SetWindowPos")?;
+
+        // Set the window position.
+        let result = unsafe {
+            SetWindowPos(
+                self.as_window().hwnd,
+                insert_after,
+                x,
+                y,
+                width,
+                height,
+                flags,
+            )
+        };
+
+        if result == 0 {
+            Err(Error::last_error("SetWindowPos"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Convert a point from screen coordinates to client coordinates.
+    fn client_to_screen(&self, point: Point<i32>) -> Result<Point<i32>, Error> {
+        let mut point = point_to_win32(point);
+        let result = unsafe { ClientToScreen(self.as_window().hwnd, &mut point) };
+
+        if result == 0 {
+            Err(Error::last_error("ClientToScreen"))
+        } else {
+            Ok(point_from_win32(point))
+        }
+    }
+
+    /// Convert a point from client coordinates to screen coordinates.
+    fn screen_to_client(&self, point: Point<i32>) -> Result<Point<i32>, Error> {
+        let mut point = point_to_win32(point);
+        let result = unsafe { ScreenToClient(self.as_window().hwnd, &mut point) };
+
+        if result == 0 {
+            Err(Error::last_error("ScreenToClient"))
+        } else {
+            Ok(point_from_win32(point))
+        }
     }
 
     /// Get a DC for this window.
@@ -387,508 +1201,2825 @@ pub trait AsWindow {
     ) -> Result<DeviceContext<GetReleaser<'_>>, Error> {
         DeviceContext::get_dc(Some(self.as_window()), region, flags)
     }
+
+    /// Copy the pixels of the client area into a bitmap.
+    ///
+    /// Unlike copying from the screen, this also captures parts of the window that are covered
+    /// by other windows or off-screen, as well as content composed by the Desktop Window Manager.
+    fn capture(&self) -> Result<Bitmap, Error> {
+        let hwnd = self.as_window().hwnd;
+        let size = self.client_rect()?.size();
+        let dc = self.get_dc(RegionType::None, GetDcFlags::empty())?;
+
+        let ((), bitmap) = dc.with_back_buffer(size, |buffer| {
+            let flags = PW_CLIENTONLY | PW_RENDERFULLCONTENT;
+
+            if unsafe { PrintWindow(hwnd, buffer.handle(), flags) } == 0 {
+                Err(Error::last_error("PrintWindow"))
+            } else {
+                Ok(())
+            }
+        })?;
+
+        Ok(bitmap)
+    }
+
+    /// Ask the system to report when the cursor leaves or hovers over the window.
+    ///
+    /// This produces [`Event::MouseLeft`] and [`Event::MouseHover`]. Tracking ends after each of
+    /// these events, so it has to be re-armed to receive another one. If `hover_time` is `None`,
+    /// the system's default hover time is used.
+    fn track_mouse(
+        &self,
+        flags: TrackMouseFlags,
+        hover_time: Option<Duration>,
+    ) -> Result<(), Error> {
+        let hover_time = match hover_time {
+            Some(time) => time.as_millis().min(u128::from(HOVER_DEFAULT - 1)) as u32,
+            None => HOVER_DEFAULT,
+        };
+
+        let mut info = TRACKMOUSEEVENT {
+            cbSize: mem::size_of::<TRACKMOUSEEVENT>() as u32,
+            dwFlags: flags.bits(),
+            hwndTrack: self.as_window().hwnd,
+            dwHoverTime: hover_time,
+        };
+
+        if unsafe { TrackMouseEvent(&mut info) } == 0 {
+            Err(Error::last_error("TrackMouseEvent"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Register a system-wide hotkey that produces [`Event::HotKey`] with `id` for this window.
+    ///
+    /// The hotkey is reported even when the window doesn't have the focus. This fails with
+    /// [`ErrorKind::HotKeyAlreadyRegistered`](crate::ErrorKind::HotKeyAlreadyRegistered) if
+    /// another hotkey, possibly in another application, already uses the same keys.
+    fn register_hotkey(
+        &self,
+        id: i32,
+        modifiers: KeyModifiers,
+        key: VirtualKey,
+    ) -> Result<(), Error> {
+        let result = unsafe {
+            RegisterHotKey(
+                self.as_window().hwnd,
+                id,
+                modifiers.to_hotkey_modifiers(),
+                u32::from(key.raw()),
+            )
+        };
+
+        if result == 0 {
+            Err(Error::last_error("RegisterHotKey"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Unregister a hotkey registered with [`register_hotkey`](AsWindow::register_hotkey).
+    fn unregister_hotkey(&self, id: i32) -> Result<(), Error> {
+        if unsafe { UnregisterHotKey(self.as_window().hwnd, id) } == 0 {
+            Err(Error::last_error("UnregisterHotKey"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Extend the window frame into the client area.
+    ///
+    /// The extended area is drawn by the Desktop Window Manager, which is how custom title bars
+    /// get the system backdrop. The client area must be painted black there for the frame to
+    /// show through.
+    #[cfg(feature = "dwm")]
+    fn extend_frame_into_client(&self, margins: Margins) -> Result<(), Error> {
+        crate::dwm::extend_frame_into_client(self.as_window().hwnd, margins)
+    }
+
+    /// Get the window's style.
+    ///
+    /// Bits that [`WindowStyle`] doesn't name, like the styles specific to a class, are kept, so
+    /// the style can be changed and passed back to [`set_style`](AsWindow::set_style).
+    fn style(&self) -> WindowStyle {
+        let style = unsafe { GetWindowLongPtrA(self.as_window().hwnd, GWL_STYLE) };
+        unsafe { WindowStyle::from_bits_unchecked(style as u32) }
+    }
+
+    /// Set the window's style.
+    ///
+    /// Some styles are cached by the system, so changes to the frame only take effect after
+    /// calling [`set_window_pos`](AsWindow::set_window_pos) with
+    /// [`WindowPosFlags::FRAME_CHANGED`].
+    fn set_style(&self, style: WindowStyle) -> Result<(), Error> {
+        set_window_long(
+            self.as_window().hwnd,
+            GWL_STYLE,
+            style.bits() as i32 as isize,
+        )
+    }
+
+    /// Get the window's extended style.
+    ///
+    /// Like [`style`](AsWindow::style), this keeps bits that [`ExtendedStyle`] doesn't name.
+    fn extended_style(&self) -> ExtendedStyle {
+        let style = unsafe { GetWindowLongPtrA(self.as_window().hwnd, GWL_EXSTYLE) };
+        unsafe { ExtendedStyle::from_bits_unchecked(style as u32) }
+    }
+
+    /// Set the window's extended style.
+    ///
+    /// Some styles are cached by the system, so changes to the frame only take effect after
+    /// calling [`set_window_pos`](AsWindow::set_window_pos) with
+    /// [`WindowPosFlags::FRAME_CHANGED`].
+    fn set_extended_style(&self, style: ExtendedStyle) -> Result<(), Error> {
+        set_window_long(
+            self.as_window().hwnd,
+            GWL_EXSTYLE,
+            style.bits() as i32 as isize,
+        )
+    }
+
+    /// Get the opacity of a layered window, from `0` for invisible to `255` for opaque.
+    ///
+    /// This fails if the window doesn't have [`ExtendedStyle::LAYERED`].
+    fn opacity(&self) -> Result<u8, Error> {
+        let mut opacity = 0;
+        let mut flags = 0;
+        let result = unsafe {
+            GetLayeredWindowAttributes(
+                self.as_window().hwnd,
+                ptr::null_mut(),
+                &mut opacity,
+                &mut flags,
+            )
+        };
+
+        if result == 0 {
+            Err(Error::last_error("GetLayeredWindowAttributes"))
+        } else if flags & LWA_ALPHA == 0 {
+            Ok(u8::MAX)
+        } else {
+            Ok(opacity)
+        }
+    }
+
+    /// Set the opacity of a layered window, from `0` for invisible to `255` for opaque.
+    ///
+    /// This fails if the window doesn't have [`ExtendedStyle::LAYERED`].
+    fn set_opacity(&self, opacity: u8) -> Result<(), Error> {
+        let result =
+            unsafe { SetLayeredWindowAttributes(self.as_window().hwnd, 0, opacity, LWA_ALPHA) };
+
+        if result == 0 {
+            Err(Error::last_error("SetLayeredWindowAttributes"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Convert the arguments of `set_window_pos` into the ones taken by `SetWindowPos`.
+///
+/// Passing a value along with the flag that says it is unchanged is rejected, since the system
+/// would silently ignore the value.
+fn window_pos_args(
+    insert_after: Option<InsertAfter<'_>>,
+    position: Option<Point<i32>>,
+    size: Option<Size<i32>>,
+    flags: WindowPosFlags,
+    function: &'static str,
+) -> Result<(HWND, [i32; 2], [i32; 2], u32), Error> {
+    let conflict = if insert_after.is_some() && flags.contains(WindowPosFlags::NO_Z_ORDER) {
+        Some("A window to insert after was passed along with NO_Z_ORDER.")
+    } else if position.is_some() && flags.contains(WindowPosFlags::NO_MOVE) {
+        Some("A position was passed along with NO_MOVE.")
+    } else if size.is_some() && flags.contains(WindowPosFlags::NO_SIZE) {
+        Some("A size was passed along with NO_SIZE.")
+    } else {
+        None
+    };
+
+    if let Some(message) = conflict {
+        return Err(Error::custom(ERROR_INVALID_PARAMETER, function, message));
+    }
+
+    let mut flags = flags.bits();
+
+    // Determine the insert after field/flag.
+    let insert_after = match insert_after {
+        Some(InsertAfter::Window(hwnd)) => hwnd.hwnd,
+        Some(InsertAfter::Bottom) => HWND_BOTTOM,
+        Some(InsertAfter::Top) => HWND_TOP,
+        Some(InsertAfter::TopMost) => HWND_TOPMOST,
+        Some(InsertAfter::NoTopMost) => HWND_NOTOPMOST,
+        None => {
+            flags |= SWP_NOZORDER;
+            0
+        }
+    };
+
+    // Determine the position field/flag.
+    let [x, y] = match position {
+        Some(posn) => posn.into(),
+        None => {
+            flags |= SWP_NOMOVE;
+            [0, 0]
+        }
+    };
+
+    // Determine the size field/flag.
+    let [width, height] = match size {
+        Some(size) => size.into(),
+        None => {
+            flags |= SWP_NOSIZE;
+            [0, 0]
+        }
+    };
+
+    Ok((insert_after, [x, y], [width, height], flags))
+}
+
+/// Set one of the window's attributes with `SetWindowLongPtrA`.
+fn set_window_long(hwnd: HWND, index: WINDOW_LONG_PTR_INDEX, value: isize) -> Result<(), Error> {
+    unsafe {
+        // A previous value of zero is only an error if the last error is set.
+        SetLastError(0);
+
+        if SetWindowLongPtrA(hwnd, index, value) == 0 && GetLastError() != 0 {
+            Err(Error::last_error("SetWindowLongPtrA"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl AsWindow for BorrowedWindow<'_> {
+    fn as_window(&self) -> BorrowedWindow<'_> {
+        unsafe { Self::from_raw_handle(self.hwnd) }
+    }
+}
+
+impl<T> AsWindow for Window<'_, T> {
+    fn as_window(&self) -> BorrowedWindow<'_> {
+        unsafe { BorrowedWindow::from_raw_handle(self.hwnd) }
+    }
+}
+
+#[cfg(feature = "raw-window-handle")]
+unsafe impl raw_window_handle::HasRawWindowHandle for BorrowedWindow<'_> {
+    fn raw_window_handle(&self) -> raw_window_handle::RawWindowHandle {
+        let mut handle = raw_window_handle::Win32WindowHandle::empty();
+        handle.hwnd = strict::invalid(self.hwnd) as *mut _;
+        handle.hinstance = strict::invalid(current_module()) as *mut _;
+
+        raw_window_handle::RawWindowHandle::Win32(handle)
+    }
+}
+
+#[cfg(feature = "raw-window-handle")]
+unsafe impl<T> raw_window_handle::HasRawWindowHandle for Window<'_, T> {
+    fn raw_window_handle(&self) -> raw_window_handle::RawWindowHandle {
+        self.as_window().raw_window_handle()
+    }
+}
+
+#[cfg(feature = "raw-window-handle-06")]
+impl raw_window_handle_06::HasWindowHandle for BorrowedWindow<'_> {
+    fn window_handle(
+        &self,
+    ) -> Result<raw_window_handle_06::WindowHandle<'_>, raw_window_handle_06::HandleError> {
+        use core::num::NonZeroIsize;
+
+        let hwnd =
+            NonZeroIsize::new(self.hwnd).ok_or(raw_window_handle_06::HandleError::Unavailable)?;
+        let mut handle = raw_window_handle_06::Win32WindowHandle::new(hwnd);
+        handle.hinstance = NonZeroIsize::new(current_module());
+
+        // SAFETY: The window stays alive for as long as it is borrowed.
+        Ok(unsafe {
+            raw_window_handle_06::WindowHandle::borrow_raw(
+                raw_window_handle_06::RawWindowHandle::Win32(handle),
+            )
+        })
+    }
+}
+
+#[cfg(feature = "raw-window-handle-06")]
+impl<T> raw_window_handle_06::HasWindowHandle for Window<'_, T> {
+    fn window_handle(
+        &self,
+    ) -> Result<raw_window_handle_06::WindowHandle<'_>, raw_window_handle_06::HandleError> {
+        let handle = self.as_window().window_handle()?.as_raw();
+
+        // SAFETY: The window stays alive for as long as it is borrowed.
+        Ok(unsafe { raw_window_handle_06::WindowHandle::borrow_raw(handle) })
+    }
+}
+
+bitflags::bitflags! {
+    /// Window styles.
+    pub struct WindowStyle : u32 {
+        /// The window has a thin-line border.
+        const BORDER = WS_BORDER;
+
+        /// The window has a title bar.
+        const CAPTION = WS_CAPTION;
+
+        /// The window is a child window.
+        const CHILD = WS_CHILD;
+
+        /// Excludes the area occupied by child windows.
+        const CLIP_CHILDREN = WS_CLIPCHILDREN;
+
+        /// Excludes the area occupied by sibling windows.
+        const CLIP_SIBLINGS = WS_CLIPSIBLINGS;
+
+        /// The window is initially disabled.
+        const DISABLED = WS_DISABLED;
+
+        /// The window has a border of a style typically used with dialog boxes.
+        const DIALOG_FRAME = WS_DLGFRAME;
+
+        /// The window is the first control of a group of controls.
+        const GROUP = WS_GROUP;
+
+        /// The window has a horizontal scroll bar.
+        const H_SCROLL = WS_HSCROLL;
+
+        /// The window is initially maximized.
+        const MAXIMIZE = WS_MAXIMIZE;
+
+        /// The window has a maximize button.
+        const MAXIMIZE_BOX = WS_MAXIMIZEBOX;
+
+        /// The window is initially minimized.
+        const MINIMIZE = WS_MINIMIZE;
+
+        /// The window has a minimize button.
+        const MINIMIZE_BOX = WS_MINIMIZEBOX;
+
+        /// The window is an overlapped window.
+        const OVERLAPPED = WS_OVERLAPPED;
+
+        /// The window is an overlapped window.
+        const OVERLAPPED_WINDOW = WS_OVERLAPPEDWINDOW;
+
+        /// The window is a pop-up window.
+        const POPUP = WS_POPUP;
+
+        /// The window is a pop-up window.
+        const POPUP_WINDOW = WS_POPUPWINDOW;
+
+        /// The window has a sizing border.
+        const SIZE_BOX = WS_SIZEBOX;
+
+        /// The window has a control that receives keyboard focus when the user presses the TAB key.
+        const TAB_STOP = WS_TABSTOP;
+
+        /// The window has a sizing border.
+        const THICK_FRAME = WS_THICKFRAME;
+
+        /// The window is initially visible.
+        const VISIBLE = WS_VISIBLE;
+
+        /// The window has a vertical scroll bar.
+        const V_SCROLL = WS_VSCROLL;
+    }
+}
+
+bitflags::bitflags! {
+    /// Extended window styles.
+    pub struct ExtendedStyle : u32 {
+        /// The window accepts drag-drop files.
+        const ACCEPT_FILES = WS_EX_ACCEPTFILES;
+
+        /// Forces a top-level window onto the taskbar when the window is visible.
+        const APP_WINDOW = WS_EX_APPWINDOW;
+
+        /// The window has a border with a sunken edge.
+        const CLIENT_EDGE = WS_EX_CLIENTEDGE;
+
+        /// Paints all descendants of a window in bottom-to-top painting order using double-buffering.
+        const COMPOSITED = WS_EX_COMPOSITED;
+
+        /// The title bar of the window includes a question mark.
+        const CONTEXT_HELP = WS_EX_CONTEXTHELP;
+
+        /// The window itself contains child windows that should take part in dialog box navigation.
+        const CONTROLS_PARENT = WS_EX_CONTROLPARENT;
+
+        /// The window has a double border.
+        const DLG_MODAL_FRAME = WS_EX_DLGMODALFRAME;
+
+        /// The window is a layered window, which can be translucent.
+        ///
+        /// See [`AsWindow::set_opacity`].
+        const LAYERED = WS_EX_LAYERED;
+
+        /// The window does not pass its window layout to its child windows.
+        const LAYOUT_RTL = WS_EX_LAYOUTRTL;
+
+        /// If the shell language is Hebrew, Arabic, or another language that supports reading order
+        /// alignment, the horizontal origin of the window is on the right edge.
+        const LEFT_TO_RIGHT_READING = WS_EX_LEFT;
+
+        /// Scroll bar on the left?
+        const LEFT_SCROLL_BAR = WS_EX_LEFTSCROLLBAR;
+
+        /// The window is an MDI child window.
+        const MDI_CHILD = WS_EX_MDICHILD;
+
+        /// A top-level window created with this style does not become the foreground window when the
+        /// user clicks it.
+        const NO_ACTIVATE = WS_EX_NOACTIVATE;
+
+        /// The window does not pass its window layout to its child windows.
+        const NO_INHERIT_LAYOUT = WS_EX_NOINHERITLAYOUT;
+
+        /// The child window created with this style does not send the WM_PARENTNOTIFY message to its
+        /// parent window when it is created or destroyed.
+        const NO_PARENT_NOTIFY = WS_EX_NOPARENTNOTIFY;
+
+        /// The window does not render to a redirection surface.
+        const NO_REDIRECTION_BITMAP = WS_EX_NOREDIRECTIONBITMAP;
+
+        /// The window is an overlapped window.
+        const OVERLAPPED_WINDOW = WS_EX_OVERLAPPEDWINDOW;
+
+        /// The window is palette window, which is a modeless dialog box that presents an array of
+        /// commands.
+        const PALETTE_WINDOW = WS_EX_PALETTEWINDOW;
+
+        /// The window has generic "right-aligned" properties. This depends on the window class.
+        const RIGHT = WS_EX_RIGHT;
+
+        /// If the shell language is Hebrew, Arabic, or another language that supports reading order
+        /// alignment, the vertical scroll bar (if present) is to the left of the client area.
+        const RIGHT_SCROLL_BAR = WS_EX_RIGHTSCROLLBAR;
+
+        /// The window text is displayed using left-to-right reading-order properties.
+        const RTL_LAYOUT = WS_EX_RTLREADING;
+
+        /// The window has a three-dimensional border style intended to be used for items that do not
+        /// accept user input.
+        const STATIC_EDGE = WS_EX_STATICEDGE;
+
+        /// The window is intended to be used as a floating toolbar.
+        const TOOL_WINDOW = WS_EX_TOOLWINDOW;
+
+        /// The window should be placed above all non-topmost windows and should stay above them.
+        const TOPMOST = WS_EX_TOPMOST;
+
+        /// The window should not be painted until siblings beneath the window (that were created by
+        /// the same thread) have been painted.
+        const TRANSPARENT = WS_EX_TRANSPARENT;
+
+        /// The window has a border with a raised edge.
+        const WINDOW_EDGE = WS_EX_WINDOWEDGE;
+    }
+}
+
+bitflags::bitflags! {
+    /// Commands to send to the window.
+    pub struct ShowCommand : u32 {
+        /// Hide the window.
+        const HIDE = SW_HIDE;
+
+        /// Show the window.
+        const NORMAL = SW_NORMAL;
+
+        /// Show and minimize the window.
+        const SHOW_MINIMIZED = SW_SHOWMINIMIZED;
+
+        /// Show and minimize the window.
+        const MINIMIZED = SW_MINIMIZE;
+
+        /// Show and maximize the window.
+        const MAXIMIZED = SW_SHOWMAXIMIZED;
+
+        /// Show the window but don't activate it.
+        const NO_ACTIVATE = SW_SHOWNOACTIVATE;
+
+        /// Show the window in its current state.
+        const SHOW = SW_SHOW;
+
+        /// Show the window using the default state.
+        const SHOW_DEFAULT = SW_SHOWDEFAULT;
+
+        /// Force the window to be minimized.
+        const FORCE_MINIMIZED = SW_FORCEMINIMIZE;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags for the `get_dc` function.
+    pub struct GetDcFlags: u32 {
+        /// Get the window area instead of the client area.
+        const WINDOW_AREA = DCX_WINDOW;
+
+        /// Returns a cached DC no matter what.
+        const CACHE = DCX_CACHE;
+
+        /// Use the visible region of the parent window.
+        const PARENT_CLIP = DCX_PARENTCLIP;
+
+        /// Exclude the visible regions of all sibling windows above the window.
+        const CLIP_SIBLINGS = DCX_CLIPSIBLINGS;
+
+        /// Exclude the visible regions of all child windows below the window.
+        const CLIP_CHILDREN = DCX_CLIPCHILDREN;
+
+        /// Allows drawing even if there is a LockWindowUpdate call in effect that would otherwise
+        /// exclude this window.
+        const LOCK_WINDOW_UPDATE = DCX_LOCKWINDOWUPDATE;
+    }
+}
+
+/// The type of region clipping to do for `GetDCEx`.
+pub enum RegionType {
+    /// No clipping.
+    None,
+
+    /// Intersect the region with the visible region.
+    Intersect(Region),
+
+    /// Exclude the region from the visible region.
+    Exclude(Region),
+}
+
+/// A scroll bar of a window.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScrollBar {
+    /// The standard horizontal scroll bar, shown with [`WindowStyle::H_SCROLL`].
+    Horizontal,
+
+    /// The standard vertical scroll bar, shown with [`WindowStyle::V_SCROLL`].
+    Vertical,
+
+    /// The window is a scroll bar control.
+    Control,
+}
+
+impl ScrollBar {
+    /// Get the `SB_*` constant for this scroll bar.
+    fn to_win32(self) -> SCROLLBAR_CONSTANTS {
+        match self {
+            Self::Horizontal => SB_HORZ,
+            Self::Vertical => SB_VERT,
+            Self::Control => SB_CTL,
+        }
+    }
+}
+
+/// The state of a scroll bar.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ScrollInfo {
+    /// The minimum scroll position.
+    pub min: i32,
+
+    /// The maximum scroll position.
+    pub max: i32,
+
+    /// The size of a page, which is also the size of the thumb.
+    pub page: u32,
+
+    /// The position of the thumb.
+    pub position: i32,
+
+    /// The position of the thumb while the user is dragging it.
+    pub track_position: i32,
+}
+
+/// A batch of window moves, created by [`Client::defer_window_pos`].
+///
+/// Dropping the batch without committing it still moves the windows.
+#[derive(Debug)]
+pub struct DeferredPositions {
+    /// The handle to the multiple-window position structure.
+    ///
+    /// This is zero once the batch has been committed or has failed.
+    handle: isize,
+
+    /// The batch can only be committed on the thread that created it.
+    _thread_unsafe: PhantomData<*mut ()>,
+}
+
+impl DeferredPositions {
+    /// Add a window move to the batch.
+    ///
+    /// The arguments are the same as for [`set_window_pos`](AsWindow::set_window_pos). All of
+    /// the windows in a batch must have the same parent. Conflicting arguments are rejected
+    /// without touching the batch, but if the system fails to add the move, the whole batch is
+    /// abandoned and none of the windows are moved.
+    pub fn set_window_pos(
+        &mut self,
+        window: &impl AsWindow,
+        insert_after: Option<InsertAfter<'_>>,
+        position: Option<Point<i32>>,
+        size: Option<Size<i32>>,
+        flags: WindowPosFlags,
+    ) -> Result<&mut Self, Error> {
+        if self.handle == 0 {
+            return Err(Error::custom(
+                ERROR_INVALID_HANDLE,
+                "DeferWindowPos",
+                "The batch has already been abandoned.",
+            ));
+        }
+
+        let (insert_after, [x, y], [width, height], flags) =
+            window_pos_args(insert_after, position, size, flags, "DeferWindowPos")?;
+
+        // DeferWindowPos frees the structure on failure.
+        self.handle = unsafe {
+            DeferWindowPos(
+                self.handle,
+                window.as_window().hwnd,
+                insert_after,
+                x,
+                y,
+                width,
+                height,
+                flags,
+            )
+        };
+
+        if self.handle == 0 {
+            Err(Error::last_error("DeferWindowPos"))
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// Move all of the windows in the batch at once.
+    pub fn commit(mut self) -> Result<(), Error> {
+        self.end()
+    }
+
+    /// Move the windows and free the structure, if it hasn't been already.
+    fn end(&mut self) -> Result<(), Error> {
+        let handle = mem::replace(&mut self.handle, 0);
+
+        if handle == 0 {
+            return Err(Error::custom(
+                ERROR_INVALID_HANDLE,
+                "EndDeferWindowPos",
+                "The batch has already been abandoned.",
+            ));
+        }
+
+        if unsafe { EndDeferWindowPos(handle) } == 0 {
+            Err(Error::last_error("EndDeferWindowPos"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Drop for DeferredPositions {
+    fn drop(&mut self) {
+        if self.handle != 0 {
+            if let Err(err) = self.end() {
+                tracing::error!("Failed to move deferred windows: {}", err);
+            }
+        }
+    }
+}
+
+/// The handle to insert the window after.
+#[derive(Debug, Copy, Clone)]
+pub enum InsertAfter<'hwnd> {
+    /// The window to insert before.
+    Window(BorrowedWindow<'hwnd>),
+
+    /// Place the window at the bottom of the Z order.
+    Bottom,
+
+    /// Place the window at the top of the Z order.
+    Top,
+
+    /// Place the window above all non-topmost windows.
+    TopMost,
+
+    //// Place the window above all non-topmost windows without making it topmost.
+    NoTopMost,
+}
+
+bitflags::bitflags! {
+    /// Flags for `SetWindowPos`.
+    pub struct WindowPosFlags : u32 {
+        /// Prevents generation of the WM_SYNCPAINT message.
+        const DEFER_ERASE = SWP_DEFERERASE;
+
+        /// Draw a frame around the window.
+        const DRAW_FRAME = SWP_DRAWFRAME;
+
+        /// Applies new frame styles set using the SetWindowLong function.
+        const FRAME_CHANGED = SWP_FRAMECHANGED;
+
+        /// Hides the window.
+        const HIDE_WINDOW = SWP_HIDEWINDOW;
+
+        /// Does not activate the window.
+        const NO_ACTIVATE = SWP_NOACTIVATE;
+
+        /// Discards the entire contents of the client area.
+        const NO_COPY_BITS = SWP_NOCOPYBITS;
+
+        /// The position is unchanged.
+        ///
+        /// This is set automatically when no position is passed to `set_window_pos`, and passing
+        /// a position along with it is an error.
+        const NO_MOVE = SWP_NOMOVE;
+
+        /// Do not change the owner window's position in the Z order.
+        const NO_OWNER_Z_ORDER = SWP_NOOWNERZORDER;
+
+        /// Do not redraw changes.
+        const NO_REDRAW = SWP_NOREDRAW;
+
+        /// Do not send the WM_WINDOWPOSCHANGING message to the window being repositioned.
+        const NO_SEND_CHANGING = SWP_NOSENDCHANGING;
+
+        /// The size is unchanged.
+        ///
+        /// This is set automatically when no size is passed to `set_window_pos`, and passing a
+        /// size along with it is an error.
+        const NO_SIZE = SWP_NOSIZE;
+
+        /// The Z order is unchanged.
+        ///
+        /// This is set automatically when no window to insert after is passed to
+        /// `set_window_pos`, and passing one along with it is an error.
+        const NO_Z_ORDER = SWP_NOZORDER;
+
+        /// Display the window.
+        const SHOW_WINDOW = SWP_SHOWWINDOW;
+    }
+}
+
+bitflags::bitflags! {
+    /// What to track with [`AsWindow::track_mouse`].
+    pub struct TrackMouseFlags : u32 {
+        /// Report when the cursor hovers over the window.
+        const HOVER = TME_HOVER;
+
+        /// Report when the cursor leaves the window.
+        const LEAVE = TME_LEAVE;
+
+        /// Cancel the given kinds of tracking instead of starting them.
+        const CANCEL = TME_CANCEL;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags for [`AsWindow::redraw`].
+    pub struct RedrawFlags : u32 {
+        /// Add the area to the update region.
+        const INVALIDATE = RDW_INVALIDATE;
+
+        /// Send a paint message even if the window isn't invalid.
+        const INTERNAL_PAINT = RDW_INTERNALPAINT;
+
+        /// Erase the background when the window is painted.
+        const ERASE = RDW_ERASE;
+
+        /// Remove the area from the update region.
+        const VALIDATE = RDW_VALIDATE;
+
+        /// Cancel any pending paint message sent because of `INTERNAL_PAINT`.
+        const NO_INTERNAL_PAINT = RDW_NOINTERNALPAINT;
+
+        /// Don't erase the background.
+        const NO_ERASE = RDW_NOERASE;
+
+        /// Only affect this window, not its children.
+        const NO_CHILDREN = RDW_NOCHILDREN;
+
+        /// Also affect the window's children.
+        const ALL_CHILDREN = RDW_ALLCHILDREN;
+
+        /// Paint the window before returning.
+        const UPDATE_NOW = RDW_UPDATENOW;
+
+        /// Erase the background before returning, but paint later.
+        const ERASE_NOW = RDW_ERASENOW;
+
+        /// Also redraw the non-client area.
+        const FRAME = RDW_FRAME;
+
+        /// Don't redraw the non-client area.
+        const NO_FRAME = RDW_NOFRAME;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags for [`AsWindow::scroll`].
+    pub struct ScrollFlags : u32 {
+        /// Also move the child windows in the scrolled area.
+        const SCROLL_CHILDREN = SW_SCROLLCHILDREN;
+
+        /// Invalidate the uncovered region.
+        const INVALIDATE = SW_INVALIDATE;
+
+        /// Erase the background of the uncovered region. Requires `INVALIDATE`.
+        const ERASE = SW_ERASE;
+
+        /// Scroll smoothly, using the system's animation time.
+        const SMOOTH_SCROLL = SW_SMOOTHSCROLL;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags for `FlashWindowEx`.
+    pub struct FlashFlags : u32 {
+        /// Flash the window caption.
+        const CAPTION = FLASHW_CAPTION;
+
+        /// Flash the taskbar button.
+        const TRAY = FLASHW_TRAY;
+
+        /// Flash both the window caption and the taskbar button.
+        const ALL = FLASHW_ALL;
+
+        /// Flash continuously, until the flash is stopped.
+        const TIMER = FLASHW_TIMER;
+
+        /// Flash continuously until the window comes to the foreground.
+        const TIMER_NO_FOREGROUND = FLASHW_TIMERNOFG;
+    }
+}
+
+#[repr(C)]
+pub(crate) struct WindowData<'a, T> {
+    /// The handle to the window.
+    hwnd: HWND,
+
+    /// A queue of messages to be processed.
+    message_queue: RefCell<VecDeque<Event<'static>>>,
+
+    /// The user data associated with the window.
+    user_data: Box<T>,
+
+    /// The class data for the window.
+    class_data: Rc<dyn ErasedClassData<T> + 'a>,
+
+    /// The re-entrancy count of the current window procedure.
+    rentrancy_count: Cell<Option<NonZeroU32>>,
+}
+
+// With libstd, we can catch panics to prevent them from hitting the abort guard.
+#[cfg(feature = "std")]
+impl<'a, T> WindowData<'a, T> {
+    /// Run code and queue the panic on the reactor if one happened.
+    pub(crate) fn catch_panic<R, F: FnOnce() -> R>(&self, f: F) -> Option<R> {
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+            Ok(result) => Some(result),
+            Err(panic) => {
+                crate::reactor::push_panic(panic);
+                None
+            }
+        }
+    }
+}
+
+// Without libstd, we can't propagate panics. Just let it hit the abort guard.
+#[cfg(not(feature = "std"))]
+impl<'a, T> WindowData<'a, T> {
+    /// Run code and store the panic if one happened.
+    #[inline]
+    pub(crate) fn catch_panic<R, F: FnOnce() -> R>(&self, f: F) -> Option<R> {
+        Some(f())
+    }
 }
 
-impl AsWindow for BorrowedWindow<'_> {
-    fn as_window(&self) -> BorrowedWindow<'_> {
-        unsafe { Self::from_raw_handle(self.hwnd) }
+impl<'a, T> WindowData<'a, T> {
+    /// Create a new window data.
+    pub(crate) fn new<F: Fn(&Client, &T, BorrowedWindow<'_>, Event<'_>) + 'a>(
+        hwnd: HWND,
+        data: Box<T>,
+        class_data: Rc<ClassData<F>>,
+    ) -> Self {
+        Self {
+            hwnd,
+            message_queue: RefCell::new(VecDeque::new()),
+            user_data: data,
+            class_data,
+            rentrancy_count: Cell::new(None),
+        }
     }
-}
 
-impl<T> AsWindow for Window<'_, T> {
-    fn as_window(&self) -> BorrowedWindow<'_> {
-        unsafe { BorrowedWindow::from_raw_handle(self.hwnd) }
+    /// Push a new event.
+    pub(crate) fn push(&self, event: Event<'static>) {
+        self.message_queue.borrow_mut().push_back(event);
     }
-}
 
-#[cfg(feature = "raw-window-handle")]
-unsafe impl raw_window_handle::HasRawWindowHandle for BorrowedWindow<'_> {
-    fn raw_window_handle(&self) -> raw_window_handle::RawWindowHandle {
-        let mut handle = raw_window_handle::Win32WindowHandle::empty();
-        handle.hwnd = strict::invalid(self.hwnd) as *mut _;
-        handle.hinstance = strict::invalid(current_module()) as *mut _;
+    /// Run the handler for an event immediately.
+    ///
+    /// This is used for events that borrow from the window procedure or that need a reply, since
+    /// they can't wait in the queue.
+    pub(crate) fn dispatch(&self, event: Event<'_>) {
+        self.class_data.run_handler(
+            &self.user_data,
+            unsafe { BorrowedWindow::from_raw_handle(self.hwnd) },
+            event,
+        );
+    }
 
-        raw_window_handle::RawWindowHandle::Win32(handle)
+    /// Process all events.
+    fn process(&self) {
+        // Don't hold the borrow while running the handler, since it may push more events.
+        loop {
+            let event = self.message_queue.borrow_mut().pop_front();
+
+            match event {
+                Some(event) => self.dispatch(event),
+                None => break,
+            }
+        }
     }
-}
 
-#[cfg(feature = "raw-window-handle")]
-unsafe impl<T> raw_window_handle::HasRawWindowHandle for Window<'_, T> {
-    fn raw_window_handle(&self) -> raw_window_handle::RawWindowHandle {
-        self.as_window().raw_window_handle()
+    /// Begin a new re-entrancy scope.
+    pub(crate) fn begin(&self) -> impl Drop + '_ {
+        struct CallOnDrop<F: Fn()>(F);
+
+        impl<F: Fn()> Drop for CallOnDrop<F> {
+            fn drop(&mut self) {
+                (self.0)();
+            }
+        }
+
+        let guard = CallOnDrop(move || {
+            let current_count = match self.rentrancy_count.get() {
+                Some(count) => count.get(),
+                None => panic!("Re-entrancy count is not set."),
+            };
+
+            match current_count - 1 {
+                0 => {
+                    // Keep the count held while draining, so that events sent from the handler
+                    // are queued behind the current ones instead of running recursively.
+                    self.process();
+                    self.rentrancy_count.set(None);
+                }
+                n => self
+                    .rentrancy_count
+                    .set(Some(unsafe { NonZeroU32::new_unchecked(n) })),
+            }
+        });
+
+        // Bump the re-entrancy count.
+        match self.rentrancy_count.get() {
+            Some(count) => self.rentrancy_count.set(Some(unsafe {
+                NonZeroU32::new_unchecked(
+                    count
+                        .get()
+                        .checked_add(1)
+                        .expect("Re-entrancy count overflowed."),
+                )
+            })),
+            None => self
+                .rentrancy_count
+                .set(Some(unsafe { NonZeroU32::new_unchecked(1) })),
+        }
+
+        guard
     }
 }
 
-bitflags::bitflags! {
-    /// Window styles.
-    pub struct WindowStyle : u32 {
-        /// The window has a thin-line border.
-        const BORDER = WS_BORDER;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::ClassBuilder;
+    use crate::cstr::CString;
+    use crate::event::{EndSessionReason, Event, HitTest, MouseButton, SizingEdge};
+    use crate::keyboard::KeyModifiers;
+    use crate::menu::MenuItem;
+    use crate::Client;
 
-        /// The window has a title bar.
-        const CAPTION = WS_CAPTION;
+    use windows_sys::Win32::Foundation::ERROR_INVALID_WINDOW_HANDLE;
+    use windows_sys::Win32::Graphics::Gdi::{GetDC, ReleaseDC};
+    use windows_sys::Win32::System::SystemServices::MK_CONTROL;
+    use windows_sys::Win32::System::Threading::GetCurrentProcessId;
+    use windows_sys::Win32::UI::Controls::{WM_MOUSEHOVER, WM_MOUSELEAVE};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        GetMenuState, GetWindowTextA, GetWindowTextW, IsWindow, HTCAPTION, HTCLIENT, HTERROR,
+        WM_APP, WM_CHAR, WM_CONTEXTMENU, WM_ERASEBKGND, WM_LBUTTONDBLCLK, WM_NCHITTEST,
+        WM_SETCURSOR, WM_SETTEXT, WM_SIZE,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{ENDSESSION_LOGOFF, WM_QUERYENDSESSION};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        WA_ACTIVE, WA_INACTIVE, WM_ACTIVATE, WM_ACTIVATEAPP, WM_HOTKEY,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{WMSZ_RIGHT, WM_MOVE, WM_MOVING, WM_SIZING};
+
+    /// Register a class named `name` with `handler`, then create a window of it with the same
+    /// title.
+    ///
+    /// The class comes first, so that it outlives the window when both are bound by one `let`.
+    fn create_test_window<'a, T: 'a>(
+        client: &Client,
+        name: &'a CStr,
+        style: WindowStyle,
+        extended_style: ExtendedStyle,
+        rectangle: Rect<i32>,
+        window_data: T,
+        handler: impl Fn(&Client, &T, BorrowedWindow<'_>, Event<'_>) + 'a,
+    ) -> (WindowClass<'a, T>, Window<'a, T>) {
+        let class = client
+            .create_class(name)
+            .build(handler)
+            .expect("Failed to create window class");
+        let window = client
+            .create_window(
+                &class,
+                name,
+                None,
+                None,
+                style,
+                extended_style,
+                rectangle,
+                window_data,
+            )
+            .expect("Failed to create window");
 
-        /// The window is a child window.
-        const CHILD = WS_CHILD;
+        (class, window)
+    }
 
-        /// Excludes the area occupied by child windows.
-        const CLIP_CHILDREN = WS_CLIPCHILDREN;
+    #[test]
+    fn test_window() {
+        let client = Client::new();
+        let class_name = CString::new("test_window_creation").unwrap();
+        let window_title = CString::new("test_window_creation").unwrap();
+        let class = client
+            .create_class(&class_name)
+            .build(|client, &(), _, ev| {
+                if let Event::Created = ev {
+                    client.quit();
+                }
+            })
+            .expect("Failed to create window class");
 
-        /// Excludes the area occupied by sibling windows.
-        const CLIP_SIBLINGS = WS_CLIPSIBLINGS;
+        // Create the window.
+        let _window = client
+            .create_window(
+                &class,
+                &window_title,
+                None,
+                None,
+                WindowStyle::empty(),
+                ExtendedStyle::empty(),
+                Rect::new(Point::new(0, 0), Size::new(1, 1)),
+                (),
+            )
+            .expect("Failed to create window");
 
-        /// The window is initially disabled.
-        const DISABLED = WS_DISABLED;
+        // Run the client.
+        crate::reactor::Reactor::new()
+            .expect("to create client")
+            .run()
+            .expect("to run without errors");
+    }
 
-        /// The window has a border of a style typically used with dialog boxes.
-        const DIALOG_FRAME = WS_DLGFRAME;
+    #[test]
+    fn test_window_creation_failure() {
+        let client = Client::new();
+        let class_name = CString::new("test_window_creation_failure").unwrap();
+        let class = client
+            .create_class(&class_name)
+            .build(|_, _: &Rc<()>, _, _| {})
+            .expect("Failed to create window class");
 
-        /// The window is the first control of a group of controls.
-        const GROUP = WS_GROUP;
+        // A child window without a parent cannot be created.
+        let data = Rc::new(());
+        let result = client.create_window(
+            &class,
+            &class_name,
+            None,
+            None,
+            WindowStyle::CHILD,
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(1, 1)),
+            data.clone(),
+        );
+        assert!(result.is_err());
+
+        // The window data should be freed, and the window count untouched.
+        assert_eq!(Rc::strong_count(&data), 1);
+        assert_eq!(client.window_count(), 0);
+    }
 
-        /// The window has a horizontal scroll bar.
-        const H_SCROLL = WS_HSCROLL;
+    #[test]
+    fn test_window_creation_failure_after_create() {
+        use windows_sys::Win32::Foundation::{LPARAM, LRESULT, WPARAM};
+        use windows_sys::Win32::UI::WindowsAndMessaging::{
+            CallNextHookEx, CallWindowProcW, SetWindowLongPtrW, SetWindowsHookExW,
+            UnhookWindowsHookEx, GWLP_WNDPROC, HCBT_CREATEWND, WH_CBT, WM_CREATE, WNDPROC,
+        };
 
-        /// The window is initially maximized.
-        const MAXIMIZE = WS_MAXIMIZE;
+        std::thread_local! {
+            static ORIGINAL: Cell<isize> = const { Cell::new(0) };
+        }
 
-        /// The window has a maximize button.
-        const MAXIMIZE_BOX = WS_MAXIMIZEBOX;
+        // Forward everything to the crate's window procedure, but fail WM_CREATE after it ran.
+        unsafe extern "system" fn fail_create(
+            hwnd: HWND,
+            msg: u32,
+            wparam: WPARAM,
+            lparam: LPARAM,
+        ) -> LRESULT {
+            let original: WNDPROC = mem::transmute(ORIGINAL.with(Cell::get));
+            let result = CallWindowProcW(original, hwnd, msg, wparam, lparam);
+
+            if msg == WM_CREATE {
+                -1
+            } else {
+                result
+            }
+        }
 
-        /// The window is initially minimized.
-        const MINIMIZE = WS_MINIMIZE;
+        // Subclass the window as soon as it exists, before it receives WM_NCCREATE.
+        unsafe extern "system" fn hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+            if code == HCBT_CREATEWND as i32 {
+                let procedure = fail_create as *const () as isize;
+                let original = SetWindowLongPtrW(wparam as HWND, GWLP_WNDPROC, procedure);
+                ORIGINAL.with(|cell| cell.set(original));
+            }
 
-        /// The window has a minimize button.
-        const MINIMIZE_BOX = WS_MINIMIZEBOX;
+            CallNextHookEx(0, code, wparam, lparam)
+        }
 
-        /// The window is an overlapped window.
-        const OVERLAPPED = WS_OVERLAPPED;
+        let client = Client::new();
+        let class_name = CString::new("test_window_creation_failure_after_create").unwrap();
+        let class = client
+            .create_class(&class_name)
+            .build(|_, _: &Rc<()>, _, _| {})
+            .expect("Failed to create window class");
+
+        let hook = unsafe { SetWindowsHookExW(WH_CBT, Some(hook), 0, GetCurrentThreadId()) };
+        assert_ne!(hook, 0);
+
+        // The window data is installed by WM_NCCREATE, and then WM_CREATE fails.
+        let data = Rc::new(());
+        let result = client.create_window(
+            &class,
+            &class_name,
+            None,
+            None,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(1, 1)),
+            data.clone(),
+        );
+        unsafe { UnhookWindowsHookEx(hook) };
+        assert!(result.is_err());
+
+        // The window data should be freed along with the window, and the count restored.
+        assert_eq!(Rc::strong_count(&data), 1);
+        assert_eq!(client.window_count(), 0);
+    }
+
+    #[test]
+    fn test_erase_background() {
+        let client = Client::new();
+        let class_name = CString::new("test_erase_background").unwrap();
+        let erased = Cell::new(0);
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, ev| {
+                if let Event::EraseBackground { reply, .. } = ev {
+                    erased.set(erased.get() + 1);
+                    reply.send(true);
+                }
+            },
+        );
+
+        // The handler's reply should be returned from the window procedure.
+        let dc = unsafe { GetDC(window.hwnd) };
+        assert_ne!(dc, 0);
+        let result = unsafe { SendMessageA(window.hwnd, WM_ERASEBKGND, dc as usize, 0) };
+        unsafe { ReleaseDC(window.hwnd, dc) };
+        assert_eq!(result, 1);
+        assert_eq!(erased.get(), 1);
+    }
+
+    #[test]
+    fn test_query_end_session() {
+        let client = Client::new();
+        let class_name = CString::new("test_query_end_session").unwrap();
+        let reason = Cell::new(None);
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, ev| {
+                if let Event::QueryEndSession { reason: r, reply } = ev {
+                    reason.set(Some(r));
+                    reply.send(false);
+                }
+            },
+        );
+        window
+            .block_shutdown("Saving data")
+            .expect("Failed to block shutdown");
+
+        // The handler should be able to veto the session ending.
+        let result = unsafe {
+            SendMessageA(
+                window.hwnd,
+                WM_QUERYENDSESSION,
+                0,
+                ENDSESSION_LOGOFF as isize,
+            )
+        };
+        assert_eq!(result, 0);
+        assert_eq!(reason.get(), Some(EndSessionReason::LOGOFF));
+
+        window
+            .unblock_shutdown()
+            .expect("Failed to unblock shutdown");
+    }
+
+    #[test]
+    fn test_set_title_str() {
+        let client = Client::new();
+        let class_name = CString::new("test_set_title_str").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, _| {},
+        );
+
+        window.set_title_str("Hello").expect("Failed to set title");
+        let mut buffer = [0u8; 16];
+        let len = unsafe { GetWindowTextA(window.hwnd, buffer.as_mut_ptr(), 16) };
+        assert_eq!(&buffer[..len as usize], b"Hello");
+
+        // Greek isn't in the Western code pages, so this would be lost on an ANSI window.
+        let title = "Ωμέγα";
+        window
+            .set_title_str(title)
+            .expect("Failed to set non-ASCII title");
+        let mut wide = [0u16; 16];
+        let len = unsafe { GetWindowTextW(window.hwnd, wide.as_mut_ptr(), 16) };
+        assert_eq!(
+            &wide[..len as usize],
+            &title.encode_utf16().collect::<Vec<_>>()[..]
+        );
+
+        let err = window
+            .set_title_str("Hello\0World")
+            .expect_err("Interior nul should be rejected");
+        assert_eq!(err.kind(), crate::ErrorKind::InvalidParameter);
+    }
+
+    #[test]
+    fn test_registered_message() {
+        let client = Client::new();
+        let message_name = CString::new("porcupine_test_registered_message").unwrap();
+        let msg = client
+            .register_message(&message_name)
+            .expect("Failed to register message");
+        assert!(msg >= 0xC000);
+
+        // Registering the same name again gives the same identifier.
+        assert_eq!(client.register_message(&message_name).unwrap(), msg);
+
+        let class_name = CString::new("test_registered_message").unwrap();
+        let received = Cell::new(None);
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, ev| {
+                if let Event::User {
+                    msg,
+                    wparam,
+                    lparam,
+                } = ev
+                {
+                    received.set(Some((msg, wparam, lparam)));
+                }
+            },
+        );
+
+        unsafe { SendMessageA(window.hwnd, msg, 1, 2) };
+        assert_eq!(received.get(), Some((msg, 1, 2)));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_timer() {
+        use crate::reactor::Reactor;
+        use futures_lite::StreamExt;
+
+        let client = Client::new();
+        let class_name = CString::new("test_timer").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, _| {},
+        );
+
+        let mut timer = window
+            .as_window()
+            .timer(Duration::from_millis(50))
+            .expect("Failed to start timer");
+
+        let ticks = Reactor::new()
+            .unwrap()
+            .block_on(async {
+                let mut ticks = 0;
+                while ticks < 2 {
+                    timer.next().await;
+                    ticks += 1;
+                }
+                ticks
+            })
+            .expect("Failed to run reactor")
+            .expect("Reactor quit early");
+        assert_eq!(ticks, 2);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_wait_for() {
+        use crate::message::Resized;
+        use crate::reactor::Reactor;
+
+        let client = Client::new();
+        let class_name = CString::new("test_wait_for").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, _| {},
+        );
+
+        // The future starts listening before it is polled.
+        let resized = window.as_window().wait_for::<Resized>();
+        unsafe { SendMessageA(window.hwnd, WM_SIZE, 0, (40 << 16) | 30) };
+
+        let resized = Reactor::new()
+            .unwrap()
+            .block_on(resized)
+            .expect("Failed to run reactor")
+            .expect("Reactor quit early");
+        assert_eq!(resized.size, Size::new(30, 40));
+    }
+
+    #[test]
+    fn test_post_message() {
+        let client = Client::new();
+        let class_name = CString::new("test_post_message").unwrap();
+        let received = Cell::new(None);
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, ev| {
+                if let Event::User {
+                    msg,
+                    wparam,
+                    lparam,
+                } = ev
+                {
+                    received.set(Some((msg, wparam, lparam)));
+                }
+            },
+        );
+
+        // Posted messages only arrive once the queue is processed.
+        unsafe { window.post_message(WM_APP + 1, 3, 4) }.expect("Failed to post message");
+        assert_eq!(received.get(), None);
+
+        client.poll_events().expect("Failed to poll events");
+        assert_eq!(received.get(), Some((WM_APP + 1, 3, 4)));
+
+        // Sent messages are processed right away.
+        let title = CString::new("sent title").unwrap();
+        let result = unsafe { window.send_message(WM_SETTEXT, 0, title.as_ptr() as isize) };
+        assert_eq!(result, 1);
+
+        let mut buffer = [0u8; 16];
+        let len = unsafe { GetWindowTextA(window.hwnd, buffer.as_mut_ptr(), 16) };
+        assert_eq!(&buffer[..len as usize], b"sent title");
+    }
+
+    #[test]
+    fn test_raw_events() {
+        let client = Client::new();
+        let class_name = CString::new("test_raw_events").unwrap();
+        let received = Cell::new(None);
+        let class = client
+            .create_class(&class_name)
+            .raw_events(true)
+            .build(|_, &(), _, ev| {
+                if let Event::Raw {
+                    msg: WM_CHAR,
+                    wparam,
+                    lparam,
+                    reply,
+                } = ev
+                {
+                    received.set(Some((wparam, lparam)));
+                    reply.send(42);
+                }
+            })
+            .expect("Failed to create window class");
+
+        let window = client
+            .create_window(
+                &class,
+                &class_name,
+                None,
+                None,
+                WindowStyle::empty(),
+                ExtendedStyle::empty(),
+                Rect::new(Point::new(0, 0), Size::new(10, 10)),
+                (),
+            )
+            .expect("Failed to create window");
 
-        /// The window is an overlapped window.
-        const OVERLAPPED_WINDOW = WS_OVERLAPPEDWINDOW;
+        unsafe { window.post_message(WM_CHAR, b'a' as usize, 1) }.expect("Failed to post message");
+        client.poll_events().expect("Failed to poll events");
+        assert_eq!(received.get(), Some((b'a' as usize, 1)));
 
-        /// The window is a pop-up window.
-        const POPUP = WS_POPUP;
+        // The reply is returned for the message.
+        let result = unsafe { window.send_message(WM_CHAR, b'b' as usize, 2) };
+        assert_eq!(result, 42);
+        assert_eq!(received.get(), Some((b'b' as usize, 2)));
+    }
 
-        /// The window is a pop-up window.
-        const POPUP_WINDOW = WS_POPUPWINDOW;
+    #[test]
+    fn test_sizing() {
+        let client = Client::new();
+        let class_name = CString::new("test_sizing").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, ev| match ev {
+                Event::Sizing { edge, rect } => {
+                    assert_eq!(edge, SizingEdge::Right);
+
+                    // Limit the width to 100 pixels.
+                    let size = Size::new(rect.size().width().min(100), rect.size().height());
+                    *rect = Rect::new(rect.origin(), size);
+                }
+                Event::Moving { rect } => {
+                    // Keep the window at the top of the screen.
+                    *rect = Rect::new(Point::new(rect.origin().x(), 0), rect.size());
+                }
+                _ => {}
+            },
+        );
 
-        /// The window has a sizing border.
-        const SIZE_BOX = WS_SIZEBOX;
+        let mut rect = rect_to_win32(Rect::new(Point::new(10, 20), Size::new(300, 200)));
+        let result = unsafe {
+            window.send_message(
+                WM_SIZING,
+                WMSZ_RIGHT as usize,
+                &mut rect as *mut RECT as isize,
+            )
+        };
+        assert_eq!(result, 1);
+        assert_eq!(
+            rect_from_win32(rect),
+            Rect::new(Point::new(10, 20), Size::new(100, 200))
+        );
+
+        unsafe { window.send_message(WM_MOVING, 0, &mut rect as *mut RECT as isize) };
+        assert_eq!(
+            rect_from_win32(rect),
+            Rect::new(Point::new(10, 0), Size::new(100, 200))
+        );
+    }
 
-        /// The window has a control that receives keyboard focus when the user presses the TAB key.
-        const TAB_STOP = WS_TABSTOP;
+    #[test]
+    fn test_destroy() {
+        let client = Client::new();
+        let class_name = CString::new("test_destroy").unwrap();
+
+        let (class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, _| {},
+        );
+        let hwnd = window.hwnd;
+
+        window.destroy().expect("Failed to destroy window");
+        assert_eq!(unsafe { IsWindow(hwnd) }, 0);
+        assert_eq!(client.window_count(), 0);
+
+        // Destroying a window that is already gone reports why.
+        let window = client
+            .create_window(
+                &class,
+                &class_name,
+                None,
+                None,
+                WindowStyle::empty(),
+                ExtendedStyle::empty(),
+                Rect::new(Point::new(0, 0), Size::new(10, 10)),
+                (),
+            )
+            .expect("Failed to create window");
+        let hwnd = window.into_raw();
+        assert_ne!(unsafe { DestroyWindow(hwnd) }, 0);
 
-        /// The window has a sizing border.
-        const THICK_FRAME = WS_THICKFRAME;
+        let window = unsafe { Window::<()>::from_raw(hwnd) };
+        let err = window.destroy().unwrap_err();
+        assert_eq!(err.code(), ERROR_INVALID_WINDOW_HANDLE);
+        assert_eq!(err.function(), "GetWindowThreadProcessId");
+    }
 
-        /// The window is initially visible.
-        const VISIBLE = WS_VISIBLE;
+    #[test]
+    fn test_destroying() {
+        let client = Client::new();
+        let class_name = CString::new("test_destroying").unwrap();
+
+        let destroyed = Rc::new(Cell::new(0));
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            destroyed.clone(),
+            |_, data: &Rc<Cell<u32>>, window, ev| {
+                if let Event::Destroying = ev {
+                    // The window and its data are still alive.
+                    assert!(unsafe { IsWindow(window.hwnd) } != 0);
+                    data.set(data.get() + 1);
+                }
+            },
+        );
+        assert_eq!(destroyed.get(), 0);
 
-        /// The window has a vertical scroll bar.
-        const V_SCROLL = WS_VSCROLL;
+        drop(window);
+        assert_eq!(destroyed.get(), 1);
+        assert_eq!(Rc::strong_count(&destroyed), 1);
     }
-}
 
-bitflags::bitflags! {
-    /// Extended window styles.
-    pub struct ExtendedStyle : u32 {
-        /// The window accepts drag-drop files.
-        const ACCEPT_FILES = WS_EX_ACCEPTFILES;
+    #[test]
+    fn test_update() {
+        let client = Client::new();
+        let class_name = CString::new("test_update").unwrap();
+        let erased = Cell::new(0);
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::OVERLAPPED_WINDOW | WindowStyle::VISIBLE,
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(100, 100)),
+            (),
+            |_, &(), _, ev| {
+                if let Event::EraseBackground { .. } = ev {
+                    erased.set(erased.get() + 1);
+                }
+            },
+        );
 
-        /// Forces a top-level window onto the taskbar when the window is visible.
-        const APP_WINDOW = WS_EX_APPWINDOW;
+        // Paint anything left over from creating the window.
+        window.update().expect("Failed to update window");
+        erased.set(0);
 
-        /// The window has a border with a sunken edge.
-        const CLIENT_EDGE = WS_EX_CLIENTEDGE;
+        // Painting happens synchronously.
+        window.invalidate(None, true).unwrap();
+        window.update().expect("Failed to update window");
+        assert_eq!(erased.get(), 1);
 
-        /// Paints all descendants of a window in bottom-to-top painting order using double-buffering.
-        const COMPOSITED = WS_EX_COMPOSITED;
+        // Nothing is invalid anymore.
+        window.update().expect("Failed to update window");
+        assert_eq!(erased.get(), 1);
 
-        /// The title bar of the window includes a question mark.
-        const CONTEXT_HELP = WS_EX_CONTEXTHELP;
+        window
+            .redraw(
+                Some(Rect::new(Point::new(0, 0), Size::new(10, 10))),
+                RedrawFlags::INVALIDATE | RedrawFlags::ERASE | RedrawFlags::UPDATE_NOW,
+            )
+            .expect("Failed to redraw window");
+        assert_eq!(erased.get(), 2);
+    }
 
-        /// The window itself contains child windows that should take part in dialog box navigation.
-        const CONTROLS_PARENT = WS_EX_CONTROLPARENT;
+    #[test]
+    fn test_update_rect() {
+        let client = Client::new();
+        let class_name = CString::new("test_update_rect").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::OVERLAPPED_WINDOW | WindowStyle::VISIBLE,
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(100, 100)),
+            (),
+            |_, &(), _, _| {},
+        );
+
+        window.update().expect("Failed to update window");
+        assert_eq!(window.update_rect(false).unwrap(), None);
+
+        let rect = Rect::new(Point::new(10, 10), Size::new(20, 20));
+        window.invalidate(Some(rect), false).unwrap();
+        assert_eq!(window.update_rect(false).unwrap(), Some(rect));
+
+        // Getting the rectangle doesn't validate it.
+        assert_eq!(window.update_rect(false).unwrap(), Some(rect));
+        window.update().expect("Failed to update window");
+        assert_eq!(window.update_rect(false).unwrap(), None);
+    }
 
-        /// The window has a double border.
-        const DLG_MODAL_FRAME = WS_EX_DLGMODALFRAME;
+    #[test]
+    fn test_repaint() {
+        let client = Client::new();
+        let class_name = CString::new("test_repaint").unwrap();
+        let erased = Cell::new(0);
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::OVERLAPPED_WINDOW | WindowStyle::VISIBLE,
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(100, 100)),
+            (),
+            |_, &(), _, ev| {
+                if let Event::EraseBackground { .. } = ev {
+                    erased.set(erased.get() + 1);
+                }
+            },
+        );
 
-        /// The window is a layered window.
-        const LAYERED = WS_EX_LAYERED;
+        window.update().expect("Failed to update window");
+        erased.set(0);
 
-        /// The window does not pass its window layout to its child windows.
-        const LAYOUT_RTL = WS_EX_LAYOUTRTL;
+        // The background is erased before `repaint` returns.
+        window.repaint().expect("Failed to repaint window");
+        assert_eq!(erased.get(), 1);
+        window.repaint().expect("Failed to repaint window");
+        assert_eq!(erased.get(), 2);
+    }
 
-        /// If the shell language is Hebrew, Arabic, or another language that supports reading order
-        /// alignment, the horizontal origin of the window is on the right edge.
-        const LEFT_TO_RIGHT_READING = WS_EX_LEFT;
+    #[test]
+    fn test_with_user_data() {
+        let client = Client::new();
+        let class_name = CString::new("test_with_user_data").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            0xDEAD_BEEFu32,
+            |_, _: &u32, _, _| {},
+        );
+
+        let data = unsafe { window.as_window().with_user_data(|data: &u32| *data) };
+        assert_eq!(data, Some(0xDEAD_BEEF));
+        assert!(format!("{:?}", window).contains(&format!("{}", 0xDEAD_BEEFu32)));
+    }
 
-        /// Scroll bar on the left?
-        const LEFT_SCROLL_BAR = WS_EX_LEFTSCROLLBAR;
+    #[test]
+    #[cfg(feature = "dwm")]
+    fn test_extend_frame_into_client() {
+        let client = Client::new();
+        let class_name = CString::new("test_extend_frame_into_client").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::OVERLAPPED_WINDOW,
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(100, 100)),
+            (),
+            |_, &(), _, _| {},
+        );
+
+        window
+            .extend_frame_into_client(Margins::default())
+            .expect("Failed to extend frame");
+    }
 
-        /// The window is an MDI child window.
-        const MDI_CHILD = WS_EX_MDICHILD;
+    #[test]
+    fn test_style() {
+        let client = Client::new();
+        let class_name = CString::new("test_style").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, _| {},
+        );
+
+        assert!(!window.style().contains(WindowStyle::THICK_FRAME));
+        window
+            .set_style(window.style() | WindowStyle::BORDER | WindowStyle::THICK_FRAME)
+            .expect("Failed to set style");
+        window
+            .set_window_pos(None, None, None, WindowPosFlags::FRAME_CHANGED)
+            .expect("Failed to apply style");
+        assert!(window
+            .style()
+            .contains(WindowStyle::BORDER | WindowStyle::THICK_FRAME));
+
+        window
+            .set_extended_style(ExtendedStyle::TOOL_WINDOW)
+            .expect("Failed to set extended style");
+        assert!(window.extended_style().contains(ExtendedStyle::TOOL_WINDOW));
+
+        // Class-specific bits survive a round trip through `style`.
+        let hwnd = window.as_window().handle();
+        unsafe { SetWindowLongPtrA(hwnd, GWL_STYLE, window.style().bits() as isize | 1) };
+        window
+            .set_style(window.style() | WindowStyle::TAB_STOP)
+            .expect("Failed to set style");
+        assert_eq!(unsafe { GetWindowLongPtrA(hwnd, GWL_STYLE) } & 1, 1);
+    }
 
-        /// A top-level window created with this style does not become the foreground window when the
-        /// user clicks it.
-        const NO_ACTIVATE = WS_EX_NOACTIVATE;
+    #[test]
+    fn test_adjust_window_rect() {
+        let client = Client::new();
+        let rect = Rect::new(Point::new(100, 100), Size::new(800, 600));
 
-        /// The window does not pass its window layout to its child windows.
-        const NO_INHERIT_LAYOUT = WS_EX_NOINHERITLAYOUT;
+        let adjusted = client
+            .adjust_window_rect(
+                rect,
+                WindowStyle::OVERLAPPED_WINDOW,
+                ExtendedStyle::empty(),
+                false,
+            )
+            .expect("Failed to adjust window rect");
+        assert!(adjusted.size().width() > 800);
+        assert!(adjusted.size().height() > 600);
+
+        // A menu bar makes the window taller.
+        let with_menu = client
+            .adjust_window_rect(
+                rect,
+                WindowStyle::OVERLAPPED_WINDOW,
+                ExtendedStyle::empty(),
+                true,
+            )
+            .expect("Failed to adjust window rect");
+        assert!(with_menu.size().height() > adjusted.size().height());
+    }
 
-        /// The child window created with this style does not send the WM_PARENTNOTIFY message to its
-        /// parent window when it is created or destroyed.
-        const NO_PARENT_NOTIFY = WS_EX_NOPARENTNOTIFY;
+    #[test]
+    fn test_defer_window_pos() {
+        let client = Client::new();
+        let class_name = CString::new("test_defer_window_pos").unwrap();
+
+        let (class, parent) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(200, 200)),
+            (),
+            |_, &(), _, _| {},
+        );
+        let create_child = || {
+            client
+                .create_window(
+                    &class,
+                    &class_name,
+                    None,
+                    Some(parent.as_window()),
+                    WindowStyle::CHILD,
+                    ExtendedStyle::empty(),
+                    Rect::new(Point::new(0, 0), Size::new(10, 10)),
+                    (),
+                )
+                .expect("Failed to create child window")
+        };
+        let first = create_child();
+        let second = create_child();
 
-        /// The window does not render to a redirection surface.
-        const NO_REDIRECTION_BITMAP = WS_EX_NOREDIRECTIONBITMAP;
+        let first_target = Rect::new(Point::new(5, 10), Size::new(20, 30));
+        let second_target = Rect::new(Point::new(50, 60), Size::new(40, 15));
 
-        /// The window is an overlapped window.
-        const OVERLAPPED_WINDOW = WS_EX_OVERLAPPEDWINDOW;
+        let mut batch = client.defer_window_pos(2).expect("Failed to begin batch");
+        batch
+            .set_window_pos(
+                &first,
+                None,
+                Some(first_target.origin()),
+                Some(first_target.size()),
+                WindowPosFlags::NO_ACTIVATE,
+            )
+            .expect("Failed to defer first move")
+            .set_window_pos(
+                &second,
+                None,
+                Some(second_target.origin()),
+                Some(second_target.size()),
+                WindowPosFlags::NO_ACTIVATE,
+            )
+            .expect("Failed to defer second move");
+        batch.commit().expect("Failed to commit batch");
+
+        for &(child, target) in &[(&first, first_target), (&second, second_target)] {
+            let origin = parent
+                .client_to_screen(target.origin())
+                .expect("Failed to convert point");
+            assert_eq!(child.window_rect(), Rect::new(origin, target.size()));
+        }
+    }
 
-        /// The window is palette window, which is a modeless dialog box that presents an array of
-        /// commands.
-        const PALETTE_WINDOW = WS_EX_PALETTEWINDOW;
+    #[test]
+    fn test_window_pos_args_conflict() {
+        let point = Some(Point::new(1, 2));
+        let size = Some(Size::new(3, 4));
+
+        let (_, position, _, flags) =
+            window_pos_args(None, point, None, WindowPosFlags::empty(), "SetWindowPos").unwrap();
+        assert_eq!(position, [1, 2]);
+        assert_eq!(flags, SWP_NOZORDER | SWP_NOSIZE);
+
+        for &(insert_after, position, size, flags) in &[
+            (
+                Some(InsertAfter::Top),
+                None,
+                None,
+                WindowPosFlags::NO_Z_ORDER,
+            ),
+            (None, point, None, WindowPosFlags::NO_MOVE),
+            (None, None, size, WindowPosFlags::NO_SIZE),
+        ] {
+            let err = window_pos_args(insert_after, position, size, flags, "SetWindowPos")
+                .expect_err("Conflicting arguments should be rejected");
+            assert_eq!(err.kind(), crate::ErrorKind::InvalidParameter);
+        }
+    }
 
-        /// The window has generic "right-aligned" properties. This depends on the window class.
-        const RIGHT = WS_EX_RIGHT;
+    #[test]
+    fn test_track_mouse() {
+        let client = Client::new();
+        let class_name = CString::new("test_track_mouse").unwrap();
+        let left = Cell::new(false);
+        let hovered = Cell::new(None);
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, ev| match ev {
+                Event::MouseLeft => left.set(true),
+                Event::MouseHover { position } => hovered.set(Some(position)),
+                _ => {}
+            },
+        );
 
-        /// If the shell language is Hebrew, Arabic, or another language that supports reading order
-        /// alignment, the vertical scroll bar (if present) is to the left of the client area.
-        const RIGHT_SCROLL_BAR = WS_EX_RIGHTSCROLLBAR;
+        window
+            .track_mouse(TrackMouseFlags::LEAVE | TrackMouseFlags::HOVER, None)
+            .expect("Failed to track mouse");
 
-        /// The window text is displayed using left-to-right reading-order properties.
-        const RTL_LAYOUT = WS_EX_RTLREADING;
+        // Simulate the messages the system sends.
+        unsafe { window.send_message(WM_MOUSELEAVE, 0, 0) };
+        assert!(left.get());
 
-        /// The window has a three-dimensional border style intended to be used for items that do not
-        /// accept user input.
-        const STATIC_EDGE = WS_EX_STATICEDGE;
+        unsafe { window.send_message(WM_MOUSEHOVER, 0, (7 << 16) | 5) };
+        assert_eq!(hovered.get(), Some(Point::new(5, 7)));
 
-        /// The window is intended to be used as a floating toolbar.
-        const TOOL_WINDOW = WS_EX_TOOLWINDOW;
+        window
+            .track_mouse(
+                TrackMouseFlags::CANCEL | TrackMouseFlags::LEAVE,
+                Some(Duration::from_millis(100)),
+            )
+            .expect("Failed to cancel tracking");
+    }
 
-        /// The window should be placed above all non-topmost windows and should stay above them.
-        const TOPMOST = WS_EX_TOPMOST;
+    #[test]
+    fn test_context_menu() {
+        let client = Client::new();
+        let class_name = CString::new("test_context_menu").unwrap();
+        let requested = Cell::new(None);
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, ev| {
+                if let Event::ContextMenu {
+                    position,
+                    from_keyboard,
+                } = ev
+                {
+                    requested.set(Some((position, from_keyboard)));
+                }
+            },
+        );
 
-        /// The window should not be painted until siblings beneath the window (that were created by
-        /// the same thread) have been painted.
-        const TRANSPARENT = WS_EX_TRANSPARENT;
+        // A right click on a monitor left of the primary one.
+        let lparam = (20 << 16) | 0xFFF6;
+        unsafe { window.send_message(WM_CONTEXTMENU, window.hwnd as usize, lparam) };
+        assert_eq!(requested.get(), Some((Point::new(-10, 20), false)));
 
-        /// The window has a border with a raised edge.
-        const WINDOW_EDGE = WS_EX_WINDOWEDGE;
+        // The menu key.
+        let lparam = 0xFFFF_FFFFu32 as isize;
+        unsafe { window.send_message(WM_CONTEXTMENU, window.hwnd as usize, lparam) };
+        assert_eq!(requested.get(), Some((Point::new(-1, -1), true)));
     }
-}
-
-bitflags::bitflags! {
-    /// Commands to send to the window.
-    pub struct ShowCommand : u32 {
-        /// Hide the window.
-        const HIDE = SW_HIDE;
 
-        /// Show the window.
-        const NORMAL = SW_NORMAL;
+    #[test]
+    fn test_activated() {
+        let client = Client::new();
+        let class_name = CString::new("test_activated").unwrap();
+        let activated = Cell::new(None);
+        let app_activated = Cell::new(None);
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, ev| match ev {
+                Event::Activated { active, minimized } => activated.set(Some((active, minimized))),
+                Event::AppActivated(active) => app_activated.set(Some(active)),
+                _ => {}
+            },
+        );
 
-        /// Show and minimize the window.
-        const SHOW_MINIMIZED = SW_SHOWMINIMIZED;
+        unsafe { window.send_message(WM_ACTIVATE, WA_ACTIVE as usize, 0) };
+        assert_eq!(activated.get(), Some((true, false)));
 
-        /// Show and minimize the window.
-        const MINIMIZED = SW_MINIMIZE;
+        unsafe { window.send_message(WM_ACTIVATE, (1 << 16) | WA_INACTIVE as usize, 0) };
+        assert_eq!(activated.get(), Some((false, true)));
 
-        /// Show and maximize the window.
-        const MAXIMIZED = SW_SHOWMAXIMIZED;
+        unsafe { window.send_message(WM_ACTIVATEAPP, 0, 0) };
+        assert_eq!(app_activated.get(), Some(false));
+    }
 
-        /// Show the window but don't activate it.
-        const NO_ACTIVATE = SW_SHOWNOACTIVATE;
+    #[test]
+    fn test_moved() {
+        let client = Client::new();
+        let class_name = CString::new("test_moved").unwrap();
+        let moved = Cell::new(None);
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, ev| {
+                if let Event::Moved(position) = ev {
+                    moved.set(Some(position));
+                }
+            },
+        );
 
-        /// Show the window in its current state.
-        const SHOW = SW_SHOW;
+        // Windows left of or above the primary monitor have negative coordinates.
+        unsafe { window.send_message(WM_MOVE, 0, (0xFFFE << 16) | 0xFFFF) };
+        assert_eq!(moved.get(), Some(Point::new(-1, -2)));
+    }
 
-        /// Show the window using the default state.
-        const SHOW_DEFAULT = SW_SHOWDEFAULT;
+    #[test]
+    fn test_geometry_changed() {
+        use windows_sys::Win32::UI::WindowsAndMessaging::{WINDOWPOS, WM_WINDOWPOSCHANGED};
 
-        /// Force the window to be minimized.
-        const FORCE_MINIMIZED = SW_FORCEMINIMIZE;
+        let client = Client::new();
+        let class_name = CString::new("test_geometry_changed").unwrap();
+        let geometry = Cell::new(None);
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, ev| {
+                if let Event::GeometryChanged {
+                    position,
+                    size,
+                    flags,
+                } = ev
+                {
+                    geometry.set(Some((position, size, flags)));
+                }
+            },
+        );
+
+        let mut pos = WINDOWPOS {
+            hwnd: window.as_window().hwnd,
+            hwndInsertAfter: 0,
+            x: -5,
+            y: 20,
+            cx: 300,
+            cy: 200,
+            flags: SWP_NOZORDER | SWP_NOACTIVATE,
+        };
+        unsafe { window.send_message(WM_WINDOWPOSCHANGED, 0, &mut pos as *mut WINDOWPOS as isize) };
+        assert_eq!(
+            geometry.get(),
+            Some((
+                Point::new(-5, 20),
+                Size::new(300, 200),
+                WindowPosFlags::NO_Z_ORDER | WindowPosFlags::NO_ACTIVATE
+            ))
+        );
     }
-}
-
-bitflags::bitflags! {
-    /// Flags for the `get_dc` function.
-    pub struct GetDcFlags: u32 {
-        /// Get the window area instead of the client area.
-        const WINDOW_AREA = DCX_WINDOW;
 
-        /// Returns a cached DC no matter what.
-        const CACHE = DCX_CACHE;
+    #[test]
+    fn test_hotkey() {
+        let client = Client::new();
+        let class_name = CString::new("test_hotkey").unwrap();
+        let hotkey = Cell::new(None);
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, ev| {
+                if let Event::HotKey(id) = ev {
+                    hotkey.set(Some(id));
+                }
+            },
+        );
 
-        /// Use the visible region of the parent window.
-        const PARENT_CLIP = DCX_PARENTCLIP;
+        let modifiers = KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT;
+        window
+            .register_hotkey(7, modifiers, VirtualKey::F12)
+            .expect("Failed to register hotkey");
 
-        /// Exclude the visible regions of all sibling windows above the window.
-        const CLIP_SIBLINGS = DCX_CLIPSIBLINGS;
+        // The same keys can't be registered twice.
+        let err = window
+            .register_hotkey(8, modifiers, VirtualKey::F12)
+            .unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::HotKeyAlreadyRegistered);
 
-        /// Exclude the visible regions of all child windows below the window.
-        const CLIP_CHILDREN = DCX_CLIPCHILDREN;
+        unsafe { window.send_message(WM_HOTKEY, 7, 0) };
+        assert_eq!(hotkey.get(), Some(7));
 
-        /// Allows drawing even if there is a LockWindowUpdate call in effect that would otherwise
-        /// exclude this window.
-        const LOCK_WINDOW_UPDATE = DCX_LOCKWINDOWUPDATE;
+        window
+            .unregister_hotkey(7)
+            .expect("Failed to unregister hotkey");
     }
-}
 
-/// The type of region clipping to do for `GetDCEx`.
-pub enum RegionType {
-    /// No clipping.
-    None,
-
-    /// Intersect the region with the visible region.
-    Intersect(Region),
-
-    /// Exclude the region from the visible region.
-    Exclude(Region),
-}
-
-/// The handle to insert the window after.
-#[derive(Debug, Copy, Clone)]
-pub enum InsertAfter<'hwnd> {
-    /// The window to insert before.
-    Window(BorrowedWindow<'hwnd>),
+    #[test]
+    fn test_set_cursor() {
+        let client = Client::new();
+        let class_name = CString::new("test_set_cursor").unwrap();
+        let hit = Cell::new(None);
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, ev| {
+                if let Event::SetCursor { hit_test, reply } = ev {
+                    hit.set(Some(hit_test));
+                    reply.send(hit_test == HitTest::Client);
+                }
+            },
+        );
 
-    /// Place the window at the bottom of the Z order.
-    Bottom,
+        let hwnd = window.hwnd as usize;
+        let result = unsafe { window.send_message(WM_SETCURSOR, hwnd, HTCLIENT as isize) };
+        assert_eq!(hit.get(), Some(HitTest::Client));
+        assert_eq!(result, 1);
 
-    /// Place the window at the top of the Z order.
-    Top,
+        // Negative codes are sign-extended from the low word.
+        let lparam = (HTERROR as u16) as isize;
+        unsafe { window.send_message(WM_SETCURSOR, hwnd, lparam) };
+        assert_eq!(hit.get(), Some(HitTest::Error));
+    }
 
-    /// Place the window above all non-topmost windows.
-    TopMost,
+    #[test]
+    fn test_hit_test() {
+        let client = Client::new();
+        let class_name = CString::new("test_hit_test").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::POPUP,
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(100, 100), Size::new(50, 50)),
+            (),
+            |_, &(), window, ev| {
+                if let Event::HitTest { position, reply } = ev {
+                    // Use the top ten pixels as a custom title bar.
+                    let origin = window.window_rect().origin();
+                    if position.y() - origin.y() < 10 {
+                        reply.send(HitTest::Caption);
+                    }
+                }
+            },
+        );
 
-    //// Place the window above all non-topmost windows without making it topmost.
-    NoTopMost,
-}
+        let lparam = (105 << 16) | 120;
+        let result = unsafe { window.send_message(WM_NCHITTEST, 0, lparam) };
+        assert_eq!(result, HTCAPTION as isize);
 
-bitflags::bitflags! {
-    /// Flags for `SetWindowPos`.
-    pub struct WindowPosFlags : u32 {
-        /// Prevents generation of the WM_SYNCPAINT message.
-        const DEFER_ERASE = SWP_DEFERERASE;
+        // Below the strip, the default hit testing finds the client area.
+        let lparam = (130 << 16) | 120;
+        let result = unsafe { window.send_message(WM_NCHITTEST, 0, lparam) };
+        assert_eq!(result, HTCLIENT as isize);
+    }
 
-        /// Draw a frame around the window.
-        const DRAW_FRAME = SWP_DRAWFRAME;
+    #[test]
+    fn test_double_click() {
+        let client = Client::new();
+        let class_name = CString::new("test_double_click").unwrap();
+        let clicked = Cell::new(None);
+        let class = client
+            .create_class(&class_name)
+            .style(crate::class::Style::DOUBLE_CLICKS)
+            .build(|_, &(), _, ev| {
+                if let Event::MouseDoubleClick {
+                    button,
+                    position,
+                    modifiers,
+                } = ev
+                {
+                    clicked.set(Some((button, position, modifiers)));
+                }
+            })
+            .expect("Failed to create window class");
 
-        /// Applies new frame styles set using the SetWindowLong function.
-        const FRAME_CHANGED = SWP_FRAMECHANGED;
+        let window = client
+            .create_window(
+                &class,
+                &class_name,
+                None,
+                None,
+                WindowStyle::empty(),
+                ExtendedStyle::empty(),
+                Rect::new(Point::new(0, 0), Size::new(10, 10)),
+                (),
+            )
+            .expect("Failed to create window");
 
-        /// Hides the window.
-        const HIDE_WINDOW = SWP_HIDEWINDOW;
+        let lparam = (4 << 16) | 3;
+        unsafe { window.send_message(WM_LBUTTONDBLCLK, MK_CONTROL as usize, lparam) };
 
-        /// Does not activate the window.
-        const NO_ACTIVATE = SWP_NOACTIVATE;
+        let (button, position, modifiers) = clicked.get().expect("No double click");
+        assert_eq!(button, MouseButton::Left);
+        assert_eq!(position, Point::new(3, 4));
+        assert!(modifiers.contains(KeyModifiers::CONTROL));
+        assert!(!modifiers.contains(KeyModifiers::SHIFT));
+    }
 
-        /// Discards the entire contents of the client area.
-        const NO_COPY_BITS = SWP_NOCOPYBITS;
+    #[test]
+    fn test_thread_process_ids() {
+        let client = Client::new();
+        let class_name = CString::new("test_thread_process_ids").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, _| {},
+        );
+
+        let (thread_id, process_id) = window.thread_process_ids().unwrap();
+        assert_eq!(thread_id, unsafe { GetCurrentThreadId() });
+        assert_eq!(process_id, unsafe { GetCurrentProcessId() });
+        assert_eq!(window.thread_id().unwrap(), thread_id);
+        assert_eq!(window.process_id().unwrap(), process_id);
+    }
 
-        /// Do not change the owner window's position in the Z order.
-        const NO_OWNER_Z_ORDER = SWP_NOOWNERZORDER;
+    #[test]
+    fn test_create_window_hidden() {
+        let client = Client::new();
+        let class_name = CString::new("test_create_window_hidden").unwrap();
+        let created_hidden = Cell::new(None);
+        let class = client
+            .create_class(&class_name)
+            .build(|_, &(), window, ev| {
+                if let Event::Created = ev {
+                    created_hidden.set(Some(!window.is_visible()));
+                }
+            })
+            .expect("Failed to create window class");
 
-        /// Do not redraw changes.
-        const NO_REDRAW = SWP_NOREDRAW;
+        let window = client
+            .create_window_hidden(
+                &class,
+                &class_name,
+                None,
+                None,
+                WindowStyle::OVERLAPPED_WINDOW | WindowStyle::VISIBLE,
+                ExtendedStyle::empty(),
+                Rect::new(Point::new(0, 0), Size::new(100, 100)),
+                (),
+            )
+            .expect("Failed to create window");
 
-        /// Do not send the WM_WINDOWPOSCHANGING message to the window being repositioned.
-        const NO_SEND_CHANGING = SWP_NOSENDCHANGING;
+        assert_eq!(created_hidden.get(), Some(true));
+        assert!(!window.is_visible());
+        assert!(!window.style().contains(WindowStyle::VISIBLE));
 
-        /// Display the window.
-        const SHOW_WINDOW = SWP_SHOWWINDOW;
+        window.show(ShowCommand::NORMAL);
+        assert!(window.is_visible());
     }
-}
 
-#[repr(C)]
-pub(crate) struct WindowData<'a, T> {
-    /// Propogate a panic from the window procedure to the main thread.
-    ///
-    /// This comes first in order to allow us to call it, like in a VTable.
-    propagate_panic: fn(*const ()),
+    #[test]
+    fn test_into_raw() {
+        let client = Client::new();
+        let class_name = CString::new("test_into_raw").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, _| {},
+        );
+
+        // Giving up ownership doesn't destroy the window.
+        let hwnd = window.into_raw();
+        assert_ne!(unsafe { IsWindow(hwnd) }, 0);
+        assert_eq!(client.window_count(), 1);
+
+        // Dropping the reclaimed window does.
+        let window = unsafe { Window::<()>::from_raw(hwnd) };
+        drop(window);
+        assert_eq!(unsafe { IsWindow(hwnd) }, 0);
+        assert_eq!(client.window_count(), 0);
+    }
 
-    /// The handle to the window.
-    hwnd: HWND,
+    #[test]
+    fn test_capture() {
+        use crate::bitmap::BitmapInfo;
+        use crate::color::Color;
 
-    /// A queue of messages to be processed.
-    message_queue: RefCell<VecDeque<Event<'static>>>,
+        use core::num::{NonZeroI32, NonZeroU16};
 
-    /// The user data associated with the window.
-    user_data: Box<T>,
+        let client = Client::new();
+        let class_name = CString::new("test_capture").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::OVERLAPPED_WINDOW | WindowStyle::VISIBLE,
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(100, 100)),
+            (),
+            |_, &(), _, ev| {
+                if let Event::EraseBackground { dc, reply } = ev {
+                    dc.clear(Color::rgb(0x12, 0x34, 0x56)).unwrap();
+                    reply.send(true);
+                }
+            },
+        );
+        window.update().expect("Failed to update window");
+
+        let bitmap = window.capture().expect("Failed to capture window");
+        let size = window.client_rect().unwrap().size();
+        assert_eq!(bitmap.dimensions().unwrap(), size);
+
+        // Read the pixels back as top-down 32-bit BGRX.
+        let mut info = BitmapInfo::new(
+            NonZeroI32::new(1).unwrap(),
+            NonZeroI32::new(-1).unwrap(),
+            NonZeroI32::new(1).unwrap(),
+            NonZeroU16::new(1).unwrap(),
+            NonZeroU16::new(32).unwrap(),
+            &[][..],
+        );
+        let dc = window
+            .get_dc(RegionType::None, GetDcFlags::empty())
+            .unwrap();
+        let bits = bitmap.get_bits(&dc, &mut info).expect("Failed to get bits");
+
+        let row = (size.height() / 2) as usize;
+        let column = (size.width() / 2) as usize;
+        let offset = row * info.scanline_width().get() as usize + column * 4;
+        assert_eq!(bits[offset..offset + 3], [0x56, 0x34, 0x12]);
+    }
 
-    /// The class data for the window.
-    class_data: Rc<dyn ErasedClassData<T> + 'a>,
+    #[test]
+    fn test_class_name() {
+        let client = Client::new();
+        let class_name = CString::new("test_class_name").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, _| {},
+        );
+
+        assert_eq!(window.as_window().class_name().unwrap(), class_name);
+
+        // The desktop window belongs to a system class.
+        let desktop = client.desktop_window();
+        assert_eq!(desktop.class_name().unwrap().as_bytes(), b"#32769");
+    }
 
-    /// The re-entrancy count of the current window procedure.
-    rentrancy_count: Cell<Option<NonZeroU32>>,
+    #[test]
+    fn test_layered_opacity() {
+        let client = Client::new();
+        let class_name = CString::new("test_layered_opacity").unwrap();
+        let class = client
+            .create_class(&class_name)
+            .build(|_, &(), _, _| {})
+            .expect("Failed to create window class");
 
-    /// The latest panic that occurred in the window's event loop, if any.
-    #[cfg(feature = "std")]
-    panic: Cell<Option<Box<dyn Any + Send>>>,
-}
+        let rect = Rect::new(Point::new(0, 0), Size::new(100, 100));
+        let mut builder = client.window_builder(rect);
+        builder
+            .style(WindowStyle::OVERLAPPED_WINDOW | WindowStyle::VISIBLE)
+            .extended_style(ExtendedStyle::LAYERED);
 
-// With libstd, we can catch panics to prevent them from hitting the abort guard.
-#[cfg(feature = "std")]
-impl<'a, T> WindowData<'a, T> {
-    /// Propagate a panic if one occurred.
-    pub(crate) fn propagate_panic(&self) {
-        if let Some(panic) = self.panic.take() {
-            std::panic::resume_unwind(panic);
-        }
+        // Layered windows start out opaque rather than undrawn.
+        let window = builder
+            .build(&class, &class_name, ())
+            .expect("Failed to create window");
+        assert_eq!(window.opacity().unwrap(), 255);
+
+        window.set_opacity(128).expect("Failed to set opacity");
+        assert_eq!(window.opacity().unwrap(), 128);
+
+        let window = builder
+            .initial_opacity(64)
+            .build(&class, &class_name, ())
+            .expect("Failed to create window");
+        assert_eq!(window.opacity().unwrap(), 64);
+
+        // create_window leaves the layered attributes unset, for UpdateLayeredWindow.
+        let window = client
+            .create_window(
+                &class,
+                &class_name,
+                None,
+                None,
+                WindowStyle::empty(),
+                ExtendedStyle::LAYERED,
+                rect,
+                (),
+            )
+            .expect("Failed to create window");
+        assert!(window.opacity().is_err());
+
+        // Windows that aren't layered have no opacity.
+        let err = client
+            .window_builder(rect)
+            .initial_opacity(128)
+            .build(&class, &class_name, ())
+            .unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::InvalidParameter);
+
+        let plain = client
+            .window_builder(rect)
+            .build(&class, &class_name, ())
+            .expect("Failed to create window");
+        assert!(plain.set_opacity(128).is_err());
     }
 
-    /// Run code and store the panic if one happened.
-    pub(crate) fn catch_panic<F: FnOnce()>(&self, f: F) {
-        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
-        if let Err(panic) = result {
-            if let Some(other_panic) = self.panic.replace(Some(panic)) {
-                abort!("Two simultaneous panics in the same event procedure.");
-            }
-        }
+    #[test]
+    fn test_as_window_object_safe() {
+        let client = Client::new();
+        let window: &dyn AsWindow = &client.desktop_window();
+        assert!(window.is_visible());
     }
-}
 
-// Without libstd, we can't propagate panics. Just let it hit the abort guard.
-#[cfg(not(feature = "std"))]
-impl<'a, T> WindowData<'a, T> {
-    /// Propagate a panic if one occurred.
-    #[inline]
-    pub(crate) fn propagate_panic(&self) {}
+    #[test]
+    fn test_is_valid() {
+        let client = Client::new();
+        let class_name = CString::new("test_is_valid").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, _| {},
+        );
+
+        let hwnd = window.into_raw();
+        let borrowed = unsafe { BorrowedWindow::from_raw_handle(hwnd) };
+        assert!(borrowed.is_valid());
+
+        drop(unsafe { Window::<()>::from_raw(hwnd) });
+        assert!(!borrowed.is_valid());
+    }
 
-    /// Run code and store the panic if one happened.
-    #[inline]
-    pub(crate) fn catch_panic<F: FnOnce()>(&self, f: F) {
-        f();
+    #[test]
+    fn test_destroy_wrong_thread() {
+        let client = Client::new();
+        let class_name = CString::new("test_destroy_wrong_thread").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, _| {},
+        );
+        let hwnd = window.hwnd;
+        mem::forget(window);
+
+        // Pretend that the window was moved to another thread.
+        let err = std::thread::spawn(move || {
+            let window = Window::<'static, ()> {
+                hwnd,
+                _window_class: PhantomData,
+                _window_data: PhantomData,
+                _thread_unsafe: PhantomData,
+            };
+            window
+                .destroy()
+                .expect_err("Destroyed the window on another thread")
+        })
+        .join()
+        .unwrap();
+        assert_eq!(err.kind(), crate::ErrorKind::AccessDenied);
+
+        // The window is still alive, and can be destroyed from its own thread.
+        assert_ne!(unsafe { IsWindow(hwnd) }, 0);
+        unsafe { DestroyWindow(hwnd) };
     }
-}
 
-impl<'a, T> WindowData<'a, T> {
-    /// Create a new window data.
-    pub(crate) fn new<F: Fn(&Client, &T, BorrowedWindow<'_>, Event<'_>) + 'a>(
-        hwnd: HWND,
-        data: Box<T>,
-        class_data: Rc<ClassData<F>>,
-    ) -> Self {
-        Self {
-            propagate_panic: |ptr| {
-                let data: &WindowData<'_, T> = unsafe { &*(ptr as *const _) };
-                data.propagate_panic();
-            },
-            hwnd,
-            message_queue: RefCell::new(VecDeque::new()),
-            user_data: data,
-            class_data,
-            rentrancy_count: Cell::new(None),
-            #[cfg(feature = "std")]
-            panic: Cell::new(None),
-        }
+    #[test]
+    fn test_create_window_parent_wrong_thread() {
+        let client = Client::new();
+        let class_name = CString::new("test_create_window_parent_wrong_thread").unwrap();
+
+        let (_class, parent) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, _| {},
+        );
+        let parent_hwnd = parent.hwnd;
+
+        let err = std::thread::spawn(move || {
+            let client = Client::new();
+            let class_name = CString::new("test_create_window_parent_wrong_thread_child").unwrap();
+            let class = client
+                .create_class(&class_name)
+                .build(|_, &(), _, _| {})
+                .expect("Failed to create window class");
+
+            client
+                .create_window(
+                    &class,
+                    &class_name,
+                    None,
+                    Some(unsafe { BorrowedWindow::from_raw_handle(parent_hwnd) }),
+                    WindowStyle::CHILD,
+                    ExtendedStyle::empty(),
+                    Rect::new(Point::new(0, 0), Size::new(10, 10)),
+                    (),
+                )
+                .map(|_| ())
+                .expect_err("Created a child of a window on another thread")
+        })
+        .join()
+        .unwrap();
+        assert_eq!(err.code(), ERROR_INVALID_THREAD_ID);
     }
 
-    /// Push a new event.
-    pub(crate) fn push(&self, event: Event<'static>) {
-        self.message_queue.borrow_mut().push_back(event);
+    #[test]
+    fn test_scroll() {
+        let client = Client::new();
+        let class_name = CString::new("test_scroll").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::POPUP | WindowStyle::VISIBLE,
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(50, 50)),
+            (),
+            |_, &(), _, _| {},
+        );
+
+        let update = window
+            .scroll(0, 10, None, None, ScrollFlags::INVALIDATE)
+            .expect("Failed to scroll window");
+
+        // At least the strip at the top was uncovered.
+        let bounds = update.bounds().expect("Failed to get region bounds");
+        assert_eq!(bounds.origin(), Point::new(0, 0));
+        assert!(bounds.size().height() >= 10);
     }
 
-    /// Process all events.
-    fn process(&self) {
-        let mut queue = self.message_queue.borrow_mut();
-        while let Some(event) = queue.pop_front() {
-            self.class_data.run_handler(
-                &self.user_data,
-                unsafe { BorrowedWindow::from_raw_handle(self.hwnd) },
-                event,
+    #[test]
+    fn test_scroll_bar() {
+        use crate::event::ScrollRequest;
+        use windows_sys::Win32::UI::WindowsAndMessaging::{SB_LINEDOWN, SB_THUMBTRACK, WM_VSCROLL};
+
+        let client = Client::new();
+        let class_name = CString::new("test_scroll_bar").unwrap();
+        let received = RefCell::new(Vec::new());
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::OVERLAPPED_WINDOW | WindowStyle::V_SCROLL,
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(100, 100)),
+            (),
+            |_, &(), _, ev| {
+                if let Event::Scroll {
+                    bar,
+                    request,
+                    position,
+                } = ev
+                {
+                    received.borrow_mut().push((bar, request, position));
+                }
+            },
+        );
+
+        window
+            .set_scroll_info(ScrollBar::Vertical, 0, 100, 10, 20)
+            .expect("Failed to set scroll info");
+        let info = window
+            .scroll_info(ScrollBar::Vertical)
+            .expect("Failed to get scroll info");
+        assert_eq!(
+            (info.min, info.max, info.page, info.position),
+            (0, 100, 10, 20)
+        );
+
+        assert!(window
+            .set_scroll_info(ScrollBar::Vertical, 10, 0, 0, 0)
+            .is_err());
+
+        unsafe {
+            SendMessageA(window.hwnd, WM_VSCROLL, SB_LINEDOWN as usize, 0);
+            SendMessageA(
+                window.hwnd,
+                WM_VSCROLL,
+                (SB_THUMBTRACK as usize) | (42 << 16),
+                0,
             );
         }
+        assert_eq!(
+            *received.borrow(),
+            [
+                (ScrollBar::Vertical, ScrollRequest::LineDown, None),
+                (ScrollBar::Vertical, ScrollRequest::ThumbTrack, Some(42)),
+            ]
+        );
     }
 
-    /// Begin a new re-entrancy scope.
-    pub(crate) fn begin(&self) -> impl Drop + '_ {
-        struct CallOnDrop<F: Fn()>(F);
+    #[test]
+    #[cfg(feature = "raw-window-handle-06")]
+    fn test_window_handle_06() {
+        use raw_window_handle_06::{HasWindowHandle, RawWindowHandle};
 
-        impl<F: Fn()> Drop for CallOnDrop<F> {
-            fn drop(&mut self) {
-                (self.0)();
+        let client = Client::new();
+        let class_name = CString::new("test_window_handle_06").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, _| {},
+        );
+
+        let handle = window.window_handle().expect("No window handle");
+        match handle.as_raw() {
+            RawWindowHandle::Win32(handle) => {
+                assert_eq!(handle.hwnd.get(), window.hwnd);
+                assert_eq!(handle.hinstance.map(|h| h.get()), Some(current_module()));
             }
+            _ => panic!("Expected a Win32 window handle"),
         }
+    }
 
-        let guard = CallOnDrop(move || {
-            let current_count = match self.rentrancy_count.get() {
-                Some(count) => count.get(),
-                None => panic!("Re-entrancy count is not set."),
-            };
-
-            match current_count - 1 {
-                0 => {
-                    self.rentrancy_count.set(None);
-                    self.process();
+    #[test]
+    fn test_min_max_info() {
+        let client = Client::new();
+        let class_name = CString::new("test_min_max_info").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::OVERLAPPED_WINDOW,
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(300, 300)),
+            (),
+            |_, &(), _, ev| {
+                if let Event::GetMinMaxInfo(info) = ev {
+                    info.set_min_track_size(Size::new(200, 150));
                 }
-                n => self
-                    .rentrancy_count
-                    .set(Some(unsafe { NonZeroU32::new_unchecked(n) })),
-            }
-        });
+            },
+        );
 
-        // Bump the re-entrancy count.
-        match self.rentrancy_count.get() {
-            Some(count) => self.rentrancy_count.set(Some(unsafe {
-                NonZeroU32::new_unchecked(
-                    count
-                        .get()
-                        .checked_add(1)
-                        .expect("Re-entrancy count overflowed."),
-                )
-            })),
-            None => self
-                .rentrancy_count
-                .set(Some(unsafe { NonZeroU32::new_unchecked(1) })),
-        }
+        // Try to shrink the window below its minimum size.
+        window
+            .set_window_pos(None, None, Some(Size::new(50, 50)), WindowPosFlags::empty())
+            .expect("Failed to resize window");
 
-        guard
+        let size = window.window_rect().size();
+        assert!(size.width() >= 200);
+        assert!(size.height() >= 150);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::class::ClassBuilder;
-    use crate::cstr::CString;
-    use crate::event::Event;
-    use crate::Client;
+    #[test]
+    fn test_set_menu() {
+        let client = Client::new();
+        let class_name = CString::new("test_set_menu").unwrap();
+
+        // Create a window without a menu.
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::OVERLAPPED_WINDOW,
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(100, 100)),
+            (),
+            |_, &(), _, _| {},
+        );
+
+        // Attach a menu to it.
+        let mut menu = Menu::new().unwrap();
+        let first = CString::new("First").unwrap();
+        let second = CString::new("Second").unwrap();
+        menu.push(&mut MenuItem::string(&first)).unwrap();
+        menu.push(&mut MenuItem::string(&second)).unwrap();
+        window.set_menu(Some(menu)).expect("Failed to set menu");
+        assert_ne!(unsafe { GetMenu(window.as_window().handle()) }, 0);
+
+        // Remove it again.
+        window.set_menu(None).expect("Failed to remove menu");
+        assert_eq!(unsafe { GetMenu(window.as_window().handle()) }, 0);
+    }
 
     #[test]
-    fn test_window() {
+    fn test_dpi() {
         let client = Client::new();
-        let class_name = CString::new("test_window_creation").unwrap();
-        let window_title = CString::new("test_window_creation").unwrap();
-        let class = client
-            .create_class(&class_name)
-            .build(|client, &(), _, ev| {
-                if let Event::Created = ev {
-                    client.quit();
-                }
-            })
-            .expect("Failed to create window class");
+        let class_name = CString::new("test_dpi").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, _| {},
+        );
+
+        assert!(window.dpi() >= crate::dpi::DEFAULT_DPI);
+    }
 
-        // Create the window.
-        let _window = client
-            .create_window(
-                &class,
-                &window_title,
-                None,
-                None,
-                WindowStyle::empty(),
-                ExtendedStyle::empty(),
-                Rect::new(Point::new(0, 0), Size::new(1, 1)),
-                (),
-            )
-            .expect("Failed to create window");
+    #[test]
+    fn test_window_monitor() {
+        let client = Client::new();
+        let class_name = CString::new("test_window_monitor").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::empty(),
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(10, 10)),
+            (),
+            |_, &(), _, _| {},
+        );
+
+        let monitors = client.monitors().expect("Failed to enumerate monitors");
+        assert!(monitors.contains(&window.monitor()));
+    }
 
-        // Run the client.
-        crate::reactor::Reactor::new()
-            .expect("to create client")
-            .run()
-            .expect("to run without errors");
+    #[test]
+    fn test_suspend_redraw() {
+        let client = Client::new();
+        let class_name = CString::new("test_suspend_redraw").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::OVERLAPPED_WINDOW | WindowStyle::CLIP_CHILDREN,
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(100, 100)),
+            (),
+            |_, &(), _, _| {},
+        );
+
+        let result = window.suspend_redraw(|| {
+            window
+                .set_window_pos(
+                    None,
+                    None,
+                    Some(Size::new(200, 200)),
+                    WindowPosFlags::empty(),
+                )
+                .expect("Failed to resize window");
+            42
+        });
+        assert_eq!(result, 42);
+        assert_eq!(window.window_rect().size(), Size::new(200, 200));
+    }
+
+    #[test]
+    fn test_set_close_enabled() {
+        let client = Client::new();
+        let class_name = CString::new("test_set_close_enabled").unwrap();
+
+        let (_class, window) = create_test_window(
+            &client,
+            &class_name,
+            WindowStyle::OVERLAPPED_WINDOW,
+            ExtendedStyle::empty(),
+            Rect::new(Point::new(0, 0), Size::new(100, 100)),
+            (),
+            |_, &(), _, _| {},
+        );
+
+        let close_state =
+            || unsafe { GetMenuState(GetSystemMenu(window.hwnd, 0), SC_CLOSE, MF_BYCOMMAND) };
+
+        window
+            .set_close_enabled(false)
+            .expect("Failed to disable close");
+        assert_ne!(close_state() & MF_GRAYED, 0);
+
+        window
+            .set_close_enabled(true)
+            .expect("Failed to enable close");
+        assert_eq!(close_state() & MF_GRAYED, 0);
     }
 }
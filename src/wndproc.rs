@@ -5,27 +5,40 @@
 use crate::abort_on_panic;
 use crate::class::ClassData;
 use crate::client::Client;
-use crate::event::Event;
+use crate::dc::DeviceContext;
+use crate::event::{
+    EndSessionReason, Event, HitTest, MinMaxInfo, MouseButton, Reply, ScrollRequest, SizingEdge,
+};
+use crate::geometry::{point_from_lparam, rect_from_win32, rect_to_win32};
+use crate::keyboard::KeyModifiers;
 use crate::strict;
-use crate::window::{BorrowedWindow, WindowData};
+use crate::window::{BorrowedWindow, ScrollBar, WindowData, WindowPosFlags};
+
+use blood_geometry::{Point, Size};
 
 use alloc::boxed::Box;
 use alloc::collections::VecDeque;
 use alloc::rc::Rc;
 
-use core::cell::RefCell;
+use core::cell::Cell;
 use core::mem::ManuallyDrop;
 
-use windows_sys::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+use windows_sys::Win32::Foundation::{HWND, LPARAM, LRESULT, RECT, WPARAM};
+use windows_sys::Win32::Graphics::Gdi::HDC;
 
-use windows_sys::Win32::UI::WindowsAndMessaging::CREATESTRUCTA;
 use windows_sys::Win32::UI::WindowsAndMessaging::{
-    DefWindowProcA, GetClassLongPtrA, GetWindowLongPtrA, IsWindow, SetWindowLongPtrA,
+    DefWindowProcW, GetClassLongPtrA, GetWindowLongPtrA, IsWindow, SetWindowLongPtrA,
 };
+use windows_sys::Win32::UI::WindowsAndMessaging::{CREATESTRUCTW, MINMAXINFO, WINDOWPOS};
 use windows_sys::Win32::UI::WindowsAndMessaging::{
-    GWLP_USERDATA, WM_CREATE, WM_GETMINMAXINFO, WM_NCCREATE, WM_NCDESTROY,
+    GWLP_USERDATA, WA_INACTIVE, WM_ACTIVATE, WM_ACTIVATEAPP, WM_COMMAND, WM_CONTEXTMENU, WM_CREATE,
+    WM_DESTROY, WM_DPICHANGED, WM_DROPFILES, WM_ENDSESSION, WM_ERASEBKGND, WM_GETMINMAXINFO,
+    WM_HOTKEY, WM_HSCROLL, WM_LBUTTONDBLCLK, WM_MBUTTONDBLCLK, WM_MOVE, WM_MOVING, WM_NCCREATE,
+    WM_NCDESTROY, WM_NCHITTEST, WM_QUERYENDSESSION, WM_RBUTTONDBLCLK, WM_SETCURSOR, WM_SIZING,
+    WM_USER, WM_VSCROLL, WM_WINDOWPOSCHANGED,
 };
 
+use windows_sys::Win32::UI::Controls::{WM_MOUSEHOVER, WM_MOUSELEAVE};
 use windows_sys::Win32::UI::Shell::DefSubclassProc;
 
 /// The real window procedure, parameterized by the event handler.
@@ -89,7 +102,7 @@ fn handle_window_message<'a, T: 'a, F: Fn(&Client, &T, BorrowedWindow<'_>, Event
     let default_handler = if is_subclass {
         DefSubclassProc
     } else {
-        DefWindowProcA
+        DefWindowProcW
     };
 
     tracing::trace!(
@@ -122,12 +135,19 @@ fn handle_window_message<'a, T: 'a, F: Fn(&Client, &T, BorrowedWindow<'_>, Event
     // If the message is WM_NCCREATE, set the user data.
     let window_data = match msg {
         WM_NCCREATE => {
-            let create_struct = strict::reconstitute(lparam).cast::<CREATESTRUCTA>();
+            let create_struct = strict::reconstitute(lparam).cast::<CREATESTRUCTW>();
             debug_assert!(!create_struct.is_null());
             debug_assert!(unsafe { !(*create_struct).lpCreateParams.is_null() });
 
-            // The passed in data will be a Box<T>.
-            let user_data = unsafe { Box::from_raw((*create_struct).lpCreateParams as *mut T) };
+            // The passed in data will be a slot containing a Box<T>.
+            let slot = unsafe { &mut *((*create_struct).lpCreateParams as *mut Option<Box<T>>) };
+            let user_data = match slot.take() {
+                Some(user_data) => user_data,
+                None => {
+                    tracing::error!("WM_NCCREATE received without window data");
+                    return 0;
+                }
+            };
 
             // Create the WindowData structure.
             let window_data = Box::new(WindowData::new(hwnd, user_data, client.clone()));
@@ -137,12 +157,20 @@ fn handle_window_message<'a, T: 'a, F: Fn(&Client, &T, BorrowedWindow<'_>, Event
 
             unsafe { SetWindowLongPtrA(hwnd, GWLP_USERDATA, ptr) };
 
+            // Bump the window count. From here on, WM_NCDESTROY is guaranteed to decrement it.
+            client.client.increment_window_count();
+
             bail_default!();
         }
         WM_NCDESTROY => {
             // If the window is being destroyed, remove the user data.
             let user_data = unsafe { SetWindowLongPtrA(hwnd, GWLP_USERDATA, 0) };
 
+            // If WM_NCCREATE never installed the data, there's nothing to clean up.
+            if user_data == 0 {
+                bail_default!();
+            }
+
             // Drop the boxed data.
             let data = strict::reconstitute(user_data) as *mut WindowData<'a, T>;
             drop(unsafe { Box::from_raw(data) });
@@ -169,25 +197,244 @@ fn handle_window_message<'a, T: 'a, F: Fn(&Client, &T, BorrowedWindow<'_>, Event
     };
 
     // From here on, we can propagate panics.
-    window_data.catch_panic(move || {
+    let result = window_data.catch_panic(move || {
         // Process all events once we aren't running reentrantly.
         let _instance = window_data.begin();
 
         // Useful variables for parsing events.
         let ClassData {
-            client, handler, ..
+            client,
+            handler,
+            raw_events,
+            ..
         } = &**client;
         let bw = unsafe { BorrowedWindow::from_raw_handle(hwnd) };
 
+        // Wake up any futures waiting for this message.
+        crate::message::notify_waiters(hwnd, msg, wparam, lparam);
+
         // Parse the event.
         match msg {
             WM_CREATE => {
                 window_data.push(Event::Created);
             }
+            WM_DESTROY => {
+                // Queued events are dropped along with the window data, so don't queue this.
+                window_data.dispatch(Event::Destroying);
+            }
+            WM_COMMAND if lparam == 0 => {
+                // Menus and accelerators leave the control handle empty.
+                window_data.push(Event::MenuCommand(wparam as u16));
+            }
+            WM_ERASEBKGND => {
+                let dc = unsafe { DeviceContext::borrowed(wparam as HDC) };
+                let erased = Cell::new(None);
+
+                window_data.dispatch(Event::EraseBackground {
+                    dc: &dc,
+                    reply: Reply::new(&erased),
+                });
+
+                if let Some(erased) = erased.get() {
+                    return Some(erased as LRESULT);
+                }
+            }
+            WM_GETMINMAXINFO => {
+                let info = unsafe {
+                    MinMaxInfo::from_ptr(strict::reconstitute(lparam) as *mut MINMAXINFO)
+                };
+                window_data.dispatch(Event::GetMinMaxInfo(info));
+
+                return Some(0);
+            }
+            WM_SIZING | WM_MOVING => {
+                // The proposed rectangle is changed in place.
+                let raw_rect = unsafe { &mut *(strict::reconstitute(lparam) as *mut RECT) };
+                let mut rect = rect_from_win32(*raw_rect);
+
+                if msg == WM_MOVING {
+                    window_data.dispatch(Event::Moving { rect: &mut rect });
+                } else if let Some(edge) = SizingEdge::from_code(wparam as u32) {
+                    window_data.dispatch(Event::Sizing {
+                        edge,
+                        rect: &mut rect,
+                    });
+                }
+
+                *raw_rect = rect_to_win32(rect);
+                return Some(1);
+            }
+            #[cfg(feature = "std")]
+            WM_DROPFILES => {
+                let (paths, position) =
+                    unsafe { crate::drop_files::take_dropped_files(wparam as _) };
+                window_data.push(Event::FilesDropped { paths, position });
+            }
+            WM_QUERYENDSESSION => {
+                let allow = Cell::new(None);
+
+                window_data.dispatch(Event::QueryEndSession {
+                    reason: EndSessionReason::from_bits_truncate(lparam as u32),
+                    reply: Reply::new(&allow),
+                });
+
+                if let Some(allow) = allow.get() {
+                    return Some(allow as LRESULT);
+                }
+            }
+            WM_ENDSESSION => {
+                window_data.push(Event::EndSession {
+                    ending: wparam != 0,
+                });
+            }
+            WM_DPICHANGED => {
+                // The X and Y DPI are always the same.
+                let dpi = (wparam & 0xFFFF) as u32;
+                let suggested_rect =
+                    rect_from_win32(unsafe { *(strict::reconstitute(lparam) as *const RECT) });
+
+                window_data.push(Event::DpiChanged {
+                    dpi,
+                    suggested_rect,
+                });
+            }
+            WM_HSCROLL | WM_VSCROLL => {
+                // Scroll bar controls pass their handle, standard scroll bars don't.
+                let bar = if lparam != 0 {
+                    ScrollBar::Control
+                } else if msg == WM_HSCROLL {
+                    ScrollBar::Horizontal
+                } else {
+                    ScrollBar::Vertical
+                };
+
+                if let Some(request) = ScrollRequest::from_code(wparam as u16) {
+                    let position = if request.has_position() {
+                        Some(i32::from((wparam >> 16) as u16))
+                    } else {
+                        None
+                    };
+
+                    window_data.push(Event::Scroll {
+                        bar,
+                        request,
+                        position,
+                    });
+                }
+            }
+            WM_MOUSELEAVE => {
+                window_data.push(Event::MouseLeft);
+            }
+            WM_MOUSEHOVER => {
+                window_data.push(Event::MouseHover {
+                    position: point_from_lparam(lparam),
+                });
+            }
+            WM_ACTIVATE => {
+                // WA_ACTIVE and WA_CLICKACTIVE both mean the window is active.
+                window_data.push(Event::Activated {
+                    active: (wparam & 0xFFFF) as u32 != WA_INACTIVE,
+                    minimized: (wparam >> 16) & 0xFFFF != 0,
+                });
+            }
+            WM_ACTIVATEAPP => {
+                window_data.push(Event::AppActivated(wparam != 0));
+            }
+            WM_HOTKEY => {
+                window_data.push(Event::HotKey(wparam as i32));
+            }
+            WM_MOVE => {
+                window_data.push(Event::Moved(point_from_lparam(lparam)));
+            }
+            WM_WINDOWPOSCHANGED => {
+                // The default window procedure turns this into WM_SIZE and WM_MOVE.
+                let pos = unsafe { &*(strict::reconstitute(lparam) as *const WINDOWPOS) };
+
+                window_data.push(Event::GeometryChanged {
+                    position: Point::new(pos.x, pos.y),
+                    size: Size::new(pos.cx, pos.cy),
+                    flags: WindowPosFlags::from_bits_truncate(pos.flags),
+                });
+            }
+            WM_LBUTTONDBLCLK | WM_RBUTTONDBLCLK | WM_MBUTTONDBLCLK => {
+                let button = match msg {
+                    WM_LBUTTONDBLCLK => MouseButton::Left,
+                    WM_RBUTTONDBLCLK => MouseButton::Right,
+                    _ => MouseButton::Middle,
+                };
+
+                window_data.push(Event::MouseDoubleClick {
+                    button,
+                    position: point_from_lparam(lparam),
+                    modifiers: KeyModifiers::from_mouse_keys(wparam as u32),
+                });
+            }
+            WM_NCHITTEST => {
+                let hit_test = Cell::new(None);
+
+                window_data.dispatch(Event::HitTest {
+                    position: point_from_lparam(lparam),
+                    reply: Reply::new(&hit_test),
+                });
+
+                if let Some(hit_test) = hit_test.get() {
+                    return Some(hit_test.to_code() as LRESULT);
+                }
+            }
+            WM_SETCURSOR => {
+                if let Some(hit_test) = HitTest::from_code(lparam as i16) {
+                    let handled = Cell::new(None);
+
+                    window_data.dispatch(Event::SetCursor {
+                        hit_test,
+                        reply: Reply::new(&handled),
+                    });
+
+                    if handled.get() == Some(true) {
+                        return Some(1);
+                    }
+                }
+            }
+            WM_CONTEXTMENU => {
+                // The keyboard leaves both coordinates at -1.
+                window_data.push(Event::ContextMenu {
+                    position: point_from_lparam(lparam),
+                    from_keyboard: lparam as u32 == u32::MAX,
+                });
+            }
+            // WM_USER through 0xFFFF are application-defined or registered messages.
+            msg if (WM_USER..=0xFFFF).contains(&msg) => {
+                window_data.push(Event::User {
+                    msg,
+                    wparam,
+                    lparam,
+                });
+            }
+            msg if *raw_events => {
+                let result = Cell::new(None);
+
+                window_data.dispatch(Event::Raw {
+                    msg,
+                    wparam,
+                    lparam,
+                    reply: Reply::new(&result),
+                });
+
+                if let Some(result) = result.get() {
+                    return Some(result);
+                }
+            }
             msg => tracing::debug!("Unhandled message: {:x}", msg),
         }
+
+        None
     });
 
+    // If the handler replied, return its answer.
+    if let Some(Some(result)) = result {
+        return result;
+    }
+
     // By default, just run the default procedure.
     bail_default!();
 }